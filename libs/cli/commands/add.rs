@@ -0,0 +1,75 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+    displayable_id::DisplayableId,
+    time, time_expr,
+};
+use clap::Args;
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+use std::fmt::Display;
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Name of the task
+    task_name: String,
+
+    /// Start of the task, e.g. "14:30", "2h ago" or "yesterday 9am"
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
+    start: u64,
+
+    /// End of the task, e.g. "15:00", "-25m" or "yesterday 10am"
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
+    end: u64,
+
+    /// Name of the project
+    #[clap(short, long)]
+    project: Option<String>,
+
+    /// List of tags
+    #[clap(long, use_value_delimiter = true)]
+    tags: Vec<String>,
+
+    /// Shorten, split or remove the tasks overlapping the new one instead of failing
+    #[clap(long)]
+    trim: bool,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let input = dto::AddTaskInputDto {
+        task_name: command.task_name,
+        project: command.project,
+        tags: command.tags,
+        start: command.start,
+        end: command.end,
+    };
+    let overlap = if command.trim {
+        dto::OverlapStrategyDto::Trim
+    } else {
+        dto::OverlapStrategyDto::Reject
+    };
+
+    let task = proxy.add_task(input, overlap).await?;
+
+    let message = format!("Added task '{}'", task.task_name.cyan().bold());
+    let project_display: Box<dyn Display> = if let Some(p) = &task.project {
+        Box::new(p.cyan())
+    } else {
+        Box::new("<none>".italic())
+    };
+    let tags_display = if !task.tags.is_empty() {
+        Some(task.tags.join(", ").yellow())
+    } else {
+        None
+    };
+    let time_display = time::format_time_period_for_display(task.start, task.end);
+
+    LogBuilder::new(LogType::Success, message)
+        .with_branch("ID", DisplayableId::from(&task))
+        .with_branch("Project", project_display)
+        .with_optional_branch("Tags", tags_display)
+        .with_branch("Time", time_display.dimmed())
+        .print();
+
+    Ok(())
+}
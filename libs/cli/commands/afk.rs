@@ -0,0 +1,76 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+    time,
+};
+use clap::{Args, Subcommand};
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+enum Resolution {
+    /// Count the time spent away in the task
+    Keep,
+    /// Stop the task when you went away
+    Discard,
+    /// Stop the task when you went away and resume it from your return
+    Split,
+}
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Show the pending AFK period when omitted
+    #[command(subcommand)]
+    resolution: Option<Resolution>,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let Some(resolution) = command.resolution else {
+        match proxy.get_pending_afk().await? {
+            Some(period) => {
+                let message = format!(
+                    "Task '{}' kept running while you were away",
+                    period.task_name.cyan()
+                );
+                print_period(LogType::Info, message, &period);
+                log::info!("Answer with `o324 afk keep|discard|split`.");
+            }
+            None => log::info!("No pending AFK period."),
+        }
+        return Ok(());
+    };
+
+    let resolution_dto = match resolution {
+        Resolution::Keep => dto::AfkResolutionDto::Keep,
+        Resolution::Discard => dto::AfkResolutionDto::Discard,
+        Resolution::Split => dto::AfkResolutionDto::Split,
+    };
+
+    match proxy.resolve_afk(resolution_dto).await? {
+        Some(period) => {
+            let message = match resolution {
+                Resolution::Keep => format!("Kept idle time in '{}'", period.task_name.cyan()),
+                Resolution::Discard => {
+                    format!("Stopped '{}' when you went away", period.task_name.cyan())
+                }
+                Resolution::Split => {
+                    format!("Removed idle time from '{}'", period.task_name.cyan())
+                }
+            };
+            print_period(LogType::Success, message, &period);
+        }
+        None => log::info!("No pending AFK period."),
+    }
+
+    Ok(())
+}
+
+fn print_period(log_type: LogType, message: String, period: &dto::AfkPeriodDto) {
+    LogBuilder::new(log_type, message)
+        .with_branch("ID", period.task_id.clone())
+        .with_branch(
+            "Away",
+            time::format_time_period_for_display(period.idle_start, Some(period.idle_end)).dimmed(),
+        )
+        .print();
+}
@@ -0,0 +1,151 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+};
+use chrono::{DateTime, Local};
+use clap::{Args, Subcommand};
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+
+#[derive(Subcommand, Debug)]
+enum Resolution {
+    /// Keep the values of this device
+    Local {
+        /// ID of the conflict
+        conflict_id: String,
+    },
+    /// Keep the values received from the other device
+    Remote {
+        /// ID of the conflict
+        conflict_id: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// List the pending conflicts when omitted
+    #[command(subcommand)]
+    resolution: Option<Resolution>,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let (conflict_id, resolution) = match command.resolution {
+        Some(Resolution::Local { conflict_id }) => (conflict_id, dto::ConflictResolutionDto::Local),
+        Some(Resolution::Remote { conflict_id }) => {
+            (conflict_id, dto::ConflictResolutionDto::Remote)
+        }
+        None => {
+            let conflicts = proxy.list_conflicts().await?;
+            if conflicts.is_empty() {
+                log::info!("No conflict to resolve.");
+                return Ok(());
+            }
+
+            println!(
+                "\n{} {}",
+                "⚠".yellow().bold(),
+                format!(
+                    "{} task(s) were edited at the same time on another device",
+                    conflicts.len()
+                )
+                .yellow()
+                .bold()
+            );
+            for conflict in &conflicts {
+                print_conflict(conflict);
+            }
+            log::info!("Answer with `o324 conflicts local|remote <ID>`.");
+            return Ok(());
+        }
+    };
+
+    let task = proxy.resolve_conflict(conflict_id, resolution).await?;
+    let kept = match resolution {
+        dto::ConflictResolutionDto::Local => "local",
+        dto::ConflictResolutionDto::Remote => "remote",
+    };
+    LogBuilder::new(
+        LogType::Success,
+        format!("Kept the {kept} version of '{}'", task.task_name.cyan()),
+    )
+    .with_branch("ID", task.id_prefix)
+    .print();
+
+    Ok(())
+}
+
+fn print_conflict(conflict: &dto::TaskConflictDto) {
+    println!(
+        "\n  {} {} {}",
+        format!("[{}]", conflict.id).yellow(),
+        conflict.local.task_name.cyan(),
+        format!("({})", conflict.local.id).dimmed()
+    );
+    for field in &conflict.fields {
+        println!("  {} {}", "├─".dimmed(), field.bold());
+        println!(
+            "  {}   {} {}",
+            "│".dimmed(),
+            "local: ".dimmed(),
+            field_value(&conflict.local, field)
+        );
+        println!(
+            "  {}   {} {}",
+            "│".dimmed(),
+            "remote:".dimmed(),
+            field_value(&conflict.remote, field)
+        );
+    }
+    println!(
+        "  {} detected {}",
+        "╰─".dimmed(),
+        format_timestamp(conflict.detected_at)
+    );
+}
+
+/// Displays the value of a task field named as in `TaskConflictDto::fields`.
+fn field_value(task: &dto::TaskActionUpsertDto, field: &str) -> String {
+    let value = match field {
+        "task_name" => Some(task.task_name.clone()),
+        "project" => task.project.clone(),
+        "tags" => (!task.tags.is_empty()).then(|| task.tags.join(", ")),
+        "start" => Some(format_timestamp(task.start)),
+        "end" => task.end.map(format_timestamp),
+        "notes" => task.notes.clone(),
+        _ => return "?".to_string(),
+    };
+    value.unwrap_or_else(|| match field {
+        "end" => "running".to_string(),
+        _ => "none".to_string(),
+    })
+}
+
+fn format_timestamp(timestamp: u64) -> String {
+    DateTime::from_timestamp_millis(timestamp as i64)
+        .map(|datetime| {
+            datetime
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_value() {
+        let task = dto::TaskActionUpsertDto {
+            task_name: "review".to_string(),
+            tags: vec!["rust".to_string(), "sync".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(field_value(&task, "task_name"), "review");
+        assert_eq!(field_value(&task, "tags"), "rust, sync");
+        assert_eq!(field_value(&task, "project"), "none");
+        assert_eq!(field_value(&task, "end"), "running");
+    }
+}
@@ -0,0 +1,96 @@
+use crate::utils::command_error;
+use clap::Args;
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+use std::io::{self, BufRead, Write};
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Fix every issue that can be fixed automatically
+    #[clap(long, conflicts_with = "interactive")]
+    fix: bool,
+
+    /// Ask before fixing each issue
+    #[clap(long, short)]
+    interactive: bool,
+
+    /// Report tasks lasting longer than this number of hours
+    #[clap(long, default_value_t = 12)]
+    max_hours: u64,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let issues = proxy
+        .check_integrity(command.max_hours.saturating_mul(3600))
+        .await?;
+
+    if issues.is_empty() {
+        log::info!("No integrity issue found.");
+        return Ok(());
+    }
+
+    println!(
+        "\n{} {}",
+        "⚠".yellow().bold(),
+        format!("Found {} integrity issue(s)", issues.len())
+            .yellow()
+            .bold()
+    );
+
+    let mut to_fix = Vec::new();
+    for issue in &issues {
+        print_issue(issue);
+
+        if issue.fix.is_some() && (command.fix || (command.interactive && confirm("Fix it?")?)) {
+            to_fix.push(issue.id.clone());
+        }
+    }
+
+    if to_fix.is_empty() {
+        if !command.fix && !command.interactive && issues.iter().any(|i| i.fix.is_some()) {
+            log::info!("Run `o324 doctor --fix` or `o324 doctor -i` to repair them.");
+        }
+        return Ok(());
+    }
+
+    let fixed = proxy.fix_integrity_issues(to_fix).await?;
+    println!(
+        "\n{} {}",
+        "✔".green().bold(),
+        format!(
+            "Fixed {} issue(s), `o324 undo` reverts task fixes",
+            fixed.len()
+        )
+        .green()
+        .bold()
+    );
+
+    Ok(())
+}
+
+fn print_issue(issue: &dto::IntegrityIssueDto) {
+    let kind = match issue.kind {
+        dto::IntegrityIssueKindDto::Overlap => "overlap",
+        dto::IntegrityIssueKindDto::Gap => "gap",
+        dto::IntegrityIssueKindDto::InvertedRange => "inverted range",
+        dto::IntegrityIssueKindDto::LongTask => "long task",
+        dto::IntegrityIssueKindDto::FutureActivity => "future activity",
+        dto::IntegrityIssueKindDto::OrphanPrefix => "orphan prefix",
+    };
+    let fix = match &issue.fix {
+        Some(fix) => fix.normal(),
+        None => "no automatic fix".italic(),
+    };
+
+    println!("\n  {} {}", format!("[{kind}]").yellow(), issue.description);
+    println!("  {} {}", "╰─ Fix:".dimmed(), fix);
+}
+
+fn confirm(question: &str) -> io::Result<bool> {
+    print!("  {} {} ", question.bold(), "[y/N]".dimmed());
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
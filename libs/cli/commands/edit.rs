@@ -3,7 +3,7 @@ use crate::utils::{
     display::{LogBuilder, LogType},
     displayable_id::DisplayableId,
     task_ref::TaskRef,
-    time,
+    time, time_expr,
 };
 use clap::Args;
 use colored::*;
@@ -27,12 +27,12 @@ pub struct Command {
     #[clap(long, use_value_delimiter = true)]
     tags: Option<Vec<String>>,
 
-    /// Start date of the task as unix timestamp
-    #[clap(long, use_value_delimiter = true)]
+    /// Start of the task, e.g. "14:30", "2h ago" or "yesterday 9am"
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
     start: Option<u64>,
 
-    /// End date of the task as unix timestamp
-    #[clap(long, use_value_delimiter = true)]
+    /// End of the task, e.g. "15:00", "-25m" or "yesterday 10am"
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
     end: Option<u64>,
 }
 
@@ -54,6 +54,7 @@ pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_er
         tags: command.tags.into(),
         start: command.start.into(),
         end: command.end.map(Option::Some).into(),
+        notes: None.into(),
     };
 
     let task_g = command.task_ref.get_task(&proxy).await?;
@@ -75,9 +75,13 @@ pub struct Command {
     /// show json output (override the verbose option)
     #[clap(long)]
     json: bool,
+
+    /// show the notes of the tasks
+    #[clap(short, long)]
+    verbose: bool,
 }
 
-pub async fn short_output(tasks: &[dto::TaskDto]) -> eyre::Result<()> {
+pub async fn short_output(tasks: &[dto::TaskDto], verbose: bool) -> eyre::Result<()> {
     if tasks.is_empty() {
         println!("No tasks to show.");
         return Ok(());
@@ -85,7 +89,7 @@ pub async fn short_output(tasks: &[dto::TaskDto]) -> eyre::Result<()> {
 
     let log_structure = build_log_structure(tasks)?;
     if !log_structure.is_empty() {
-        print_log_structure(&log_structure)?;
+        print_log_structure(&log_structure, verbose)?;
     }
 
     Ok(())
@@ -245,7 +249,7 @@ fn colorize_percentage(percentage: i64) -> ColoredString {
 }
 
 /// Prints a log structure to the console with proper formatting.
-fn print_log_structure(log_items: &[TopLevelElem]) -> eyre::Result<()> {
+fn print_log_structure(log_items: &[TopLevelElem], verbose: bool) -> eyre::Result<()> {
     let total_sessions = log_items
         .iter()
         .filter(|item| matches!(item, TopLevelElem::Session(_)))
@@ -438,6 +442,11 @@ fn print_log_structure(log_items: &[TopLevelElem]) -> eyre::Result<()> {
                                 task.task_name,
                                 tags
                             );
+                            if let Some(notes) = task.notes.as_deref().filter(|_| verbose) {
+                                for line in notes.lines() {
+                                    println!("{}  {}", desc_line_prefix, line.italic().dimmed());
+                                }
+                            }
                         }
                     }
                 }
@@ -497,7 +506,7 @@ pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_er
     if command.json {
         json_output(&tasks).await?;
     } else {
-        short_output(&tasks).await?;
+        short_output(&tasks, command.verbose).await?;
     }
 
     Ok(())
@@ -0,0 +1,41 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+    displayable_id::DisplayableId,
+    task_ref::TaskRef,
+    time,
+};
+use clap::Args;
+use colored::*;
+use o324_dbus::proxy::O324ServiceProxy;
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Tasks to merge, e.g. "@2 @last" or id prefixes. The merged task keeps
+    /// the name, project and tags of the first one
+    #[clap(num_args = 2.., required = true)]
+    task_refs: Vec<TaskRef>,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let mut task_ids = Vec::new();
+    for task_ref in &command.task_refs {
+        task_ids.push(task_ref.get_task(&proxy).await?.id);
+    }
+    let merged_count = task_ids.len();
+
+    let task = proxy.merge_tasks(task_ids).await?;
+
+    let message = format!(
+        "Merged {merged_count} tasks into '{}'",
+        task.task_name.cyan().bold()
+    );
+    let time_display = time::format_time_period_for_display(task.start, task.end);
+
+    LogBuilder::new(LogType::Success, message)
+        .with_branch("ID", DisplayableId::from(&task))
+        .with_branch("Time", time_display.dimmed())
+        .print();
+
+    Ok(())
+}
@@ -3,25 +3,41 @@ use o324_dbus::{proxy::O324ServiceProxy, zbus::Connection};
 
 use crate::utils::command_error;
 
+pub mod add;
+pub mod afk;
 pub mod cancel;
+pub mod conflicts;
 pub mod db;
 pub mod delete;
+pub mod doctor;
 pub mod edit;
 pub mod log;
+pub mod merge;
+pub mod note;
 pub mod playground;
+pub mod redo;
 pub mod resume;
+pub mod split;
 pub mod start;
 pub mod stats;
 pub mod status;
 pub mod stop;
+pub mod sync;
+pub mod undo;
+pub mod unlock;
+pub mod watch;
 pub mod activity;
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     ///  Stop any current task and start a new task and and
     Start(start::Command),
+    /// Log a completed task after the fact
+    Add(add::Command),
     /// Display infos on an ongoing task
     Status(status::Command),
+    /// Live view of the running task, updated as it changes
+    Watch(watch::Command),
     /// Stop an ongoing task
     Stop(stop::Command),
     /// Stop and remove a currently running task
@@ -34,8 +50,28 @@ pub enum Command {
     Stats(stats::Command),
     /// Update a task information
     Edit(edit::Command),
+    /// End a task at a given time and continue with a new task from there
+    Split(split::Command),
+    /// Merge tasks into one spanning their time ranges
+    Merge(merge::Command),
+    /// Write the notes of a task in your editor
+    Note(note::Command),
     /// Remove a task
     Delete(delete::Command),
+    /// Revert the last task mutation
+    Undo(undo::Command),
+    /// Reapply the last undone task mutation
+    Redo(redo::Command),
+    /// Decide what to do with the time spent away from a running task
+    Afk(afk::Command),
+    /// Check the tasks and activities for inconsistencies and repair them
+    Doctor(doctor::Command),
+    /// Synchronize tasks with the configured remote
+    Sync(sync::Command),
+    /// Resolve tasks edited at the same time on several devices
+    Conflicts(conflicts::Command),
+    /// Give the passphrase of the encrypted storage to the daemon
+    Unlock(unlock::Command),
     /// Query the database directly; this is mainly use in development
     Db(db::Command),
     /// Shows the user window activity
@@ -80,14 +116,26 @@ impl Command {
 
         match self {
             Self::Start(o) => start::handle(o, proxy).await?,
+            Self::Add(o) => add::handle(o, proxy).await?,
             Self::Stop(o) => stop::handle(o, proxy).await?,
             Self::Cancel(o) => cancel::handle(o, proxy).await?,
             Self::Status(o) => status::handle(o, proxy).await?,
+            Self::Watch(o) => watch::handle(o, proxy).await?,
             Self::Resume(o) => resume::handle(o, proxy).await?,
             Self::Log(o) => log::handle(o, proxy).await?,
             Self::Stats(o) => stats::handle(o, proxy).await?,
             Self::Edit(o) => edit::handle(o, proxy).await?,
+            Self::Split(o) => split::handle(o, proxy).await?,
+            Self::Merge(o) => merge::handle(o, proxy).await?,
+            Self::Note(o) => note::handle(o, proxy).await?,
             Self::Delete(o) => delete::handle(o, proxy).await?,
+            Self::Undo(o) => undo::handle(o, proxy).await?,
+            Self::Redo(o) => redo::handle(o, proxy).await?,
+            Self::Afk(o) => afk::handle(o, proxy).await?,
+            Self::Doctor(o) => doctor::handle(o, proxy).await?,
+            Self::Sync(o) => sync::handle(o, proxy).await?,
+            Self::Conflicts(o) => conflicts::handle(o, proxy).await?,
+            Self::Unlock(o) => unlock::handle(o, proxy).await?,
             Self::Db(o) => db::handle(o, proxy).await?,
             Self::Activity(o) => activity::handle(o, proxy).await?,
             Self::Playground(o) => playground::handle(o, proxy).await?,
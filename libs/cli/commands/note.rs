@@ -0,0 +1,105 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+    displayable_id::DisplayableId,
+    task_ref::TaskRef,
+};
+use clap::Args;
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+use std::io::Write;
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Task to describe, e.g. "@current", "@last", "@2" or an id prefix
+    task_ref: TaskRef,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let task = command.task_ref.get_task(&proxy).await?;
+
+    // Only readable by the user and removed on drop, notes may be private
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("o324-note-{}-", task.id))
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(task.notes.as_deref().unwrap_or_default().as_bytes())?;
+    file.flush()?;
+    open_editor(file.path()).await?;
+
+    let notes = normalize_notes(&std::fs::read_to_string(file.path())?);
+    if notes == task.notes {
+        LogBuilder::new(
+            LogType::Info,
+            format!("Notes of '{}' unchanged", task.task_name.cyan().bold()),
+        )
+        .print();
+        return Ok(());
+    }
+
+    let task_update = dto::TaskUpdateDto {
+        task_name: None.into(),
+        project: None.into(),
+        tags: None.into(),
+        start: None.into(),
+        end: None.into(),
+        notes: Some(notes).into(),
+    };
+    let task = proxy.edit_task(task.id, task_update).await?;
+
+    let message = match &task.notes {
+        Some(_) => format!("Updated the notes of '{}'", task.task_name.cyan().bold()),
+        None => format!("Removed the notes of '{}'", task.task_name.cyan().bold()),
+    };
+    LogBuilder::new(LogType::Success, message)
+        .with_branch("ID", DisplayableId::from(&task))
+        .print();
+
+    Ok(())
+}
+
+/// Opens the file in `$VISUAL` or `$EDITOR`, falling back to `vi`.
+async fn open_editor(path: &std::path::Path) -> eyre::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    // The editor may come with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = tokio::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .await
+        .map_err(|e| eyre::eyre!("Couldn't start the editor '{editor}': {e}"))?;
+
+    if !status.success() {
+        eyre::bail!("The editor '{editor}' exited with {status}, the notes are unchanged");
+    }
+    Ok(())
+}
+
+/// Trims the trailing whitespace left by editors, blank notes being removed.
+fn normalize_notes(content: &str) -> Option<String> {
+    let notes = content.trim_end();
+    let notes = notes.trim_start_matches(['\n', '\r']);
+    (!notes.is_empty()).then(|| notes.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_notes() {
+        assert_eq!(
+            normalize_notes("\n- fixed the parser\n  - added tests\n\n"),
+            Some("- fixed the parser\n  - added tests".to_string())
+        );
+        assert_eq!(normalize_notes(" \n\t\n"), None);
+        assert_eq!(normalize_notes(""), None);
+    }
+}
@@ -0,0 +1,16 @@
+use super::undo::print_history_entry;
+use crate::utils::command_error;
+use clap::Args;
+use o324_dbus::proxy::O324ServiceProxy;
+
+#[derive(Args, Debug)]
+pub struct Command {}
+
+pub async fn handle(_: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    match proxy.redo().await? {
+        Some(entry) => print_history_entry("Redone", entry),
+        None => log::info!("Nothing to redo."),
+    }
+
+    Ok(())
+}
@@ -91,6 +91,7 @@ pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_er
             .tags
             .clone()
             .unwrap_or_else(|| task_to_resume.tags.clone()),
+        at: None,
     };
 
     let task = proxy.start_new_task(start_task_input).await?;
@@ -0,0 +1,74 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+    displayable_id::DisplayableId,
+    task_ref::TaskRef,
+    time, time_expr,
+};
+use clap::Args;
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Task to split, e.g. "@current", "@last", "@2" or an id prefix
+    task_ref: TaskRef,
+
+    /// When the second task starts, e.g. "14:30", "-25m" or "yesterday 10am"
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
+    at: u64,
+
+    /// Name of the second task
+    #[clap(short, long)]
+    name: String,
+
+    /// Project of the second task, the project of the split task by default.
+    /// An empty string leaves the second task without project
+    #[clap(short, long)]
+    project: Option<String>,
+
+    /// List of tags of the second task, the tags of the split task by default
+    #[clap(long, use_value_delimiter = true)]
+    tags: Option<Vec<String>>,
+}
+
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let task = command.task_ref.get_task(&proxy).await?;
+
+    let project = match command.project {
+        Some(project) if project.is_empty() => None,
+        Some(project) => Some(project),
+        None => task.project.clone(),
+    };
+    let input = dto::SplitTaskInputDto {
+        at: command.at,
+        task_name: command.name,
+        project,
+        tags: command.tags.unwrap_or_else(|| task.tags.clone()),
+    };
+
+    let second = proxy.split_task(task.id.clone(), input).await?;
+
+    let message = format!(
+        "Split task '{}' into '{}'",
+        task.task_name.cyan().bold(),
+        second.task_name.cyan().bold()
+    );
+    let first_display = format!(
+        "{} {}",
+        DisplayableId::from(&task),
+        time::format_time_period_for_display(task.start, Some(second.start)).dimmed()
+    );
+    let second_display = format!(
+        "{} {}",
+        DisplayableId::from(&second),
+        time::format_time_period_for_display(second.start, second.end).dimmed()
+    );
+
+    LogBuilder::new(LogType::Success, message)
+        .with_branch("First", first_display)
+        .with_branch("Second", second_display)
+        .print();
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use crate::utils::command_error;
 use crate::utils::display::{LogBuilder, LogType};
 use crate::utils::displayable_id::DisplayableId;
+use crate::utils::time_expr;
 use clap::Args;
 use colored::*;
 use o324_dbus::{dto, proxy::O324ServiceProxy};
@@ -18,6 +19,10 @@ pub struct Command {
     /// List of tags
     #[clap(long, use_value_delimiter = true)]
     tags: Vec<String>,
+
+    /// When the task started, e.g. "-25m" or "9:15", now by default
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
+    at: Option<u64>,
 }
 
 pub fn print_started_task(task: dto::TaskDto) {
@@ -50,6 +55,7 @@ pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_er
             task_name: command.task_name,
             project: command.project,
             tags: command.tags,
+            at: command.at,
         })
         .await?;
     print_started_task(task);
@@ -481,7 +481,7 @@ fn create_category_summary(
             }
         })
         .collect();
-    items.sort_by(|a, b| b.duration_secs.cmp(&a.duration_secs));
+    items.sort_by_key(|b| std::cmp::Reverse(b.duration_secs));
     items
 }
 
@@ -694,7 +694,7 @@ pub fn calculate_date_range_with_default(command: &Command) -> eyre::Result<UtcD
         let today = Local::now().date_naive();
         if command.subcommand.is_some() {
             let end = today;
-            let num_days = (command.date_range.last.clone().max(1) as i64) - 1;
+            let num_days = (command.date_range.last.max(1) as i64) - 1;
             let start = end - Duration::days(num_days);
             (
                 start,
@@ -1,9 +1,11 @@
+use crate::utils::command_error;
 use crate::utils::display::{LogBuilder, LogType};
 use crate::utils::displayable_id::DisplayableId;
-use crate::utils::command_error;
+use crate::utils::status_bar::{self, BarFormat};
 use chrono::{DateTime, Duration, Local, Utc};
 use clap::Args;
 use colored::Colorize;
+use futures_util::StreamExt;
 use o324_dbus::{dto, proxy::O324ServiceProxy};
 use serde::Serialize;
 use std::fmt::Display;
@@ -17,11 +19,23 @@ struct StatusOutput<'a> {
 #[derive(Args, Debug)]
 pub struct Command {
     /// Show json output
-    #[clap(long)]
+    #[clap(long, conflicts_with = "format")]
     json: bool,
+
+    /// Print the status for a status bar
+    #[clap(long, value_enum)]
+    format: Option<BarFormat>,
+
+    /// Keep printing the status bar output whenever it changes
+    #[clap(long, requires = "format")]
+    follow: bool,
 }
 
 pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    if let Some(format) = command.format {
+        return print_bar(format, command.follow, proxy).await;
+    }
+
     let tasks = proxy.list_last_tasks(0, 1).await?;
 
     if let Some(task) = tasks.first().filter(|t| t.end.is_none()) {
@@ -46,7 +60,70 @@ pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_er
     Ok(())
 }
 
-fn format_duration_human(duration: Duration) -> String {
+/// Prints the status bar output, once or on every change of the last task
+/// when following.
+async fn print_bar(
+    format: BarFormat,
+    follow: bool,
+    proxy: O324ServiceProxy<'_>,
+) -> command_error::Result<()> {
+    let now = || Utc::now().timestamp_millis() as u64;
+
+    if !follow {
+        let tasks = proxy.list_last_tasks(0, 1).await?;
+        println!("{}", status_bar::render(format, tasks.first(), now())?);
+        return Ok(());
+    }
+
+    let mut current_task_changes = proxy.receive_current_task_changed().await?;
+    let mut task_changes = proxy.receive_task_changed().await?;
+    let mut task_deletions = proxy.receive_task_deleted().await?;
+
+    let mut last_task = proxy.list_last_tasks(0, 1).await?.into_iter().next();
+    let mut last_output = String::new();
+    // The elapsed time is shown in minutes, a few checks per minute is enough
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(5));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            Some(signal) = current_task_changes.next() => {
+                // A stopped task is also signaled as changed
+                if let Some(task) = signal.args()?.task {
+                    update_last_task(&mut last_task, task);
+                }
+            }
+            Some(signal) = task_changes.next() => {
+                update_last_task(&mut last_task, signal.args()?.task);
+            }
+            Some(signal) = task_deletions.next() => {
+                // Only the deletion of the shown task requires finding the previous one
+                let task_id = signal.args()?.task_id;
+                if last_task.as_ref().is_some_and(|t| t.id == task_id) {
+                    last_task = proxy.list_last_tasks(0, 1).await?.into_iter().next();
+                }
+            }
+        }
+
+        let output = status_bar::render(format, last_task.as_ref(), now())?;
+        if output != last_output {
+            println!("{output}");
+            last_output = output;
+        }
+    }
+}
+
+/// Replaces the last task by a changed task when it is the same one, or a
+/// task started later.
+fn update_last_task(last_task: &mut Option<dto::TaskDto>, task: dto::TaskDto) {
+    let is_last = last_task
+        .as_ref()
+        .is_none_or(|last| last.id == task.id || last.start <= task.start);
+    if is_last {
+        *last_task = Some(task);
+    }
+}
+
+pub(crate) fn format_duration_human(duration: Duration) -> String {
     let secs = duration.num_seconds();
 
     if secs < 60 {
@@ -72,12 +149,15 @@ fn format_duration_human(duration: Duration) -> String {
     parts.join(" ")
 }
 
-fn ms_to_datetime(ms: u64) -> eyre::Result<DateTime<Utc>> {
+pub(crate) fn ms_to_datetime(ms: u64) -> eyre::Result<DateTime<Utc>> {
     DateTime::from_timestamp_millis(ms as i64)
         .ok_or_else(|| eyre::eyre!("Failed to create DateTime from milliseconds: {}", ms))
 }
 
-fn pretty_print_running_task(task: &dto::TaskDto, elapsed: Duration) -> eyre::Result<()> {
+pub(crate) fn pretty_print_running_task(
+    task: &dto::TaskDto,
+    elapsed: Duration,
+) -> eyre::Result<()> {
     let start_time_local = ms_to_datetime(task.start)?.with_timezone(&Local);
     let elapsed_str = format_duration_human(elapsed);
     let display_id = DisplayableId::from(task);
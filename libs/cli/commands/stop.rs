@@ -1,15 +1,19 @@
 use crate::utils::display::{LogBuilder, LogType};
 use crate::utils::displayable_id::DisplayableId;
-use crate::utils::{command_error, time};
+use crate::utils::{command_error, time, time_expr};
 use clap::Args;
 use colored::*;
 use o324_dbus::proxy::O324ServiceProxy;
 
 #[derive(Args, Debug)]
-pub struct Command {}
+pub struct Command {
+    /// When the task stopped, e.g. "-10m" or "17:30", now by default
+    #[clap(long, value_parser = time_expr::parse_timestamp)]
+    at: Option<u64>,
+}
 
-pub async fn handle(_: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
-    let task = proxy.stop_current_task().await?;
+pub async fn handle(command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let task = proxy.stop_current_task(command.at).await?;
 
     match task {
         Some(stopped_task) => {
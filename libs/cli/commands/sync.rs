@@ -0,0 +1,20 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+};
+use clap::Args;
+use colored::*;
+use o324_dbus::proxy::O324ServiceProxy;
+
+#[derive(Args, Debug)]
+pub struct Command {}
+
+pub async fn handle(_: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let imported = proxy.sync().await?;
+
+    LogBuilder::new(LogType::Success, "Synchronization complete")
+        .with_branch("Imported", format!("{imported} change(s)").dimmed())
+        .print();
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+};
+use clap::Args;
+use colored::*;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+
+#[derive(Args, Debug)]
+pub struct Command {}
+
+pub async fn handle(_: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    match proxy.undo().await? {
+        Some(entry) => print_history_entry("Undone", entry),
+        None => log::info!("Nothing to undo."),
+    }
+
+    Ok(())
+}
+
+/// Prints a history entry along with the actions that were applied to the store.
+pub fn print_history_entry(verb: &str, entry: dto::HistoryEntryDto) {
+    let message = format!("{verb}: {}", entry.description.cyan());
+    let mut builder = LogBuilder::new(LogType::Success, message);
+
+    for action in entry.actions {
+        builder = match action.unpack() {
+            dto::TaskActionDto::Upsert(task) => builder.with_branch(
+                "Updated",
+                format!("{} ({})", task.task_name, task.id).dimmed(),
+            ),
+            dto::TaskActionDto::Delete(task_id) => builder.with_branch("Removed", task_id.dimmed()),
+        };
+    }
+
+    builder.print();
+}
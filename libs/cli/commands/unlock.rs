@@ -0,0 +1,33 @@
+use crate::utils::{
+    command_error,
+    display::{LogBuilder, LogType},
+    term::read_passphrase,
+};
+use clap::Args;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+
+#[derive(Args, Debug)]
+pub struct Command {}
+
+pub async fn handle(_: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    let passphrase = match proxy.get_lock_state().await? {
+        dto::LockStateDto::Unlocked => {
+            log::info!("The storage is already unlocked.");
+            return Ok(());
+        }
+        dto::LockStateDto::Locked => read_passphrase("Passphrase: ")?,
+        dto::LockStateDto::Uninitialized => {
+            log::info!("The storage will be encrypted with the passphrase you choose, it can't be recovered if lost.");
+            let passphrase = read_passphrase("New passphrase: ")?;
+            if read_passphrase("Repeat the passphrase: ")? != passphrase {
+                return Err(eyre::eyre!("The passphrases don't match").into());
+            }
+            passphrase
+        }
+    };
+
+    proxy.unlock(passphrase).await?;
+    LogBuilder::new(LogType::Success, "Storage unlocked").print();
+
+    Ok(())
+}
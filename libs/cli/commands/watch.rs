@@ -0,0 +1,277 @@
+use crate::commands::status::{format_duration_human, ms_to_datetime, pretty_print_running_task};
+use crate::utils::command_error;
+use chrono::{Duration, Local, Utc};
+use clap::Args;
+use colored::Colorize;
+use futures_util::StreamExt;
+use o324_dbus::{dto, proxy::O324ServiceProxy};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+/// Granularity of the activity score: a minute counts as active when at
+/// least one window change happened during it.
+const ACTIVITY_BUCKET_MS: u64 = 60_000;
+
+/// Tasks are listed by their start, so the tasks of the day are looked up
+/// from a day before midnight to include the ones running past it.
+const DAY_MS: u64 = 24 * 3_600_000;
+
+#[derive(Args, Debug)]
+pub struct Command {}
+
+/// State of the view, kept up to date from the daemon signals.
+struct WatchState {
+    current_task: Option<dto::TaskDto>,
+    /// Tasks overlapping the current day
+    today_tasks: HashMap<String, dto::TaskDto>,
+    today_start: u64,
+    /// Minutes of the current session with window activity
+    active_minutes: BTreeSet<u64>,
+}
+
+pub async fn handle(_command: Command, proxy: O324ServiceProxy<'_>) -> command_error::Result<()> {
+    // Subscribe before the initial queries so no change is missed in between
+    let mut current_task_changes = proxy.receive_current_task_changed().await?;
+    let mut task_changes = proxy.receive_task_changed().await?;
+    let mut task_deletions = proxy.receive_task_deleted().await?;
+    let mut activities = proxy.receive_activity_recorded().await?;
+
+    let mut state = WatchState {
+        current_task: None,
+        today_tasks: HashMap::new(),
+        today_start: 0,
+        active_minutes: BTreeSet::new(),
+    };
+    let current_task = proxy
+        .list_last_tasks(0, 1)
+        .await?
+        .into_iter()
+        .find(|task| task.end.is_none());
+    state.load_today(&proxy).await?;
+    state.set_current_task(&proxy, current_task).await?;
+
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if state.today_start != today_start() {
+                    state.load_today(&proxy).await?;
+                }
+                state.draw()?;
+            }
+            Some(signal) = current_task_changes.next() => {
+                let task = signal.args()?.task;
+                state.set_current_task(&proxy, task).await?;
+                state.draw()?;
+            }
+            Some(signal) = task_changes.next() => {
+                state.upsert_task(signal.args()?.task);
+                state.draw()?;
+            }
+            Some(signal) = task_deletions.next() => {
+                state.today_tasks.remove(signal.args()?.task_id());
+                state.draw()?;
+            }
+            Some(signal) = activities.next() => {
+                state.record_activity(signal.args()?.activity.at);
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    Ok(())
+}
+
+impl WatchState {
+    async fn load_today(&mut self, proxy: &O324ServiceProxy<'_>) -> eyre::Result<()> {
+        let today_start = today_start();
+        let now = Utc::now().timestamp_millis() as u64;
+        let tasks = proxy
+            .list_task_range(today_start.saturating_sub(DAY_MS), now)
+            .await?;
+        self.reset_today(today_start, tasks);
+        Ok(())
+    }
+
+    /// Replaces the tasks of the day, the running task included.
+    fn reset_today(&mut self, today_start: u64, tasks: Vec<dto::TaskDto>) {
+        self.today_start = today_start;
+        self.today_tasks.clear();
+
+        for task in tasks {
+            self.upsert_task(task);
+        }
+        if let Some(task) = self.current_task.clone() {
+            self.upsert_task(task);
+        }
+    }
+
+    async fn set_current_task(
+        &mut self,
+        proxy: &O324ServiceProxy<'_>,
+        task: Option<dto::TaskDto>,
+    ) -> eyre::Result<()> {
+        let same_session = match (&self.current_task, &task) {
+            (Some(current), Some(task)) => current.id == task.id && current.start == task.start,
+            _ => false,
+        };
+
+        if !same_session {
+            self.active_minutes.clear();
+            if let Some(task) = &task {
+                let now = Utc::now().timestamp_millis() as u64;
+                for activity in proxy.list_activity_range(task.start, now).await? {
+                    self.record_activity(activity.at);
+                }
+            }
+        }
+
+        if let Some(task) = &task {
+            self.upsert_task(task.clone());
+        }
+        self.current_task = task;
+        Ok(())
+    }
+
+    fn upsert_task(&mut self, task: dto::TaskDto) {
+        if task.end.is_none_or(|end| end > self.today_start) {
+            self.today_tasks.insert(task.id.clone(), task);
+        } else {
+            self.today_tasks.remove(&task.id);
+        }
+    }
+
+    fn record_activity(&mut self, at: u64) {
+        if let Some(task) = &self.current_task {
+            if at >= task.start {
+                self.active_minutes.insert(at / ACTIVITY_BUCKET_MS);
+            }
+        }
+    }
+
+    /// Time tracked since the beginning of the day, including the running task.
+    fn today_total(&self, now: u64) -> Duration {
+        let total_ms: u64 = self
+            .today_tasks
+            .values()
+            .map(|task| {
+                let end = task.end.unwrap_or(now).min(now);
+                end.saturating_sub(task.start.max(self.today_start))
+            })
+            .sum();
+        Duration::milliseconds(total_ms as i64)
+    }
+
+    fn draw(&self) -> eyre::Result<()> {
+        let now = Utc::now().timestamp_millis() as u64;
+
+        // Move the cursor home and clear the screen
+        print!("\x1b[H\x1b[2J");
+
+        match &self.current_task {
+            Some(task) => {
+                let elapsed = Utc::now() - ms_to_datetime(task.start)?;
+                pretty_print_running_task(task, elapsed)?;
+            }
+            None => println!("\n{}", "No task is currently running.".dimmed()),
+        }
+
+        println!(
+            "\n  {} {}",
+            "Today:".bold(),
+            format_duration_human(self.today_total(now))
+        );
+        if let Some(task) = &self.current_task {
+            let score = activity_score(&self.active_minutes, task.start, now);
+            println!("  {} {score}%", "Activity:".bold());
+        }
+        println!(
+            "\n{}",
+            format!("Updated at {}", Local::now().format("%H:%M:%S")).dimmed()
+        );
+
+        std::io::stdout().flush()?;
+        Ok(())
+    }
+}
+
+/// Share of the minutes elapsed since `session_start` during which some
+/// window activity was recorded, as a percentage.
+fn activity_score(active_minutes: &BTreeSet<u64>, session_start: u64, now: u64) -> u8 {
+    let first_minute = session_start / ACTIVITY_BUCKET_MS;
+    let last_minute = now / ACTIVITY_BUCKET_MS;
+    let session_minutes = last_minute.saturating_sub(first_minute) + 1;
+    let active = active_minutes.range(first_minute..=last_minute).count() as u64;
+
+    (active * 100 / session_minutes).min(100) as u8
+}
+
+fn today_start() -> u64 {
+    Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or(0, |start| start.timestamp_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_score_counts_active_minutes() {
+        let start = 10 * ACTIVITY_BUCKET_MS;
+        let active: BTreeSet<u64> = [10, 11, 13, 42].into_iter().collect();
+
+        // Minutes 10 to 13, the later activity is out of the session
+        assert_eq!(
+            activity_score(&active, start, start + 3 * ACTIVITY_BUCKET_MS),
+            75
+        );
+        assert_eq!(activity_score(&BTreeSet::new(), start, start), 0);
+        assert_eq!(activity_score(&active, start, start + 30_000), 100);
+    }
+
+    fn task(id: &str, start: u64, end: Option<u64>) -> dto::TaskDto {
+        dto::TaskDto {
+            id: id.to_string(),
+            id_prefix: id.to_string(),
+            task_name: format!("task {id}"),
+            project: None,
+            tags: vec![],
+            computer_name: "laptop".to_string(),
+            start,
+            end,
+            notes: None,
+            project_color_hue: None,
+            __hash: 0,
+        }
+    }
+
+    #[test]
+    fn test_today_total_across_midnight() {
+        let midnight = 10 * DAY_MS;
+        let hour = DAY_MS / 24;
+        let running = task("ccccccc", midnight + hour, None);
+        let mut state = WatchState {
+            current_task: Some(running),
+            today_tasks: HashMap::new(),
+            today_start: 0,
+            active_minutes: BTreeSet::new(),
+        };
+
+        state.reset_today(
+            midnight,
+            vec![
+                // Only the hour after midnight counts
+                task("aaaaaaa", midnight - hour, Some(midnight + hour)),
+                task("bbbbbbb", midnight - 2 * hour, Some(midnight - hour)),
+            ],
+        );
+
+        // The running task is kept across the reload
+        assert_eq!(state.today_total(midnight + 2 * hour), Duration::hours(2));
+    }
+}
@@ -5,4 +5,6 @@ pub mod term;
 pub mod command_error;
 pub mod exit_code;
 pub mod log;
+pub mod status_bar;
 pub mod time;
+pub mod time_expr;
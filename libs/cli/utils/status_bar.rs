@@ -0,0 +1,217 @@
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use o324_dbus::dto::TaskDto;
+use palette::{Clamp, FromColor, Oklch, Srgb};
+use serde::Serialize;
+
+/// Output formats understood by status bars.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarFormat {
+    /// JSON object for a waybar `custom` module with `return-type: json`
+    Waybar,
+    /// Text with polybar format tags
+    Polybar,
+    /// Full text, short text and color lines for i3blocks
+    I3blocks,
+}
+
+/// State of the tracker, also used as CSS class in waybar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarState {
+    /// A task is running
+    Running,
+    /// No task is running, the last task is shown
+    Idle,
+    /// No task was ever tracked
+    None,
+}
+
+impl BarState {
+    fn class(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Idle => "idle",
+            Self::None => "none",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WaybarOutput<'a> {
+    text: String,
+    alt: &'a str,
+    tooltip: String,
+    class: &'a str,
+}
+
+/// Renders the last task for a status bar, `now` being a timestamp in ms.
+pub fn render(format: BarFormat, last_task: Option<&TaskDto>, now: u64) -> eyre::Result<String> {
+    let state = match last_task {
+        Some(task) if task.end.is_none() => BarState::Running,
+        Some(_) => BarState::Idle,
+        None => BarState::None,
+    };
+    let color = last_task
+        .and_then(|task| task.project_color_hue)
+        .filter(|_| state == BarState::Running)
+        .map(hue_to_hex);
+
+    let text = match (state, last_task) {
+        (BarState::Running, Some(task)) => format!(
+            "{} {}",
+            task.task_name,
+            format_bar_duration(now.saturating_sub(task.start))
+        ),
+        _ => "idle".to_string(),
+    };
+
+    let output = match format {
+        BarFormat::Waybar => {
+            let text = match &color {
+                Some(color) => format!("<span color='{color}'>●</span> {}", escape_markup(&text)),
+                None => escape_markup(&text),
+            };
+            let output = WaybarOutput {
+                text,
+                alt: state.class(),
+                tooltip: escape_markup(&tooltip(state, last_task, now)),
+                class: state.class(),
+            };
+            serde_json::to_string(&output)?
+        }
+        BarFormat::Polybar => match &color {
+            Some(color) => format!("%{{F{color}}}●%{{F-}} {}", text.replace('%', "%%")),
+            None => text.replace('%', "%%"),
+        },
+        BarFormat::I3blocks => {
+            let short_text = match (state, last_task) {
+                (BarState::Running, Some(task)) => {
+                    format_bar_duration(now.saturating_sub(task.start))
+                }
+                _ => text.clone(),
+            };
+            match &color {
+                Some(color) => format!("{text}\n{short_text}\n{color}"),
+                None => format!("{text}\n{short_text}"),
+            }
+        }
+    };
+
+    Ok(output)
+}
+
+fn tooltip(state: BarState, last_task: Option<&TaskDto>, now: u64) -> String {
+    let Some(task) = last_task else {
+        return "No task tracked yet".to_string();
+    };
+
+    let mut lines = match state {
+        BarState::Running => vec![
+            format!("Task: {}", task.task_name),
+            format!(
+                "Started: {} ({} ago)",
+                format_local_time(task.start),
+                format_bar_duration(now.saturating_sub(task.start))
+            ),
+        ],
+        _ => vec![format!(
+            "Last task: {} (stopped at {})",
+            task.task_name,
+            format_local_time(task.end.unwrap_or(task.start))
+        )],
+    };
+    if let Some(project) = &task.project {
+        lines.push(format!("Project: {project}"));
+    }
+    if !task.tags.is_empty() {
+        lines.push(format!("Tags: {}", task.tags.join(", ")));
+    }
+
+    lines.join("\n")
+}
+
+/// Compact duration, the bar isn't refreshed often enough to show seconds.
+fn format_bar_duration(ms: u64) -> String {
+    let minutes = ms / 60_000;
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h{:02}m", minutes % 60),
+    }
+}
+
+fn format_local_time(ms: u64) -> String {
+    DateTime::from_timestamp_millis(ms as i64)
+        .map(|dt| dt.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Converts a project hue to a `#rrggbb` color readable on dark and light bars.
+fn hue_to_hex(hue: u32) -> String {
+    let color: Srgb<u8> = Srgb::<f32>::from_color(Oklch::new(0.7, 0.15, hue as f32))
+        .clamp()
+        .into_format();
+    format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(end: Option<u64>) -> TaskDto {
+        TaskDto {
+            id: "abcdef1".to_string(),
+            id_prefix: "a".to_string(),
+            task_name: "review <PR>".to_string(),
+            project: Some("o324".to_string()),
+            tags: vec!["rust".to_string()],
+            start: 0,
+            end,
+            project_color_hue: Some(140),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_waybar_running() {
+        let output = render(BarFormat::Waybar, Some(&task(None)), 65 * 60_000).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["class"], "running");
+        let text = json["text"].as_str().unwrap();
+        assert!(text.starts_with("<span color='#"));
+        assert!(text.ends_with("review &lt;PR&gt; 1h05m"));
+        assert!(json["tooltip"].as_str().unwrap().contains("Project: o324"));
+    }
+
+    #[test]
+    fn test_idle_and_none_states() {
+        let idle = render(BarFormat::Waybar, Some(&task(Some(1_000))), 60_000).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&idle).unwrap();
+        assert_eq!(json["class"], "idle");
+        assert_eq!(json["text"], "idle");
+
+        let none = render(BarFormat::Waybar, None, 0).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&none).unwrap();
+        assert_eq!(json["class"], "none");
+    }
+
+    #[test]
+    fn test_polybar_and_i3blocks() {
+        let running = task(None);
+        let polybar = render(BarFormat::Polybar, Some(&running), 5 * 60_000).unwrap();
+        assert!(polybar.starts_with("%{F#"));
+        assert!(polybar.ends_with("%{F-} review <PR> 5m"));
+
+        let i3blocks = render(BarFormat::I3blocks, Some(&running), 5 * 60_000).unwrap();
+        let lines: Vec<&str> = i3blocks.lines().collect();
+        assert_eq!(lines[0], "review <PR> 5m");
+        assert_eq!(lines[1], "5m");
+        assert!(lines[2].starts_with('#'));
+    }
+}
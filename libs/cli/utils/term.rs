@@ -1,7 +1,7 @@
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::io::{self, Read, Write};
-use termios::{tcsetattr, Termios, CREAD, ECHO, ICANON, TCSANOW, VMIN, VTIME};
+use std::io::{self, BufRead, Read, Write};
+use termios::{tcsetattr, Termios, CREAD, ECHO, ECHONL, ICANON, TCSANOW, VMIN, VTIME};
 
 /// Represents an RGB color with 8-bit channels.
 #[derive(Debug, PartialEq, Eq)]
@@ -23,6 +23,27 @@ impl Drop for RawModeGuard {
     }
 }
 
+/// Prompts for a secret on the terminal, the typed characters not being echoed.
+pub fn read_passphrase(prompt: &str) -> io::Result<String> {
+    let stdin_fd = libc::STDIN_FILENO;
+    let original_termios = Termios::from_fd(stdin_fd)?;
+    let _guard = RawModeGuard { original_termios };
+
+    // Keep the line editing, only hide the input (but not the final newline)
+    let mut hidden_termios = original_termios;
+    hidden_termios.c_lflag &= !ECHO;
+    hidden_termios.c_lflag |= ECHONL;
+    tcsetattr(stdin_fd, TCSANOW, &hidden_termios)?;
+
+    let mut stdout = io::stdout();
+    stdout.write_all(prompt.as_bytes())?;
+    stdout.flush()?;
+
+    let mut passphrase = String::new();
+    io::stdin().lock().read_line(&mut passphrase)?;
+    Ok(passphrase.trim_end_matches(['\r', '\n']).to_string())
+}
+
 /// Queries the terminal for its background color using an OSC 11 sequence.
 ///
 /// Returns `Ok(None)` if the terminal does not respond within the timeout.
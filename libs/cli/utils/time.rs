@@ -2,6 +2,8 @@ use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
 use clap::Args;
 use colored::Colorize;
 
+use crate::utils::time_expr;
+
 pub fn format_time_period_for_display(start: u64, end: Option<u64>) -> String {
     // Use from_timestamp_millis to correctly handle millisecond precision
     let start_dt = DateTime::from_timestamp_millis(start as i64)
@@ -28,49 +30,32 @@ pub fn format_time_period_for_display(start: u64, end: Option<u64>) -> String {
     format!("{} - {}", start_dt.format("%Y-%m-%d %H:%M"), end_display)
 }
 
+/// Parses a bound of a custom period: a day expression, or a month as
+/// `YYYY-MM` standing for its first day, or its last one for the end bound.
 pub fn parse_date_string(date_str: &str, is_end_of_period: bool) -> eyre::Result<NaiveDate> {
-    if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+    if let Some(date) = time_expr::parse_day(date_str, Local::now()) {
         return Ok(date);
     }
-    if let Ok(date) = NaiveDate::parse_from_str(&format!("{date_str}-01"), "%Y-%m-%d") {
-        if is_end_of_period {
-            let next_month_date = if date.month() == 12 {
-                date.with_year(date.year() + 1)
-                    .unwrap()
-                    .with_month(1)
-                    .unwrap()
-            } else {
-                date.with_month(date.month() + 1).unwrap()
-            };
-            return Ok(next_month_date - Duration::days(1));
+    if let Some((first_day, last_day)) = time_expr::parse_month(date_str) {
+        return Ok(if is_end_of_period {
+            last_day
         } else {
-            return Ok(date);
-        }
+            first_day
+        });
     }
     eyre::bail!(
-        "Invalid date format '{}'. Use YYYY-MM-DD or YYYY-MM.",
+        "Invalid date '{}'. Use YYYY-MM-DD, YYYY-MM, today, yesterday, Nd_ago, friday or last friday.",
         date_str
     )
 }
 
 pub fn parse_day_string(day_str: &str) -> eyre::Result<NaiveDate> {
-    let today = Local::now().date_naive();
-    match day_str {
-        "today" => Ok(today),
-        "yesterday" => Ok(today - Duration::days(1)),
-        s if s.ends_with("d_ago") => {
-            let days_ago_str = s.trim_end_matches("d_ago");
-            let days_ago: i64 = days_ago_str.parse()?;
-            Ok(today - Duration::days(days_ago))
-        }
-        s => NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| {
-            eyre::eyre!(
-                "Invalid date format '{}': {}. Use YYYY-MM-DD, today, yesterday, or Nd_ago.",
-                s,
-                e
-            )
-        }),
-    }
+    time_expr::parse_day(day_str, Local::now()).ok_or_else(|| {
+        eyre::eyre!(
+            "Invalid day '{}'. Use YYYY-MM-DD, today, yesterday, Nd_ago, friday or last friday.",
+            day_str
+        )
+    })
 }
 
 #[derive(Clone, Args, Debug)]
@@ -79,11 +64,11 @@ pub struct DateRange {
     #[clap(long, short, global = true, default_value_t = 30)]
     pub last: u64,
 
-    /// Set a custom start date for the stats period (YYYY-MM-DD or YYYY-MM)
+    /// Set a custom start date for the stats period (YYYY-MM-DD, YYYY-MM, yesterday, Nd_ago, last friday)
     #[clap(long, requires = "end")]
     pub start: Option<String>,
 
-    /// Set a custom end date for the stats period (YYYY-MM-DD or YYYY-MM)
+    /// Set a custom end date for the stats period (YYYY-MM-DD, YYYY-MM, today, Nd_ago, friday)
     #[clap(long, requires = "start")]
     pub end: Option<String>,
 
@@ -103,7 +88,7 @@ pub struct DateRange {
     #[clap(long, conflicts_with_all = &["this_week", "last_week", "day", "this_month", "start"])]
     pub last_month: bool,
 
-    /// Show summary for a specific day (YYYY-MM-DD, today, yesterday, Nd_ago)
+    /// Show summary for a specific day (YYYY-MM-DD, today, yesterday, Nd_ago, last friday)
     #[clap(long, short, conflicts_with_all = &["this_week", "last_week", "this_month", "last_month", "start"])]
     pub day: Option<String>,
 }
@@ -0,0 +1,342 @@
+//! Parser for the time expressions accepted by the CLI, e.g. `14:30`, `-25m`,
+//! `yesterday 9am`, `last friday 17:00` or `2h ago`. Expressions are
+//! interpreted in the local timezone.
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Weekday,
+};
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+];
+
+/// Parses a time expression relative to now as a unix timestamp in ms, to be
+/// used as a clap `value_parser`.
+pub fn parse_timestamp(input: &str) -> eyre::Result<u64> {
+    let time = parse_time_expression(input, Local::now())?;
+    u64::try_from(time.timestamp_millis())
+        .map_err(|_| eyre::eyre!("Invalid time '{}', it is before 1970.", input.trim()))
+}
+
+/// Parses a time expression relative to `now`.
+///
+/// An expression is either:
+/// - `now` or a unix timestamp in milliseconds,
+/// - an offset: `-25m`, `+1h30m`, `2h ago`, `in 10 minutes`,
+/// - a day and/or a time of day: `yesterday 9am`, `last friday 17:00`,
+///   `2024-03-01 14:30`, `14:30`. The day defaults to today and the time to
+///   midnight.
+pub fn parse_time_expression(input: &str, now: DateTime<Local>) -> eyre::Result<DateTime<Local>> {
+    let expr = input.trim().to_lowercase();
+    let invalid = || {
+        eyre::eyre!(
+            "Invalid time '{}'. Use e.g. 14:30, -25m, 2h ago, yesterday 9am or last friday 17:00.",
+            input.trim()
+        )
+    };
+
+    if expr == "now" {
+        return Ok(now);
+    }
+    if expr.len() >= 10 && expr.chars().all(|c| c.is_ascii_digit()) {
+        let timestamp = expr.parse::<i64>().map_err(|_| invalid())?;
+        return DateTime::from_timestamp_millis(timestamp)
+            .map(|dt| dt.with_timezone(&Local))
+            .ok_or_else(invalid);
+    }
+
+    if let Some(offset) = parse_offset(&expr) {
+        return now.checked_add_signed(offset).ok_or_else(invalid);
+    }
+
+    let words: Vec<&str> = expr.split_whitespace().collect();
+    let (date, rest) = match words.as_slice() {
+        [] => return Err(invalid()),
+        [first, second, rest @ ..] if parse_day(&format!("{first} {second}"), now).is_some() => {
+            (parse_day(&format!("{first} {second}"), now), rest)
+        }
+        [first, rest @ ..] if parse_day(first, now).is_some() => (parse_day(first, now), rest),
+        rest => (None, rest),
+    };
+    let time = match rest {
+        [] => None,
+        [time] => Some(parse_time_of_day(time).ok_or_else(invalid)?),
+        // "9 am"
+        [time, meridiem] => {
+            Some(parse_time_of_day(&format!("{time}{meridiem}")).ok_or_else(invalid)?)
+        }
+        _ => return Err(invalid()),
+    };
+    if date.is_none() && time.is_none() {
+        return Err(invalid());
+    }
+
+    let date = date.unwrap_or_else(|| now.date_naive());
+    let time = time.unwrap_or(NaiveTime::MIN);
+    to_local(date.and_time(time)).ok_or_else(invalid)
+}
+
+/// Parses a day expression: `today`, `yesterday`, `tomorrow`, `Nd_ago`,
+/// `YYYY-MM-DD`, a weekday (its last occurrence, today included) or
+/// `last`/`next` followed by a weekday.
+pub fn parse_day(input: &str, now: DateTime<Local>) -> Option<NaiveDate> {
+    let today = now.date_naive();
+    let input = input.trim().to_lowercase();
+
+    match input.as_str() {
+        "today" => return Some(today),
+        "yesterday" => return Some(today - Duration::days(1)),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        _ => (),
+    }
+    if let Some(days) = input.strip_suffix("d_ago") {
+        return today.checked_sub_signed(Duration::try_days(days.parse().ok()?)?);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let (modifier, weekday) = match input.split_once(' ') {
+        Some((modifier, weekday)) => (Some(modifier), weekday),
+        None => (None, input.as_str()),
+    };
+    let weekday = parse_weekday(weekday)?;
+    let days_since = (7 + today.weekday().num_days_from_monday() as i64
+        - weekday.num_days_from_monday() as i64)
+        % 7;
+
+    match modifier {
+        None => Some(today - Duration::days(days_since)),
+        Some("last") => Some(today - Duration::days(if days_since == 0 { 7 } else { days_since })),
+        Some("next") => Some(today + Duration::days(7 - days_since)),
+        Some(_) => None,
+    }
+}
+
+/// Parses a month as `YYYY-MM`, returning its first and last days.
+pub fn parse_month(input: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let first_day = NaiveDate::parse_from_str(&format!("{}-01", input.trim()), "%Y-%m-%d").ok()?;
+    let last_day = first_day.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some((first_day, last_day))
+}
+
+fn parse_weekday(input: &str) -> Option<Weekday> {
+    WEEKDAYS
+        .iter()
+        .find(|(name, _)| input.len() >= 3 && name.starts_with(input))
+        .map(|(_, weekday)| *weekday)
+}
+
+/// Parses `-25m`, `+1h30m`, `2h ago`, `25 minutes ago` or `in 10m`.
+fn parse_offset(input: &str) -> Option<Duration> {
+    if let Some(duration) = input.strip_prefix('-') {
+        return parse_duration(duration).map(|d| -d);
+    }
+    if let Some(duration) = input.strip_prefix('+') {
+        return parse_duration(duration);
+    }
+    if let Some(duration) = input.strip_suffix("ago") {
+        return parse_duration(duration).map(|d| -d);
+    }
+    if let Some(duration) = input.strip_prefix("in ") {
+        return parse_duration(duration);
+    }
+    None
+}
+
+/// Parses a duration made of `<number><unit>` parts, e.g. `1h30m` or `2 hours`.
+fn parse_duration(input: &str) -> Option<Duration> {
+    let input: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if input.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::zero();
+    let mut rest = input.as_str();
+    while !rest.is_empty() {
+        let digits_end = rest.find(|c: char| !c.is_ascii_digit())?;
+        if digits_end == 0 {
+            return None;
+        }
+        let value: i64 = rest[..digits_end].parse().ok()?;
+        let value = i32::try_from(value).ok()?;
+        rest = &rest[digits_end..];
+
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_end] {
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::seconds(1),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(1),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(1),
+            "d" | "day" | "days" => Duration::days(1),
+            _ => return None,
+        };
+        total = total.checked_add(&unit.checked_mul(value)?)?;
+        rest = &rest[unit_end..];
+    }
+
+    Some(total)
+}
+
+/// Parses `14:30`, `14:30:15`, `9am`, `9:15pm`, `noon` or `midnight`.
+fn parse_time_of_day(input: &str) -> Option<NaiveTime> {
+    match input {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => (),
+    }
+
+    let (clock, pm) = if let Some(clock) = input.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = input.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (input, None)
+    };
+
+    let mut parts = clock.split(':');
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let second: u32 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    // A bare number is only a time with a meridiem, e.g. "9am"
+    if pm.is_none() && !clock.contains(':') {
+        return None;
+    }
+
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) => hour % 12,
+        Some(true) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// Resolves a local wall-clock time. Ambiguous times (DST fall back) take the
+/// earliest occurrence, skipped times (DST spring forward) are moved past the gap.
+fn to_local(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(dt) => Some(dt),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest),
+        LocalResult::None => (1..=4).find_map(|hours| {
+            Local
+                .from_local_datetime(&naive.checked_add_signed(Duration::minutes(30 * hours))?)
+                .earliest()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday 2024-03-15 at 10:00 local time
+    fn now() -> DateTime<Local> {
+        to_local(
+            NaiveDate::from_ymd_opt(2024, 3, 15)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn local(date: (i32, u32, u32), time: (u32, u32)) -> DateTime<Local> {
+        to_local(
+            NaiveDate::from_ymd_opt(date.0, date.1, date.2)
+                .unwrap()
+                .and_hms_opt(time.0, time.1, 0)
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn parse(input: &str) -> DateTime<Local> {
+        parse_time_expression(input, now()).unwrap()
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        assert_eq!(parse("14:30"), local((2024, 3, 15), (14, 30)));
+        assert_eq!(parse("9am"), local((2024, 3, 15), (9, 0)));
+        assert_eq!(parse("9:15 PM"), local((2024, 3, 15), (21, 15)));
+        assert_eq!(parse("12am"), local((2024, 3, 15), (0, 0)));
+        assert_eq!(parse("noon"), local((2024, 3, 15), (12, 0)));
+    }
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(parse("-25m"), now() - Duration::minutes(25));
+        assert_eq!(parse("+1h30m"), now() + Duration::minutes(90));
+        assert_eq!(parse("2h ago"), now() - Duration::hours(2));
+        assert_eq!(parse("25 minutes ago"), now() - Duration::minutes(25));
+        assert_eq!(parse("in 10m"), now() + Duration::minutes(10));
+        assert_eq!(parse("now"), now());
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(parse("yesterday 9am"), local((2024, 3, 14), (9, 0)));
+        assert_eq!(parse("yesterday"), local((2024, 3, 14), (0, 0)));
+        assert_eq!(parse("last friday 17:00"), local((2024, 3, 8), (17, 0)));
+        assert_eq!(parse("friday 17:00"), local((2024, 3, 15), (17, 0)));
+        assert_eq!(parse("mon 8:00"), local((2024, 3, 11), (8, 0)));
+        assert_eq!(parse("next monday"), local((2024, 3, 18), (0, 0)));
+        assert_eq!(parse("2024-02-29 14:30"), local((2024, 2, 29), (14, 30)));
+        assert_eq!(parse("3d_ago 12:00"), local((2024, 3, 12), (12, 0)));
+    }
+
+    #[test]
+    fn test_months() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            parse_month("2024-02"),
+            Some((day(2024, 2, 1), day(2024, 2, 29)))
+        );
+        assert_eq!(
+            parse_month("2023-12"),
+            Some((day(2023, 12, 1), day(2023, 12, 31)))
+        );
+        assert_eq!(parse_month("2024-02-10"), None);
+        assert_eq!(parse_month("2024-13"), None);
+    }
+
+    #[test]
+    fn test_unix_timestamp() {
+        assert_eq!(parse("1710496800000").timestamp_millis(), 1710496800000);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for input in [
+            "",
+            "later",
+            "25:00",
+            "13pm",
+            "9",
+            "last 9am",
+            "-25x",
+            "friday 9 9",
+            "99999999999999999d_ago",
+            "-9999999999d",
+            "-999999999d",
+            "in 2147483648s",
+        ] {
+            assert!(
+                parse_time_expression(input, now()).is_err(),
+                "'{input}' should be rejected"
+            );
+        }
+    }
+}
@@ -3,15 +3,28 @@ use std::sync::Arc;
 use wrap_builder::wrap_builder;
 
 use crate::{
-    config::Config,
+    config::{self, defs::SyncConfig, Config},
     core::storage::Storage,
     repositories::{
-        activity::ActivityRepository, project_color::ProjectColorRepository, task::TaskRepository,
+        activity::ActivityRepository, idle_period::IdlePeriodRepository,
+        project_color::ProjectColorRepository, sync_cursor::SyncCursorRepository,
+        task::TaskRepository, task_operation::TaskOperationRepository,
         task_prefix::TaskPrefixRepository,
     },
     services::{
-        activity::ActivityService, dbus::DbusService, storage_bridge::StorageBridgeService,
-        task::TaskService,
+        activity::ActivityService,
+        afk::AfkService,
+        dbus::DbusService,
+        doctor::DoctorService,
+        events::EventBus,
+        git_sync::GitSyncService,
+        http_sync::HttpSyncService,
+        logind::LogindService,
+        nudge::{NudgeService, QuietHours},
+        p2p_sync::{auth::PeerKey, P2pSyncService},
+        storage_bridge::StorageBridgeService,
+        task::{import::TaskImporter, TaskService},
+        vault::VaultService,
     },
 };
 
@@ -20,13 +33,21 @@ use crate::{
 pub struct App {
     pub dbus_service: DbusService,
     pub activity_service: ActivityService,
+    pub git_sync_service: Option<GitSyncService>,
+    pub http_sync_service: Option<HttpSyncService>,
+    pub p2p_sync_service: Option<P2pSyncService>,
+    pub logind_service: LogindService,
+    pub nudge_service: Option<NudgeService>,
+    pub vault_service: VaultService,
     pub config: Config,
 }
 
 pub fn build(storage: Storage, config: Config) -> eyre::Result<App> {
+    let profile_config = config.get_current_profile()?;
     let task_repository = TaskRepository::builder()
         .storage(storage.clone())
         .computer_name(config.core.computer_name.clone())
+        .running_task_policy(profile_config.get_running_task_policy())
         .build();
 
     let activity_repository = ActivityRepository::builder()
@@ -34,36 +55,177 @@ pub fn build(storage: Storage, config: Config) -> eyre::Result<App> {
         .computer_name(config.core.computer_name.clone())
         .build();
 
+    let idle_period_repository = IdlePeriodRepository::builder()
+        .storage(storage.clone())
+        .computer_name(config.core.computer_name.clone())
+        .build();
+
     let task_prefix_repository = TaskPrefixRepository::new(storage.clone());
 
+    let task_operation_repository = TaskOperationRepository::builder()
+        .storage(storage.clone())
+        .computer_name(config.core.computer_name.clone())
+        .build();
+
     let project_color_repository = ProjectColorRepository::builder()
         .storage(storage.clone())
         .build();
 
+    let event_bus = EventBus::default();
+
+    let task_importer = TaskImporter::builder()
+        .task_repository(task_repository.clone())
+        .task_prefix_repository(task_prefix_repository.clone())
+        .event_bus(event_bus.clone())
+        .build();
+
+    let git_sync_service = match (&profile_config.sync, profile_config.get_git_working_tree()) {
+        (Some(SyncConfig::Git(git_config)), Some(working_tree)) => Some(
+            GitSyncService::builder()
+                .config(git_config.clone())
+                .working_tree(working_tree)
+                .computer_name(config.core.computer_name.clone())
+                .task_repository(task_repository.clone())
+                .task_importer(task_importer.clone())
+                .build(),
+        ),
+        _ => None,
+    };
+
+    let http_sync_service = match &profile_config.sync {
+        Some(SyncConfig::Server(server_config)) => Some(
+            HttpSyncService::builder()
+                .config(server_config.clone())
+                .computer_name(config.core.computer_name.clone())
+                .task_repository(task_repository.clone())
+                .task_importer(task_importer.clone())
+                .task_operation_repository(task_operation_repository.clone())
+                .sync_cursor_repository(
+                    SyncCursorRepository::builder()
+                        .storage(storage.clone())
+                        .build(),
+                )
+                .build(),
+        ),
+        _ => None,
+    };
+
+    let p2p_sync_service = match &profile_config.sync {
+        Some(SyncConfig::P2p(p2p_config)) => Some(
+            P2pSyncService::builder()
+                .key(PeerKey::derive(&p2p_config.pairing_code, &p2p_config.psk)?)
+                .config(p2p_config.clone())
+                .computer_name(config.core.computer_name.clone())
+                .task_repository(task_repository.clone())
+                .task_importer(task_importer.clone())
+                .task_operation_repository(task_operation_repository.clone())
+                .sync_cursor_repository(
+                    SyncCursorRepository::builder()
+                        .storage(storage.clone())
+                        .build(),
+                )
+                .build(),
+        ),
+        _ => None,
+    };
+
     let task_service = TaskService::builder()
-        .task_repository(task_repository)
-        .task_prefix_repository(task_prefix_repository)
+        .task_repository(task_repository.clone())
+        .task_prefix_repository(task_prefix_repository.clone())
         .project_color_repository(project_color_repository)
+        .git_sync_service(git_sync_service.clone())
+        .http_sync_service(http_sync_service.clone())
+        .p2p_sync_service(p2p_sync_service.clone())
+        .event_bus(event_bus.clone())
+        .build();
+
+    let encrypted = config::is_encrypted(&config, &storage)?;
+    if encrypted
+        && matches!(
+            profile_config.sync,
+            Some(SyncConfig::Git(_) | SyncConfig::Server(_))
+        )
+    {
+        tracing::warn!(
+            "The storage is encrypted, but the synchronization exports the tasks in plain text."
+        );
+    }
+    let vault_service = VaultService::builder()
+        .storage(storage.clone())
+        .encrypted(encrypted)
         .build();
 
     let storage_bridge_service = StorageBridgeService::builder()
         .storage(storage.clone())
         .build();
 
+    let nudge_service = match profile_config.nudge.is_enabled() {
+        true => Some(
+            NudgeService::builder()
+                .task_service(task_service.clone())
+                .threshold(profile_config.nudge.get_threshold())
+                .cooldown(profile_config.nudge.get_cooldown())
+                .quiet_hours(
+                    profile_config
+                        .nudge
+                        .quiet_hours
+                        .as_deref()
+                        .map(str::parse::<QuietHours>)
+                        .transpose()?,
+                )
+                .build(),
+        ),
+        false => None,
+    };
+
+    let afk_service = AfkService::builder()
+        .task_service(task_service.clone())
+        .event_bus(event_bus.clone())
+        .threshold(profile_config.activity.get_afk_threshold())
+        .policy(profile_config.activity.get_afk_policy())
+        .build();
+
     let activity_service = ActivityService::builder()
         .task_service(task_service.clone())
         .activity_repository(activity_repository.clone())
+        .idle_period_repository(idle_period_repository)
+        .idle_timeout(profile_config.activity.get_idle_timeout())
+        .afk_service(afk_service.clone())
+        .event_bus(event_bus.clone())
+        .nudge_service(nudge_service.clone())
+        .build();
+
+    let logind_service = LogindService::builder()
+        .task_service(task_service.clone())
+        .grace_period(profile_config.activity.get_sleep_grace_period())
+        .build();
+
+    let doctor_service = DoctorService::builder()
+        .task_service(task_service.clone())
+        .task_repository(task_repository)
+        .activity_repository(activity_repository.clone())
+        .task_prefix_repository(task_prefix_repository)
         .build();
 
     let dbus_service = DbusService::builder()
         .task_service(task_service.clone())
         .activity_service(activity_service.clone())
+        .afk_service(afk_service)
+        .doctor_service(doctor_service)
         .storage_bridge_service(storage_bridge_service)
+        .vault_service(vault_service.clone())
+        .event_bus(event_bus)
         .build();
 
     Ok(App::builder()
         .dbus_service(dbus_service)
         .activity_service(activity_service)
+        .git_sync_service(git_sync_service)
+        .http_sync_service(http_sync_service)
+        .p2p_sync_service(p2p_sync_service)
+        .logind_service(logind_service)
+        .nudge_service(nudge_service)
+        .vault_service(vault_service)
         .config(config)
         .build())
 }
@@ -0,0 +1,80 @@
+use crate::{
+    config::{self, Config},
+    core::{migration::Migrator, storage::Storage},
+    entities::MIGRATIONS,
+};
+use clap::Args;
+use colored::*;
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Check that the pending migrations apply without saving them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub async fn handle(command: Command, config: Config) -> eyre::Result<()> {
+    let (storage, is_new) = config::open_storage_from_config(&config).map_err(|e| {
+        if Storage::is_already_open(&e) {
+            eyre::eyre!(
+                "{e:#}\n\nThe database can't be migrated while the daemon is running, stop it first."
+            )
+        } else {
+            e
+        }
+    })?;
+    if !is_new && config::is_encrypted(&config, &storage)? {
+        eyre::bail!(
+            "The storage is encrypted, the daemon migrates it once unlocked with `o324 unlock`."
+        );
+    }
+    let migrator = Migrator::new(&storage, &MIGRATIONS);
+
+    if is_new {
+        migrator.stamp()?;
+        println!(
+            "Created a new database at schema version {}.",
+            migrator.latest_version().to_string().bold()
+        );
+        return Ok(());
+    }
+
+    let pending = migrator.pending()?;
+    println!(
+        "Schema version: {} (latest: {})",
+        migrator.stored_version()?.to_string().bold(),
+        migrator.latest_version().to_string().bold()
+    );
+    if pending.is_empty() {
+        println!("{}", "The database is up to date.".green());
+        return Ok(());
+    }
+
+    println!("Pending migrations:");
+    for migration in &pending {
+        println!(
+            "  {} {}",
+            format!("v{}", migration.version).cyan(),
+            migration.description
+        );
+    }
+
+    if command.dry_run {
+        migrator.dry_run()?;
+        println!(
+            "{}",
+            "Dry run: the migrations apply cleanly, nothing was saved.".green()
+        );
+        return Ok(());
+    }
+
+    let report = migrator.upgrade()?;
+    if let Some(backup) = &report.backup {
+        println!("Backup saved to {}", backup.display().to_string().dimmed());
+    }
+    println!(
+        "{}",
+        format!("Migrated to schema version {}.", report.to_version).green()
+    );
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::config::Config;
+use clap::Args;
+use colored::*;
+use rand::Rng;
+
+/// Characters of the pairing code, without the ones easily mistaken for each other.
+const PAIRING_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Args, Debug)]
+pub struct Command {}
+
+pub async fn handle(_: Command, config: Config) -> eyre::Result<()> {
+    let mut rng = rand::rng();
+    let pairing_code = (0..3)
+        .map(|_| {
+            (0..4)
+                .map(|_| {
+                    PAIRING_CODE_ALPHABET[rng.random_range(0..PAIRING_CODE_ALPHABET.len())] as char
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("-");
+    let psk = hex::encode(rng.random::<[u8; 32]>());
+
+    println!(
+        "Add this section to the profile '{}' of every device to pair:\n",
+        config.core.get_default_profile_name().bold()
+    );
+    println!("[profile.{}.sync]", config.core.get_default_profile_name());
+    println!("type = \"p2p\"");
+    println!("pairing_code = \"{pairing_code}\"");
+    println!("psk = \"{psk}\"");
+    println!("listen = \"0.0.0.0:3325\"");
+    println!(
+        "\n{}",
+        "Keep the psk secret, anyone knowing it can read and edit your tasks.".yellow()
+    );
+    Ok(())
+}
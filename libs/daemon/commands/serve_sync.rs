@@ -0,0 +1,67 @@
+use crate::{
+    config::{self, Config},
+    repositories::sync_server::SyncServerRepository,
+    services::sync_server::SyncServerService,
+};
+use clap::Args;
+use std::path::PathBuf;
+use tokio::net::TcpListener;
+
+const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:3324";
+
+#[derive(Args, Debug)]
+pub struct Command {
+    /// Address to listen on (default: "127.0.0.1:3324")
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// Database storing the operations of the devices (default: <storage_location>/sync-server.db)
+    #[arg(long)]
+    storage: Option<String>,
+
+    /// File holding the token the devices must send, which can also be set
+    /// with O324_SYNC_TOKEN (default: none)
+    #[arg(long)]
+    token_file: Option<String>,
+}
+
+pub async fn handle(command: Command, config: Config) -> eyre::Result<()> {
+    let db_path = match command.storage {
+        Some(path) => PathBuf::from(shellexpand::tilde(&path).as_ref()),
+        None => config
+            .get_current_profile()?
+            .get_storage_location()
+            .join("sync-server.db"),
+    };
+    let storage = config::create_storage(&db_path)?;
+
+    // The token isn't taken as an argument, visible to the other users in
+    // the process list
+    let token = match command.token_file {
+        Some(path) => {
+            let path = shellexpand::tilde(&path).to_string();
+            let token = std::fs::read_to_string(&path)
+                .map_err(|e| eyre::eyre!("Couldn't read the token file '{path}': {e}"))?;
+            Some(token.trim().to_string())
+        }
+        None => std::env::var("O324_SYNC_TOKEN").ok(),
+    }
+    .filter(|token| !token.is_empty());
+    if token.is_none() {
+        tracing::warn!(
+            "No token is set, any client reaching the sync server can read and push tasks."
+        );
+    }
+
+    let listen = command.listen.unwrap_or(DEFAULT_LISTEN_ADDRESS.to_string());
+    let listener = TcpListener::bind(&listen)
+        .await
+        .map_err(|e| eyre::eyre!("Couldn't listen on '{listen}': {e}"))?;
+
+    SyncServerService::builder()
+        .repository(SyncServerRepository::builder().storage(storage).build())
+        .token(token)
+        .build()
+        .serve(listener)
+        .await
+}
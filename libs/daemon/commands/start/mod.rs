@@ -32,6 +32,17 @@ pub async fn handle(_: Command, config: Config) -> eyre::Result<()> {
         },
     );
 
+    if app.vault_service.is_locked() {
+        tracing::info!("The storage is locked, waiting for `o324 unlock` to start the services.");
+        tokio::select! {
+            _ = app.vault_service.wait_unlocked() => {},
+            _ = wait_for_shutdown_signal() => {
+                tracing::info!("Shutdown signal received while locked, exiting.");
+                return Ok(());
+            }
+        }
+    }
+
     let _we_handle = supervisor.spawn_supervised_task(
         "ActivityService",
         RetryStrategy::Exponential {
@@ -49,6 +60,104 @@ pub async fn handle(_: Command, config: Config) -> eyre::Result<()> {
         },
     );
 
+    let _idle_handle = supervisor.spawn_supervised_task(
+        "IdleMonitor",
+        RetryStrategy::Exponential {
+            max_attempts: None,
+            initial_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            max_delay: Some(Duration::from_secs(60)),
+        },
+        {
+            let app_cloned = app.clone();
+            move || {
+                let app = app_cloned.clone();
+                async move { app.activity_service.start_idle_monitoring().await }
+            }
+        },
+    );
+
+    let _logind_handle = supervisor.spawn_supervised_task(
+        "LogindService",
+        RetryStrategy::Exponential {
+            max_attempts: None,
+            initial_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            max_delay: Some(Duration::from_secs(60)),
+        },
+        {
+            let app_cloned = app.clone();
+            move || {
+                let app = app_cloned.clone();
+                async move { app.logind_service.serve().await }
+            }
+        },
+    );
+
+    let _nudge_handle = app.nudge_service.clone().map(|nudge_service| {
+        supervisor.spawn_supervised_task(
+            "NudgeService",
+            RetryStrategy::Exponential {
+                max_attempts: None,
+                initial_delay: Duration::from_secs(5),
+                multiplier: 2.0,
+                max_delay: Some(Duration::from_secs(300)),
+            },
+            move || {
+                let nudge_service = nudge_service.clone();
+                async move { nudge_service.serve().await }
+            },
+        )
+    });
+
+    let _git_sync_handle = app.git_sync_service.clone().map(|git_sync_service| {
+        supervisor.spawn_supervised_task(
+            "GitSyncService",
+            RetryStrategy::Exponential {
+                max_attempts: None,
+                initial_delay: Duration::from_secs(5),
+                multiplier: 2.0,
+                max_delay: Some(Duration::from_secs(300)),
+            },
+            move || {
+                let git_sync_service = git_sync_service.clone();
+                async move { git_sync_service.serve().await }
+            },
+        )
+    });
+
+    let _http_sync_handle = app.http_sync_service.clone().map(|http_sync_service| {
+        supervisor.spawn_supervised_task(
+            "HttpSyncService",
+            RetryStrategy::Exponential {
+                max_attempts: None,
+                initial_delay: Duration::from_secs(5),
+                multiplier: 2.0,
+                max_delay: Some(Duration::from_secs(300)),
+            },
+            move || {
+                let http_sync_service = http_sync_service.clone();
+                async move { http_sync_service.serve().await }
+            },
+        )
+    });
+
+    let _p2p_sync_handle = app.p2p_sync_service.clone().map(|p2p_sync_service| {
+        supervisor.spawn_supervised_task(
+            "P2pSyncService",
+            RetryStrategy::Exponential {
+                max_attempts: None,
+                initial_delay: Duration::from_secs(5),
+                multiplier: 2.0,
+                max_delay: Some(Duration::from_secs(300)),
+            },
+            move || {
+                let p2p_sync_service = p2p_sync_service.clone();
+                async move { p2p_sync_service.serve().await }
+            },
+        )
+    });
+
     tracing::info!("All services spawned. Application is running. Press Ctrl-C to exit.");
     wait_for_shutdown_signal().await;
     tracing::info!("Shutdown signal received. Cleaning up services and exiting.");
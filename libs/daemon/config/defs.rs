@@ -34,14 +34,207 @@ pub struct ProfileConfig {
     /// Where the o324 database will be located (default: ~/.local/share/o324/)
     storage_location: Option<String>,
 
-    ///// Desired synchronization method (e.g. git)
-    //pub storage_sync_type: Option<String>,
+    /// Desired synchronization method (e.g. git)
+    pub sync: Option<SyncConfig>,
+
+    /// Activity monitoring settings
+    #[serde(default)]
+    pub activity: ActivityConfig,
+
+    /// Notifications sent when working without a running task
+    #[serde(default)]
+    pub nudge: NudgeConfig,
+
+    /// How many tasks can run at once across synced computers (default: single)
+    pub running_tasks: Option<RunningTaskPolicy>,
+
+    /// Encrypt the stored tasks and activities with a passphrase given
+    /// through `o324 unlock` (default: false). Only this database is
+    /// encrypted: the git repository and the sync server receive the tasks in
+    /// plain text, and p2p peers store them as their own profile does.
+    pub encrypted: Option<bool>,
 
     // Rest of the storage config as a flexible structure
     #[serde(flatten)]
     pub details: toml::Value,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunningTaskPolicy {
+    /// A single task runs across all computers, the older one being stopped
+    /// when tasks started on several computers are synchronized
+    #[default]
+    Single,
+    /// Each computer runs its own task
+    PerComputer,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SyncConfig {
+    /// Synchronize tasks through a git remote
+    Git(GitSyncConfig),
+    /// Synchronize tasks through an `o324-daemon serve-sync` server
+    Server(ServerSyncConfig),
+    /// Synchronize tasks directly with other daemons on the local network
+    P2p(P2pSyncConfig),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GitSyncConfig {
+    /// Url or path of the git remote used for synchronization
+    pub remote: String,
+
+    /// Branch used on the remote (default: "main")
+    pub branch: Option<String>,
+
+    /// Location of the git working tree (default: <storage_location>/git-sync)
+    pub working_tree: Option<String>,
+
+    /// Interval between two automatic synchronizations in seconds (default: 300)
+    pub interval_secs: Option<u64>,
+}
+
+impl GitSyncConfig {
+    pub fn get_branch(&self) -> String {
+        self.branch.clone().unwrap_or("main".to_owned())
+    }
+
+    pub fn get_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.unwrap_or(300))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ServerSyncConfig {
+    /// Url of the sync server, e.g. "http://127.0.0.1:3324"
+    pub url: String,
+
+    /// Token expected by the server, if it was started with one
+    pub token: Option<String>,
+
+    /// Interval between two automatic synchronizations in seconds (default: 60)
+    pub interval_secs: Option<u64>,
+}
+
+impl ServerSyncConfig {
+    pub fn get_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.unwrap_or(60))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct P2pSyncConfig {
+    /// Code shared by the peers synchronizing together, see `o324-daemon pair`
+    pub pairing_code: String,
+
+    /// Key shared by the peers to authenticate each other, at least 32 characters
+    pub psk: String,
+
+    /// Address listened on for the peers, e.g. "0.0.0.0:3325" to be reachable
+    /// from the local network (default: "127.0.0.1:3325")
+    pub listen: Option<String>,
+
+    /// Addresses of peers to synchronize with, e.g. "192.168.1.20:3325"
+    #[serde(default)]
+    pub peers: Vec<String>,
+
+    /// Whether peers are discovered on the local network through mDNS (default: true)
+    pub mdns: Option<bool>,
+
+    /// Interval between two automatic synchronizations in seconds (default: 60)
+    pub interval_secs: Option<u64>,
+}
+
+impl P2pSyncConfig {
+    pub fn get_listen(&self) -> String {
+        self.listen.clone().unwrap_or("127.0.0.1:3325".to_owned())
+    }
+
+    pub fn is_mdns_enabled(&self) -> bool {
+        self.mdns.unwrap_or(true)
+    }
+
+    pub fn get_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_secs.unwrap_or(60))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ActivityConfig {
+    /// Inactivity in seconds after which the user is considered idle (default: 300)
+    pub idle_timeout_secs: Option<u64>,
+
+    /// Sleep duration in seconds after which the running task is stopped at the sleep time (default: 180)
+    pub sleep_grace_period_secs: Option<u64>,
+
+    /// Idle duration in seconds after which the running task is considered AFK (default: 900)
+    pub afk_threshold_secs: Option<u64>,
+
+    /// What to do with the idle time of a running task (default: ask)
+    pub afk_policy: Option<AfkPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AfkPolicy {
+    /// Stop the task when the user went idle and resume it on return
+    Split,
+    /// Stop the task when the user went idle
+    Stop,
+    /// Let the user decide on return with `o324 afk`
+    #[default]
+    Ask,
+}
+
+impl ActivityConfig {
+    pub fn get_idle_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.idle_timeout_secs.unwrap_or(300))
+    }
+
+    pub fn get_sleep_grace_period(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.sleep_grace_period_secs.unwrap_or(180))
+    }
+
+    pub fn get_afk_threshold(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.afk_threshold_secs.unwrap_or(900))
+    }
+
+    pub fn get_afk_policy(&self) -> AfkPolicy {
+        self.afk_policy.unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NudgeConfig {
+    /// Whether nudges are sent at all (default: true)
+    pub enabled: Option<bool>,
+
+    /// Sustained activity in seconds without a running task before a nudge is sent (default: 600)
+    pub threshold_secs: Option<u64>,
+
+    /// Minimum delay in seconds between two nudges (default: 1800)
+    pub cooldown_secs: Option<u64>,
+
+    /// Local time range during which no nudge is sent, e.g. "22:00-08:00"
+    pub quiet_hours: Option<String>,
+}
+
+impl NudgeConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn get_threshold(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.threshold_secs.unwrap_or(600))
+    }
+
+    pub fn get_cooldown(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.cooldown_secs.unwrap_or(1800))
+    }
+}
+
 impl Config {
     /// Gets the current profile based on the `default_profile_name` in the core configuration.
     pub fn get_current_profile(&self) -> eyre::Result<&ProfileConfig> {
@@ -62,6 +255,26 @@ impl ProfileConfig {
         let expanded_path = shellexpand::tilde(path_str);
         PathBuf::from(expanded_path.as_ref())
     }
+
+    pub fn get_running_task_policy(&self) -> RunningTaskPolicy {
+        self.running_tasks.unwrap_or_default()
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted.unwrap_or(false)
+    }
+
+    /// Gets the git working tree location, if git synchronization is enabled.
+    pub fn get_git_working_tree(&self) -> Option<PathBuf> {
+        let Some(SyncConfig::Git(git)) = &self.sync else {
+            return None;
+        };
+
+        Some(match git.working_tree.as_deref() {
+            Some(path_str) => PathBuf::from(shellexpand::tilde(path_str).as_ref()),
+            None => self.get_storage_location().join("git-sync"),
+        })
+    }
 }
 
 impl CoreConfig {
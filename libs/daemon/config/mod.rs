@@ -4,4 +4,6 @@ pub mod storage;
 
 pub use defs::Config;
 pub use load_config::load;
-pub use storage::create_storage_from_config;
+pub use storage::{
+    create_storage, create_storage_from_config, is_encrypted, open_storage_from_config,
+};
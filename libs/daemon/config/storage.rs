@@ -1,12 +1,79 @@
-use crate::{config::Config, core::storage::Storage, entities::MODELS};
+use crate::{
+    config::Config,
+    core::{crypto::read_key_check, migration::Migrator, storage::Storage},
+    entities::{MIGRATIONS, MODELS},
+};
+use eyre::WrapErr;
+use std::path::{Path, PathBuf};
 
+/// Opens the storage of the current profile, upgrading its schema when it
+/// was created by a previous version. The upgrade of an encrypted storage is
+/// left to the vault, the records can't be read until it is unlocked.
 pub fn create_storage_from_config(config: &Config) -> eyre::Result<Storage> {
-    let profile_config = config.get_current_profile()?;
+    let (storage, is_new) = open_storage_from_config(config)?;
+    if is_encrypted(config, &storage)? {
+        storage.lock()?;
+        if !is_new {
+            return Ok(storage);
+        }
+    }
+    stamp_or_upgrade(storage, is_new)
+}
+
+/// Opens the storage at `db_path`, upgrading its schema when it was created
+/// by a previous version.
+pub fn create_storage(db_path: &Path) -> eyre::Result<Storage> {
+    let (storage, is_new) = open_storage(db_path)?;
+    stamp_or_upgrade(storage, is_new)
+}
 
-    let mut db_path = profile_config.get_storage_location().clone();
-    std::fs::create_dir_all(&db_path)?;
-    db_path.push("storage.db");
-    let storage = Storage::try_new(&db_path, &MODELS)
-        .map_err(|e| eyre::eyre!("Couldn't initialize storage on path {db_path:?}: {e}"))?;
+/// Tells whether the records of the storage are encrypted, or are to be once
+/// unlocked. A storage encrypted before stays so when the profile disables it.
+pub fn is_encrypted(config: &Config, storage: &Storage) -> eyre::Result<bool> {
+    Ok(config.get_current_profile()?.is_encrypted() || read_key_check(storage)?.is_some())
+}
+
+fn stamp_or_upgrade(storage: Storage, is_new: bool) -> eyre::Result<Storage> {
+    if is_new {
+        Migrator::new(&storage, &MIGRATIONS).stamp()?;
+    } else {
+        upgrade_storage(&storage)?;
+    }
     Ok(storage)
 }
+
+/// Applies the pending migrations of an existing storage.
+pub fn upgrade_storage(storage: &Storage) -> eyre::Result<()> {
+    let report = Migrator::new(storage, &MIGRATIONS)
+        .upgrade()
+        .map_err(|e| eyre::eyre!("Couldn't migrate storage on path {:?}: {e}", storage.path()))?;
+    if let Some(backup) = &report.backup {
+        tracing::info!(
+            "Migrated storage from schema version {} to {}, backup saved to {backup:?}",
+            report.from_version,
+            report.to_version
+        );
+    }
+    Ok(())
+}
+
+/// Opens the storage of the current profile without migrating it, telling
+/// whether the database was just created.
+pub fn open_storage_from_config(config: &Config) -> eyre::Result<(Storage, bool)> {
+    open_storage(&storage_path_from_config(config)?)
+}
+
+fn storage_path_from_config(config: &Config) -> eyre::Result<PathBuf> {
+    let profile_config = config.get_current_profile()?;
+    Ok(profile_config.get_storage_location().join("storage.db"))
+}
+
+fn open_storage(db_path: &Path) -> eyre::Result<(Storage, bool)> {
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let is_new = !db_path.exists();
+    let storage = Storage::try_new(db_path, &MODELS)
+        .wrap_err_with(|| format!("Couldn't initialize storage on path {db_path:?}"))?;
+    Ok((storage, is_new))
+}
@@ -77,6 +77,7 @@ mod tests {
     #[derive(Clone)]
     struct MockBatchCall<K, V> {
         calls: Arc<Mutex<Vec<Vec<K>>>>,
+        #[allow(clippy::type_complexity)]
         result_generator: Arc<dyn Fn(&[K]) -> eyre::Result<HashMap<K, V>> + Send + Sync>,
     }
 
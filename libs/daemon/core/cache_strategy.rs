@@ -16,6 +16,12 @@ pub trait CacheStrategy<T>: Send + Sync + Clone {
 
     /// Inserts an item into the cache.
     fn insert_into_cache(&self, key: String, value: T);
+
+    /// Evicts an item from the cache.
+    fn remove_from_cache(&self, key: &str);
+
+    /// Evicts every item from the cache.
+    fn clear_cache(&self);
 }
 
 // --- No-Cache Implementation ---
@@ -37,6 +43,12 @@ impl<T: Cacheable> CacheStrategy<T> for NoCache<T> {
 
     #[inline(always)]
     fn insert_into_cache(&self, _key: String, _value: T) {}
+
+    #[inline(always)]
+    fn remove_from_cache(&self, _key: &str) {}
+
+    #[inline(always)]
+    fn clear_cache(&self) {}
 }
 
 // --- In-Memory Cache Implementation ---
@@ -63,4 +75,12 @@ impl<T: Cacheable> CacheStrategy<T> for InMemoryCache<T> {
         //debug!("INSERT {key} {value:?}");
         self.cache.insert(key, value);
     }
+
+    fn remove_from_cache(&self, key: &str) {
+        self.cache.remove(key);
+    }
+
+    fn clear_cache(&self) {
+        self.cache.clear();
+    }
 }
@@ -0,0 +1,349 @@
+//! Encryption of the stored records. The payloads of the sealed models are
+//! encrypted with a key derived from a passphrase, their keys and secondary
+//! keys (ids, timestamps) staying readable so that they can be indexed.
+//!
+//! The records keyed by a name, e.g. project colors, use a keyed digest of
+//! it as key once encrypted, see `blind_key`.
+//!
+//! Each storage holds its key in a `KeySlot`. The codec of the sealed models
+//! can't be given any context, so the storage makes its slot active on the
+//! current thread for the duration of each transaction.
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    XChaCha20Poly1305, XNonce,
+};
+use hmac::{Hmac, Mac};
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    cell::RefCell,
+    sync::{Arc, RwLock},
+};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::core::storage::Storage;
+
+/// Primary key of the `StorageKeyCheck` singleton
+pub const KEY_CHECK_ID: u32 = 0;
+const NONCE_LENGTH: usize = 24;
+const SALT_LENGTH: usize = 16;
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+/// Plaintext sealed to check a passphrase before using the key it derives.
+const KEY_CHECK: &[u8] = b"o324-storage-key";
+/// Label of the digests keying records by a name
+const BLIND_KEY_LABEL: &[u8] = b"o324-blind-key\n";
+/// Label of the digests used as nonces
+const NONCE_LABEL: &[u8] = b"o324-nonce\n";
+
+/// Payload stored without encryption, the storage key not being set
+const PLAIN_PAYLOAD: u8 = 0;
+const SEALED_PAYLOAD: u8 = 1;
+
+thread_local! {
+    /// Key slot of the storage running a transaction on this thread
+    static ACTIVE_SLOT: RefCell<Option<Arc<KeySlot>>> = const { RefCell::new(None) };
+}
+
+/// Salt of the passphrase and a sealed value proving the passphrase is the
+/// right one, kept as a singleton once the storage is encrypted.
+#[native_model(id = 12, version = 1)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StorageKeyCheck {
+    #[primary_key]
+    pub id: u32,
+    pub salt: Vec<u8>,
+    pub check: Vec<u8>,
+}
+
+/// Key derived from the passphrase, erased from memory once dropped.
+#[derive(Clone)]
+pub struct StorageKey([u8; 32]);
+
+impl Drop for StorageKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for StorageKey {}
+
+impl PartialEq for StorageKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl Eq for StorageKey {}
+
+impl StorageKey {
+    pub fn derive(passphrase: &str, salt: &[u8]) -> eyre::Result<Self> {
+        let mut key = Self([0u8; 32]);
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key.0)
+            .map_err(|e| eyre::eyre!("Couldn't derive the storage key: {e}"))?;
+        Ok(key)
+    }
+
+    /// Derives a key from a new passphrase, along with the check to store.
+    pub fn create(passphrase: &str) -> eyre::Result<(Self, StorageKeyCheck)> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            eyre::bail!("The passphrase must be at least {MIN_PASSPHRASE_LENGTH} characters long");
+        }
+        let salt = rand::random::<[u8; SALT_LENGTH]>().to_vec();
+        let key = Self::derive(passphrase, &salt)?;
+        let check = StorageKeyCheck {
+            id: KEY_CHECK_ID,
+            check: key.seal(KEY_CHECK)?,
+            salt,
+        };
+        Ok((key, check))
+    }
+
+    /// Derives the key of a passphrase, failing when it isn't the one the
+    /// check was created with.
+    pub fn verify(passphrase: &str, check: &StorageKeyCheck) -> eyre::Result<Self> {
+        let key = Self::derive(passphrase, &check.salt)?;
+        match key.open(&check.check) {
+            Ok(plaintext) if plaintext == KEY_CHECK => Ok(key),
+            _ => eyre::bail!("Wrong passphrase"),
+        }
+    }
+
+    /// Encrypts `plaintext` with its nonce prepended to the ciphertext. The
+    /// nonce is a digest of the plaintext: native_db compares the encoded
+    /// records to the stored ones when removing or updating them, so a record
+    /// must always be sealed to the same bytes.
+    pub fn seal(&self, plaintext: &[u8]) -> eyre::Result<Vec<u8>> {
+        let digest = self.digest(NONCE_LABEL, plaintext)?;
+        let nonce = &digest[..NONCE_LENGTH];
+        let ciphertext = XChaCha20Poly1305::new((&self.0).into())
+            .encrypt(XNonce::from_slice(nonce), plaintext)
+            .map_err(|_| eyre::eyre!("Couldn't encrypt the payload"))?;
+        Ok([nonce, &ciphertext].concat())
+    }
+
+    /// Keyed digest of `value`, the same for a given key.
+    pub fn blind(&self, value: &str) -> eyre::Result<String> {
+        Ok(hex::encode(self.digest(BLIND_KEY_LABEL, value.as_bytes())?))
+    }
+
+    fn digest(&self, label: &[u8], data: &[u8]) -> eyre::Result<[u8; 32]> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.0)
+            .map_err(|e| eyre::eyre!("Invalid HMAC key: {e}"))?;
+        mac.update(label);
+        mac.update(data);
+        Ok(mac.finalize().into_bytes().into())
+    }
+
+    pub fn open(&self, sealed: &[u8]) -> eyre::Result<Vec<u8>> {
+        if sealed.len() < NONCE_LENGTH {
+            eyre::bail!("The encrypted payload is truncated");
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+        XChaCha20Poly1305::new((&self.0).into())
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| eyre::eyre!("Couldn't decrypt the payload, the key is wrong"))
+    }
+}
+
+/// Reads the check of the storage, `None` when it was never encrypted.
+pub fn read_key_check(storage: &Storage) -> eyre::Result<Option<StorageKeyCheck>> {
+    storage.read_txn(|txn| Ok(txn.get().primary(KEY_CHECK_ID)?))
+}
+
+#[derive(Default)]
+enum KeyState {
+    /// The records of an unencrypted profile are stored as is
+    #[default]
+    Plain,
+    /// The profile is encrypted and waits for its key
+    Locked,
+    Unlocked(StorageKey),
+}
+
+/// Key used by the sealed models of a storage, shared by its clones.
+#[derive(Default)]
+pub struct KeySlot(RwLock<KeyState>);
+
+impl KeySlot {
+    /// Refuses to store the sealed models until a key is installed.
+    pub fn lock(&self) -> eyre::Result<()> {
+        let mut state = self.write()?;
+        if matches!(*state, KeyState::Plain) {
+            *state = KeyState::Locked;
+        }
+        Ok(())
+    }
+
+    /// Sets the key used by the sealed models. It can't be replaced by
+    /// another one since the records already sealed couldn't be read anymore.
+    pub fn install(&self, key: StorageKey) -> eyre::Result<()> {
+        let mut state = self.write()?;
+        match &*state {
+            KeyState::Unlocked(installed) if *installed != key => {
+                eyre::bail!("Another storage key is already in use")
+            }
+            _ => *state = KeyState::Unlocked(key),
+        }
+        Ok(())
+    }
+
+    /// Drops the key, e.g. when the records couldn't be sealed with it.
+    pub fn forget(&self) -> eyre::Result<()> {
+        *self.write()? = KeyState::Locked;
+        Ok(())
+    }
+
+    /// Makes the slot the one of the sealed models encoded or decoded on this
+    /// thread, until the returned scope is dropped.
+    pub fn enter(self: &Arc<Self>) -> SlotScope {
+        let previous = ACTIVE_SLOT.with(|active| active.replace(Some(self.clone())));
+        SlotScope(previous)
+    }
+
+    fn write(&self) -> eyre::Result<std::sync::RwLockWriteGuard<'_, KeyState>> {
+        self.0
+            .write()
+            .map_err(|_| eyre::eyre!("The storage key lock is poisoned"))
+    }
+}
+
+/// Restores the slot active before `KeySlot::enter` once dropped.
+pub struct SlotScope(Option<Arc<KeySlot>>);
+
+impl Drop for SlotScope {
+    fn drop(&mut self) {
+        ACTIVE_SLOT.with(|active| *active.borrow_mut() = self.0.take());
+    }
+}
+
+/// Runs `f` with the key state of the active slot, failing outside of a
+/// storage transaction.
+fn with_active_key<R>(
+    f: impl FnOnce(&KeyState) -> Result<R, SealedError>,
+) -> Result<R, SealedError> {
+    let slot = ACTIVE_SLOT
+        .with(|active| active.borrow().clone())
+        .ok_or(SealedError::Locked)?;
+    let state = slot.0.read().map_err(|_| SealedError::Locked)?;
+    f(&state)
+}
+
+/// Key of a record standing for `value`, which can't be read back without
+/// the storage key. It's the value itself when the profile isn't encrypted.
+pub fn blind_key(value: &str) -> Result<String, SealedError> {
+    with_active_key(|state| match state {
+        KeyState::Plain => Ok(value.to_string()),
+        KeyState::Locked => Err(SealedError::Locked),
+        KeyState::Unlocked(key) => key
+            .blind(value)
+            .map_err(|e| SealedError::Crypto(e.to_string())),
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SealedError {
+    #[error("The storage is locked, run `o324 unlock` first")]
+    Locked,
+    #[error("Unknown payload format {0}")]
+    UnknownFormat(u8),
+    #[error("{0}")]
+    Crypto(String),
+    #[error(transparent)]
+    Encode(#[from] bincode::error::EncodeError),
+    #[error(transparent)]
+    Decode(#[from] bincode::error::DecodeError),
+}
+
+/// Codec of the sealed models: the payload is encrypted with the key of the
+/// storage, and stored as is when the profile isn't encrypted.
+pub struct Sealed;
+
+impl<T: Serialize> native_model::Encode<T> for Sealed {
+    type Error = SealedError;
+
+    fn encode(obj: &T) -> Result<Vec<u8>, SealedError> {
+        let payload = bincode::serde::encode_to_vec(obj, bincode::config::standard())?;
+        with_active_key(|state| match state {
+            KeyState::Plain => frame(&payload, None),
+            KeyState::Locked => Err(SealedError::Locked),
+            KeyState::Unlocked(key) => frame(&payload, Some(key)),
+        })
+    }
+}
+
+impl<T: for<'de> Deserialize<'de>> native_model::Decode<T> for Sealed {
+    type Error = SealedError;
+
+    fn decode(data: Vec<u8>) -> Result<T, SealedError> {
+        let payload = with_active_key(|state| match state {
+            KeyState::Unlocked(key) => unframe(&data, Some(key)),
+            KeyState::Plain | KeyState::Locked => unframe(&data, None),
+        })?;
+        Ok(bincode::serde::decode_from_slice(&payload, bincode::config::standard())?.0)
+    }
+}
+
+fn frame(payload: &[u8], key: Option<&StorageKey>) -> Result<Vec<u8>, SealedError> {
+    match key {
+        Some(key) => {
+            let sealed = key
+                .seal(payload)
+                .map_err(|e| SealedError::Crypto(e.to_string()))?;
+            Ok([&[SEALED_PAYLOAD], sealed.as_slice()].concat())
+        }
+        None => Ok([&[PLAIN_PAYLOAD], payload].concat()),
+    }
+}
+
+fn unframe(data: &[u8], key: Option<&StorageKey>) -> Result<Vec<u8>, SealedError> {
+    match data.split_first() {
+        Some((&PLAIN_PAYLOAD, payload)) => Ok(payload.to_vec()),
+        Some((&SEALED_PAYLOAD, sealed)) => key
+            .ok_or(SealedError::Locked)?
+            .open(sealed)
+            .map_err(|e| SealedError::Crypto(e.to_string())),
+        Some((format, _)) => Err(SealedError::UnknownFormat(*format)),
+        None => Err(SealedError::UnknownFormat(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_roundtrip() -> eyre::Result<()> {
+        let key = StorageKey::derive("correct horse", b"0123456789abcdef")?;
+
+        let sealed = frame(b"review", Some(&key))?;
+        assert_eq!(sealed[0], SEALED_PAYLOAD);
+        assert!(!sealed.windows(6).any(|window| window == b"review"));
+        // Sealed to the same bytes, to be removed from the storage
+        assert_eq!(frame(b"review", Some(&key))?, sealed);
+        assert_eq!(unframe(&sealed, Some(&key))?, b"review");
+        assert!(matches!(unframe(&sealed, None), Err(SealedError::Locked)));
+
+        // Records stored before the key was set stay readable
+        let plain = frame(b"review", None)?;
+        assert_eq!(unframe(&plain, Some(&key))?, b"review");
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_passphrase() -> eyre::Result<()> {
+        let (key, check) = StorageKey::create("correct horse")?;
+
+        assert!(StorageKey::verify("correct horse", &check)? == key);
+        assert!(StorageKey::verify("wrong horse", &check).is_err());
+        assert!(StorageKey::create("short").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+use native_db::transaction::RwTransaction;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    core::{storage::Storage, utils::unix_now},
+    entities::schema_version::SchemaVersion,
+};
+
+/// Schema version of databases created before migrations were tracked.
+pub const INITIAL_SCHEMA_VERSION: u32 = 1;
+
+/// Extension of the backups up to the version they were taken at
+const BACKUP_PREFIX: &str = "db.v";
+
+/// A step upgrading the stored records to a new schema version.
+///
+/// A model changes by keeping its previous version in a `vN` module, registered
+/// with `NamedModels::define_previous`, and declaring the new version with
+/// `from = vN::Model` along with the `From` conversions in both directions. The
+/// migration then moves the stored records with `RwTransaction::migrate`.
+pub struct Migration {
+    /// Schema version reached once applied
+    pub version: u32,
+    pub description: &'static str,
+    pub apply: fn(&RwTransaction) -> eyre::Result<()>,
+}
+
+/// Outcome of an upgrade.
+#[derive(Debug)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Descriptions of the applied migrations, in order
+    pub applied: Vec<&'static str>,
+    /// Copy of the database taken before migrating
+    pub backup: Option<PathBuf>,
+}
+
+/// Brings a storage to the latest schema version.
+pub struct Migrator<'a> {
+    storage: &'a Storage,
+    migrations: &'a [Migration],
+}
+
+impl<'a> Migrator<'a> {
+    pub fn new(storage: &'a Storage, migrations: &'a [Migration]) -> Self {
+        Self {
+            storage,
+            migrations,
+        }
+    }
+
+    pub fn latest_version(&self) -> u32 {
+        self.migrations
+            .iter()
+            .map(|migration| migration.version)
+            .max()
+            .unwrap_or(INITIAL_SCHEMA_VERSION)
+    }
+
+    /// Schema version of the stored records, databases created before
+    /// migrations were tracked being at the initial version.
+    pub fn stored_version(&self) -> eyre::Result<u32> {
+        self.storage.read_txn(|txn| {
+            Ok(txn
+                .get()
+                .primary::<SchemaVersion>(0_u32)?
+                .map_or(INITIAL_SCHEMA_VERSION, |schema| schema.version))
+        })
+    }
+
+    /// Migrations to apply to reach the latest version, in order.
+    pub fn pending(&self) -> eyre::Result<Vec<&'a Migration>> {
+        let stored_version = self.stored_version()?;
+        if stored_version > self.latest_version() {
+            eyre::bail!(
+                "The database schema (version {stored_version}) is newer than the one \
+                supported by this version of o324 (version {})",
+                self.latest_version()
+            );
+        }
+
+        let mut pending: Vec<&Migration> = self
+            .migrations
+            .iter()
+            .filter(|migration| migration.version > stored_version)
+            .collect();
+        pending.sort_by_key(|migration| migration.version);
+        Ok(pending)
+    }
+
+    /// Marks a new database as being at the latest version.
+    pub fn stamp(&self) -> eyre::Result<()> {
+        let version = self.latest_version();
+        self.storage.write_txn(|txn| store_version(txn, version))
+    }
+
+    /// Lists the backups taken by the previous upgrades.
+    pub fn backups(&self) -> eyre::Result<Vec<PathBuf>> {
+        let path = self.storage.path();
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let prefix = path.with_extension(BACKUP_PREFIX);
+        let Some(prefix) = prefix.file_name().and_then(|name| name.to_str()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let backup = entry?.path();
+            let is_backup = backup
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(prefix) && name.ends_with(".bak"));
+            if is_backup {
+                backups.push(backup);
+            }
+        }
+        Ok(backups)
+    }
+
+    /// Applies the pending migrations in a transaction that is rolled back,
+    /// returning them once they all succeeded.
+    pub fn dry_run(&self) -> eyre::Result<Vec<&'a Migration>> {
+        let pending = self.pending()?;
+
+        self.storage.dry_run_txn(|txn| {
+            pending
+                .iter()
+                .try_for_each(|migration| apply(txn, migration))
+        })?;
+        Ok(pending)
+    }
+
+    /// Applies the pending migrations in a single transaction, after copying
+    /// the database next to it.
+    pub fn upgrade(&self) -> eyre::Result<MigrationReport> {
+        self.upgrade_with(|_| Ok(()))
+    }
+
+    /// Applies the pending migrations then `finish` in a single transaction,
+    /// after copying the database next to it when a migration is pending.
+    pub fn upgrade_with(
+        &self,
+        finish: impl FnOnce(&RwTransaction) -> eyre::Result<()>,
+    ) -> eyre::Result<MigrationReport> {
+        let from_version = self.stored_version()?;
+        let pending = self.pending()?;
+        let mut report = MigrationReport {
+            from_version,
+            to_version: from_version,
+            applied: Vec::new(),
+            backup: None,
+        };
+        if pending.is_empty() {
+            self.storage.write_txn(|txn| finish(txn))?;
+            return Ok(report);
+        }
+
+        let backup = self
+            .storage
+            .path()
+            .with_extension(format!("{BACKUP_PREFIX}{from_version}.{}.bak", unix_now()));
+        self.storage.backup(&backup)?;
+        report.backup = Some(backup);
+
+        let to_version = self.latest_version();
+        self.storage.write_txn(|txn| {
+            for migration in &pending {
+                apply(txn, migration)?;
+            }
+            store_version(txn, to_version)?;
+            finish(txn)
+        })?;
+
+        report.to_version = to_version;
+        report.applied = pending
+            .iter()
+            .map(|migration| migration.description)
+            .collect();
+        Ok(report)
+    }
+}
+
+fn apply(txn: &RwTransaction, migration: &Migration) -> eyre::Result<()> {
+    (migration.apply)(txn).map_err(|e| {
+        eyre::eyre!(
+            "Migration to version {} ({}) failed: {e}",
+            migration.version,
+            migration.description
+        )
+    })
+}
+
+fn store_version(txn: &RwTransaction, version: u32) -> eyre::Result<()> {
+    txn.upsert(SchemaVersion {
+        id: 0,
+        version,
+        migrated_at: unix_now(),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{project_color::ProjectColor, MODELS};
+    use tempfile::tempdir;
+
+    fn insert_color(txn: &RwTransaction) -> eyre::Result<()> {
+        txn.upsert(ProjectColor {
+            key: "o324".to_string(),
+            project: "o324".to_string(),
+            color_hue: 42,
+        })?;
+        Ok(())
+    }
+
+    fn fail(_: &RwTransaction) -> eyre::Result<()> {
+        eyre::bail!("unsupported record")
+    }
+
+    static MIGRATIONS: [Migration; 2] = [
+        Migration {
+            version: 3,
+            description: "Fail",
+            apply: fail,
+        },
+        Migration {
+            version: 2,
+            description: "Color the o324 project",
+            apply: insert_color,
+        },
+    ];
+
+    fn color_count(storage: &Storage) -> eyre::Result<usize> {
+        storage.read_txn(|txn| Ok(txn.scan().primary::<ProjectColor>()?.all()?.count()))
+    }
+
+    #[test]
+    fn test_new_database_is_stamped() -> eyre::Result<()> {
+        let dir = tempdir()?;
+        let storage = Storage::try_new(dir.path().join("test.db"), &MODELS)?;
+        let migrator = Migrator::new(&storage, &MIGRATIONS[1..]);
+
+        assert_eq!(migrator.stored_version()?, INITIAL_SCHEMA_VERSION);
+        migrator.stamp()?;
+        assert_eq!(migrator.stored_version()?, 2);
+        assert!(migrator.pending()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_upgrade_backs_up_and_applies() -> eyre::Result<()> {
+        let dir = tempdir()?;
+        let storage = Storage::try_new(dir.path().join("test.db"), &MODELS)?;
+        let migrator = Migrator::new(&storage, &MIGRATIONS[1..]);
+
+        assert_eq!(migrator.dry_run()?.len(), 1);
+        assert_eq!(migrator.stored_version()?, INITIAL_SCHEMA_VERSION);
+        assert_eq!(color_count(&storage)?, 0);
+
+        let report = migrator.upgrade()?;
+        assert_eq!((report.from_version, report.to_version), (1, 2));
+        assert_eq!(report.applied, vec!["Color the o324 project"]);
+        assert!(report.backup.is_some_and(|backup| backup.exists()));
+        assert_eq!(migrator.backups()?.len(), 1);
+        assert_eq!(color_count(&storage)?, 1);
+
+        assert!(migrator.upgrade()?.applied.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_migration_is_rolled_back() -> eyre::Result<()> {
+        let dir = tempdir()?;
+        let storage = Storage::try_new(dir.path().join("test.db"), &MODELS)?;
+        let migrator = Migrator::new(&storage, &MIGRATIONS);
+
+        assert!(migrator.dry_run().is_err());
+        assert!(migrator.upgrade().is_err());
+        assert_eq!(migrator.stored_version()?, INITIAL_SCHEMA_VERSION);
+        assert_eq!(color_count(&storage)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_database_is_rejected() -> eyre::Result<()> {
+        let dir = tempdir()?;
+        let storage = Storage::try_new(dir.path().join("test.db"), &MODELS)?;
+        Migrator::new(&storage, &MIGRATIONS).stamp()?;
+
+        assert!(Migrator::new(&storage, &MIGRATIONS[1..]).pending().is_err());
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 pub mod storage;
+pub mod migration;
+pub mod crypto;
 pub mod named_model;
 pub mod utils;
 pub mod tracing;
@@ -7,3 +9,5 @@ pub mod cache_strategy;
 pub mod supervisor;
 pub mod color;
 pub mod batch_loader;
+#[cfg(test)]
+pub mod testing;
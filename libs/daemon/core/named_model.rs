@@ -37,6 +37,13 @@ impl NamedModels {
         Ok(())
     }
 
+    /// Defines a previous version of a model. It is only known to `native_db`
+    /// so that its stored records can be migrated, and isn't listed by name.
+    pub fn define_previous<T: ToInput + 'static>(&mut self) -> eyre::Result<()> {
+        self.inner.define::<T>()?;
+        Ok(())
+    }
+
     /// Returns an immutable reference to the inner `native_db::Models` instance.
     ///
     /// This is essential for passing the models collection to `native_db` functions
@@ -102,6 +102,17 @@ where
         self.cache_handler.insert_into_cache(item.cache_key(), item);
         Ok(())
     }
+
+    pub fn remove(&mut self, item: T) -> eyre::Result<()> {
+        self.cache_handler.remove_from_cache(&item.cache_key());
+        self.txn.remove(item)?;
+        Ok(())
+    }
+
+    /// Evicts the whole cache, e.g. before regenerating every item.
+    pub fn clear_cache(&mut self) {
+        self.cache_handler.clear_cache();
+    }
 }
 
 // Main Repository implementation
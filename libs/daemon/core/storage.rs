@@ -2,33 +2,144 @@ use native_db::{
     transaction::{self},
     Builder, Database, ToInput,
 };
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
 
-use crate::core::named_model::NamedModels;
+use crate::core::{
+    crypto::{KeySlot, StorageKey},
+    named_model::NamedModels,
+};
 
 #[derive(Clone)]
 pub struct Storage {
-    inner_storage: Arc<Database<'static>>,
+    /// Swapped by `rewrite`, the transactions in progress keep the previous one
+    inner_storage: Arc<RwLock<Arc<Database<'static>>>>,
     pub models: &'static NamedModels,
+    path: PathBuf,
+    key_slot: Arc<KeySlot>,
 }
 
 #[allow(dead_code)]
 impl Storage {
     pub fn try_new(path: impl AsRef<Path>, models: &'static NamedModels) -> eyre::Result<Self> {
+        let path = path.as_ref().to_path_buf();
         let builder = Builder::new();
-        let db = builder.create(models.get_inner(), path)?;
+        let db = builder.create(models.get_inner(), &path)?;
         Ok(Self {
-            inner_storage: Arc::new(db),
+            inner_storage: Arc::new(RwLock::new(Arc::new(db))),
             models,
+            path,
+            key_slot: Arc::default(),
+        })
+    }
+
+    /// Refuses to store the sealed models until the key is installed, for the
+    /// storage of an encrypted profile.
+    pub fn lock(&self) -> eyre::Result<()> {
+        self.key_slot.lock()
+    }
+
+    /// Sets the key of the sealed models.
+    pub fn install_key(&self, key: StorageKey) -> eyre::Result<()> {
+        self.key_slot.install(key)
+    }
+
+    /// Drops the key of the sealed models, locking the storage again.
+    pub fn forget_key(&self) -> eyre::Result<()> {
+        self.key_slot.forget()
+    }
+
+    /// Tells whether opening the database failed because another process,
+    /// e.g. the daemon, holds it.
+    pub fn is_already_open(error: &eyre::Report) -> bool {
+        error.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<native_db::db_type::Error>(),
+                Some(native_db::db_type::Error::RedbDatabaseError(
+                    redb::DatabaseError::DatabaseAlreadyOpen
+                ))
+            )
         })
     }
 
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Copies the database file to `destination`. Committed transactions
+    /// are already written to the file so no write must be in progress.
+    pub fn backup(&self, destination: impl AsRef<Path>) -> eyre::Result<()> {
+        std::fs::copy(&self.path, destination)?;
+        Ok(())
+    }
+
+    /// Copies the records into a fresh database file which then replaces
+    /// this one, so that the pages freed by previous writes are left behind.
+    /// No write must be in progress, it would be lost.
+    pub fn rewrite<F>(&self, copy: F) -> eyre::Result<()>
+    where
+        F: FnOnce(&transaction::RTransaction, &transaction::RwTransaction) -> eyre::Result<()>,
+    {
+        let fresh_path = self.path.with_extension("db.fresh");
+        if fresh_path.exists() {
+            std::fs::remove_file(&fresh_path)?;
+        }
+
+        let fresh = (|| {
+            let fresh = Builder::new().create(self.models.get_inner(), &fresh_path)?;
+            {
+                let current = self.database()?;
+                let _scope = self.key_slot.enter();
+                let from = current.r_transaction()?;
+                let to = fresh.rw_transaction()?;
+                copy(&from, &to)?;
+                to.commit()?;
+            }
+            // The open database keeps the replaced file until it is dropped
+            std::fs::rename(&fresh_path, &self.path)?;
+            Ok(fresh)
+        })();
+
+        match fresh {
+            Ok(fresh) => {
+                *self
+                    .inner_storage
+                    .write()
+                    .map_err(|_| eyre::eyre!("The database lock is poisoned"))? = Arc::new(fresh);
+                Ok(())
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&fresh_path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Executes read-write operations within a transaction which is rolled
+    /// back, e.g. to check that they succeed
+    pub fn dry_run_txn<F, R>(&self, f: F) -> eyre::Result<R>
+    where
+        F: FnOnce(&transaction::RwTransaction) -> eyre::Result<R>,
+    {
+        let db = self.database()?;
+        let _scope = self.key_slot.enter();
+        let txn = db.rw_transaction()?;
+        let result = f(&txn);
+        txn.abort()?;
+        result
+    }
+
     /// Executes read-only operation within a transaction
     pub fn read_txn<F, R>(&self, f: F) -> eyre::Result<R>
     where
         F: FnOnce(transaction::RTransaction) -> eyre::Result<R>,
     {
-        f(self.inner_storage.r_transaction()?)
+        let db = self.database()?;
+        let _scope = self.key_slot.enter();
+        f(db.r_transaction()?)
     }
 
     /// Executes read-write operation within a transaction
@@ -36,7 +147,9 @@ impl Storage {
     where
         F: FnOnce(&mut transaction::RwTransaction) -> eyre::Result<R>,
     {
-        let mut txn = self.inner_storage.rw_transaction()?;
+        let db = self.database()?;
+        let _scope = self.key_slot.enter();
+        let mut txn = db.rw_transaction()?;
         match f(&mut txn) {
             Ok(result) => {
                 txn.commit()?;
@@ -51,6 +164,14 @@ impl Storage {
         }
     }
 
+    fn database(&self) -> eyre::Result<Arc<Database<'static>>> {
+        Ok(self
+            .inner_storage
+            .read()
+            .map_err(|_| eyre::eyre!("The database lock is poisoned"))?
+            .clone())
+    }
+
     pub fn insert<T: ToInput>(&self, item: T) -> eyre::Result<()> {
         self.write_txn(|qr| Ok(qr.insert(item)?))
     }
@@ -446,4 +567,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_database_already_open() {
+        let (dir, _storage) = setup_database(&MODELS);
+
+        let Err(error) = Storage::try_new(dir.path().join("test.db"), &MODELS) else {
+            panic!("the database should be locked");
+        };
+        assert!(Storage::is_already_open(&error));
+
+        let Err(error) = Storage::try_new(dir.path().join("missing/test.db"), &MODELS) else {
+            panic!("the directory doesn't exist");
+        };
+        assert!(!Storage::is_already_open(&error));
+    }
 }
@@ -0,0 +1,161 @@
+//! Helpers shared by the daemon tests.
+
+use crate::{
+    core::storage::Storage,
+    entities::MODELS,
+    repositories::{
+        project_color::ProjectColorRepository, sync_cursor::SyncCursorRepository,
+        task::TaskRepository, task_operation::TaskOperationRepository,
+        task_prefix::TaskPrefixRepository,
+    },
+    services::{
+        git_sync::GitSyncService,
+        task::{import::TaskImporter, TaskService},
+    },
+};
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+};
+use tempfile::{tempdir, TempDir};
+
+/// Builds a task service on top of the given storage, without synchronization.
+pub fn build_task_service(storage: Storage, computer_name: &str) -> TaskService {
+    TaskService::builder()
+        .task_repository(
+            TaskRepository::builder()
+                .storage(storage.clone())
+                .computer_name(computer_name.to_string())
+                .build(),
+        )
+        .task_prefix_repository(TaskPrefixRepository::new(storage.clone()))
+        .project_color_repository(ProjectColorRepository::builder().storage(storage).build())
+        .build()
+}
+
+/// Storage and repositories of a device in the synchronization tests.
+pub struct SyncDevice {
+    pub dir: TempDir,
+    pub storage: Storage,
+    pub task_repository: TaskRepository,
+}
+
+impl SyncDevice {
+    /// Creates the storage of `computer_name` in a temporary directory.
+    pub fn new(computer_name: &str) -> Self {
+        let dir = tempdir().unwrap();
+        let storage = Storage::try_new(dir.path().join("storage.db"), &MODELS).unwrap();
+        let task_repository = TaskRepository::builder()
+            .storage(storage.clone())
+            .computer_name(computer_name.to_string())
+            .build();
+
+        Self {
+            dir,
+            storage,
+            task_repository,
+        }
+    }
+
+    pub fn task_importer(&self) -> TaskImporter {
+        TaskImporter::builder()
+            .task_repository(self.task_repository.clone())
+            .task_prefix_repository(TaskPrefixRepository::new(self.storage.clone()))
+            .build()
+    }
+
+    pub fn task_operation_repository(&self) -> TaskOperationRepository {
+        TaskOperationRepository::builder()
+            .storage(self.storage.clone())
+            .computer_name(self.task_repository.computer_name.clone())
+            .build()
+    }
+
+    pub fn sync_cursor_repository(&self) -> SyncCursorRepository {
+        SyncCursorRepository::builder()
+            .storage(self.storage.clone())
+            .build()
+    }
+
+    /// Builds the task service of the device, exporting its changes to
+    /// `git_sync_service` when given.
+    pub fn task_service(&self, git_sync_service: Option<GitSyncService>) -> TaskService {
+        TaskService::builder()
+            .task_repository(self.task_repository.clone())
+            .task_prefix_repository(TaskPrefixRepository::new(self.storage.clone()))
+            .project_color_repository(
+                ProjectColorRepository::builder()
+                    .storage(self.storage.clone())
+                    .build(),
+            )
+            .git_sync_service(git_sync_service)
+            .build()
+    }
+}
+
+/// A dbus-daemon standing in for the system or session bus, killed on drop.
+pub struct PrivateBus {
+    child: Child,
+    pub address: String,
+    _dir: TempDir,
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl PrivateBus {
+    /// Starts a private bus, dbus-daemon must be installed.
+    pub fn start() -> Self {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("bus.conf");
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"<busconfig>
+  <type>session</type>
+  <listen>unix:dir={}</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>"#,
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+
+        let mut child = Command::new("dbus-daemon")
+            .arg(format!("--config-file={}", config_path.display()))
+            .args(["--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("dbus-daemon should be installed");
+
+        let mut address = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+
+        Self {
+            child,
+            address: address.trim().to_string(),
+            _dir: dir,
+        }
+    }
+
+    /// Opens a new connection to the bus.
+    pub async fn connect(&self) -> zbus::Connection {
+        zbus::connection::Builder::address(self.address.as_str())
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+    }
+}
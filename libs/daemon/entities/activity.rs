@@ -1,9 +1,10 @@
+use crate::core::crypto::Sealed;
 use native_db::{native_db, ToKey};
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
-#[native_model(id = 4, version = 1)]
+#[native_model(id = 4, version = 2, from = v1::Activity, with = Sealed)]
 #[native_db]
 #[derive(Clone, Debug, Serialize, Deserialize, TypedBuilder)]
 pub struct Activity {
@@ -15,3 +16,47 @@ pub struct Activity {
     pub at: u64,
     pub computer_name: String,
 }
+
+impl From<v1::Activity> for Activity {
+    fn from(activity: v1::Activity) -> Self {
+        Self {
+            id: activity.id,
+            app_title: activity.app_title,
+            app_name: activity.app_name,
+            at: activity.at,
+            computer_name: activity.computer_name,
+        }
+    }
+}
+
+impl From<Activity> for v1::Activity {
+    fn from(activity: Activity) -> Self {
+        Self {
+            id: activity.id,
+            app_title: activity.app_title,
+            app_name: activity.app_name,
+            at: activity.at,
+            computer_name: activity.computer_name,
+        }
+    }
+}
+
+pub mod v1 {
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Activity before its payload was encrypted.
+    #[native_model(id = 4, version = 1)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Activity {
+        #[primary_key]
+        pub id: String,
+        pub app_title: Option<String>,
+        pub app_name: String,
+        #[secondary_key]
+        pub at: u64,
+        pub computer_name: String,
+    }
+}
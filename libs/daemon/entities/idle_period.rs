@@ -0,0 +1,61 @@
+use crate::core::crypto::Sealed;
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// A span of time during which the user didn't interact with the computer.
+#[native_model(id = 6, version = 2, from = v1::IdlePeriod, with = Sealed)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdlePeriod {
+    #[primary_key]
+    pub id: String,
+    #[secondary_key]
+    pub start: u64,
+    /// None while the user is still idle
+    #[secondary_key]
+    pub end: Option<u64>,
+    pub computer_name: String,
+}
+
+impl From<v1::IdlePeriod> for IdlePeriod {
+    fn from(period: v1::IdlePeriod) -> Self {
+        Self {
+            id: period.id,
+            start: period.start,
+            end: period.end,
+            computer_name: period.computer_name,
+        }
+    }
+}
+
+impl From<IdlePeriod> for v1::IdlePeriod {
+    fn from(period: IdlePeriod) -> Self {
+        Self {
+            id: period.id,
+            start: period.start,
+            end: period.end,
+            computer_name: period.computer_name,
+        }
+    }
+}
+
+pub mod v1 {
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Idle period before its payload was encrypted.
+    #[native_model(id = 6, version = 1)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct IdlePeriod {
+        #[primary_key]
+        pub id: String,
+        #[secondary_key]
+        pub start: u64,
+        #[secondary_key]
+        pub end: Option<u64>,
+        pub computer_name: String,
+    }
+}
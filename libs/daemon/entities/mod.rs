@@ -1,21 +1,215 @@
 use once_cell::sync::Lazy;
 
-use crate::core::named_model::NamedModels;
+use native_db::{
+    transaction::{RTransaction, RwTransaction},
+    ToInput,
+};
+
+use crate::core::{
+    crypto::{blind_key, StorageKeyCheck},
+    migration::Migration,
+    named_model::NamedModels,
+};
 
 pub mod prefix_trie_node;
 pub mod task;
 pub mod project_color;
 pub mod activity;
+pub mod task_operation;
+pub mod idle_period;
+pub mod sync_operation;
+pub mod sync_device;
+pub mod sync_cursor;
+pub mod task_conflict;
+pub mod schema_version;
+pub mod task_tombstone;
+pub mod task_sync_base;
+
+#[cfg(test)]
+mod tests;
 
 pub fn get_models() -> NamedModels {
     let mut models = NamedModels::new();
+    models.define_previous::<task::v1::Task>().unwrap();
+    models.define_previous::<task::v2::Task>().unwrap();
+    models.define_previous::<task::v3::Task>().unwrap();
+    models.define_previous::<task::v4::Task>().unwrap();
     models.define::<task::Task>("task").unwrap();
+    models
+        .define_previous::<prefix_trie_node::v1::PrefixTrieNode>()
+        .unwrap();
     models
         .define::<prefix_trie_node::PrefixTrieNode>("prefix-trie-node")
         .unwrap();
+    models
+        .define_previous::<project_color::v1::ProjectColor>()
+        .unwrap();
     models.define::<project_color::ProjectColor>("project_color").unwrap();
+    models.define_previous::<activity::v1::Activity>().unwrap();
     models.define::<activity::Activity>("activity").unwrap();
     models
+        .define_previous::<task_operation::v1::TaskOperation>()
+        .unwrap();
+    models
+        .define_previous::<task_operation::v2::TaskOperation>()
+        .unwrap();
+    models
+        .define_previous::<task_operation::v3::TaskOperation>()
+        .unwrap();
+    models
+        .define::<task_operation::TaskOperation>("task_operation")
+        .unwrap();
+    models
+        .define_previous::<idle_period::v1::IdlePeriod>()
+        .unwrap();
+    models
+        .define::<idle_period::IdlePeriod>("idle_period")
+        .unwrap();
+    models
+        .define::<schema_version::SchemaVersion>("schema_version")
+        .unwrap();
+    models
+        .define_previous::<sync_operation::v1::SyncOperation>()
+        .unwrap();
+    models
+        .define::<sync_operation::SyncOperation>("sync_operation")
+        .unwrap();
+    models
+        .define::<sync_device::SyncDevice>("sync_device")
+        .unwrap();
+    models
+        .define::<sync_cursor::SyncCursor>("sync_cursor")
+        .unwrap();
+    models
+        .define_previous::<task_conflict::v1::TaskConflict>()
+        .unwrap();
+    models
+        .define::<task_conflict::TaskConflict>("task_conflict")
+        .unwrap();
+    models
+        .define::<StorageKeyCheck>("storage_key_check")
+        .unwrap();
+    models
+        .define::<task_tombstone::TaskTombstone>("task_tombstone")
+        .unwrap();
+    models
+        .define::<task_sync_base::TaskSyncBase>("task_sync_base")
+        .unwrap();
+    models
+}
+
+/// Schema migrations, each one moving the records of the previous model
+/// versions to the current ones.
+pub static MIGRATIONS: [Migration; 4] = [
+    Migration {
+        version: 2,
+        description: "Add notes to tasks",
+        apply: add_task_notes,
+    },
+    Migration {
+        version: 3,
+        description: "Add field clocks to tasks",
+        apply: add_task_field_clocks,
+    },
+    Migration {
+        version: 4,
+        description: "Allow a running task per computer",
+        apply: allow_running_task_per_computer,
+    },
+    Migration {
+        version: 5,
+        description: "Move the record payloads to the sealed format",
+        apply: move_to_sealed_payloads,
+    },
+];
+
+fn add_task_notes(txn: &RwTransaction) -> eyre::Result<()> {
+    txn.migrate::<task::Task>()?;
+    txn.migrate::<task_operation::TaskOperation>()?;
+    Ok(())
+}
+
+fn add_task_field_clocks(txn: &RwTransaction) -> eyre::Result<()> {
+    txn.migrate::<task::Task>()?;
+    txn.migrate::<task_operation::TaskOperation>()?;
+    txn.migrate::<sync_operation::SyncOperation>()?;
+    Ok(())
+}
+
+fn allow_running_task_per_computer(txn: &RwTransaction) -> eyre::Result<()> {
+    txn.migrate::<task::Task>()?;
+    Ok(())
+}
+
+fn move_to_sealed_payloads(txn: &RwTransaction) -> eyre::Result<()> {
+    txn.migrate::<task::Task>()?;
+    txn.migrate::<prefix_trie_node::PrefixTrieNode>()?;
+    txn.migrate::<project_color::ProjectColor>()?;
+    txn.migrate::<activity::Activity>()?;
+    txn.migrate::<task_operation::TaskOperation>()?;
+    txn.migrate::<idle_period::IdlePeriod>()?;
+    txn.migrate::<task_conflict::TaskConflict>()?;
+    Ok(())
+}
+
+/// Rewrites the records of the sealed models, encrypting the ones stored
+/// before the storage key was set.
+pub fn seal_records(txn: &RwTransaction) -> eyre::Result<()> {
+    txn.refresh::<task::Task>()?;
+    txn.refresh::<prefix_trie_node::PrefixTrieNode>()?;
+    blind_project_colors(txn)?;
+    txn.refresh::<activity::Activity>()?;
+    txn.refresh::<task_operation::TaskOperation>()?;
+    txn.refresh::<idle_period::IdlePeriod>()?;
+    txn.refresh::<task_conflict::TaskConflict>()?;
+    Ok(())
+}
+
+/// Keys the project colors by the blinded project names.
+fn blind_project_colors(txn: &RwTransaction) -> eyre::Result<()> {
+    // Sealed first, a record being removed as it's encoded now
+    txn.refresh::<project_color::ProjectColor>()?;
+    let colors = txn
+        .scan()
+        .primary::<project_color::ProjectColor>()?
+        .all()?
+        .collect::<Result<Vec<_>, _>>()?;
+    for color in colors {
+        let key = blind_key(&color.project)?;
+        if key == color.key {
+            continue;
+        }
+        txn.remove(color.clone())?;
+        txn.insert(project_color::ProjectColor { key, ..color })?;
+    }
+    Ok(())
+}
+
+/// Copies the records of every current model to another database, see
+/// `Storage::rewrite`. A new model has to be added here.
+pub fn copy_records(from: &RTransaction, to: &RwTransaction) -> eyre::Result<()> {
+    copy::<task::Task>(from, to)?;
+    copy::<prefix_trie_node::PrefixTrieNode>(from, to)?;
+    copy::<project_color::ProjectColor>(from, to)?;
+    copy::<activity::Activity>(from, to)?;
+    copy::<task_operation::TaskOperation>(from, to)?;
+    copy::<idle_period::IdlePeriod>(from, to)?;
+    copy::<schema_version::SchemaVersion>(from, to)?;
+    copy::<sync_operation::SyncOperation>(from, to)?;
+    copy::<sync_device::SyncDevice>(from, to)?;
+    copy::<sync_cursor::SyncCursor>(from, to)?;
+    copy::<task_conflict::TaskConflict>(from, to)?;
+    copy::<StorageKeyCheck>(from, to)?;
+    copy::<task_tombstone::TaskTombstone>(from, to)?;
+    copy::<task_sync_base::TaskSyncBase>(from, to)?;
+    Ok(())
+}
+
+fn copy<T: ToInput>(from: &RTransaction, to: &RwTransaction) -> eyre::Result<()> {
+    for record in from.scan().primary::<T>()?.all()? {
+        to.insert(record?)?;
+    }
+    Ok(())
 }
 
 pub static MODELS: Lazy<NamedModels> = Lazy::new(get_models);
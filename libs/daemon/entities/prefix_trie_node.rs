@@ -1,10 +1,11 @@
+use crate::core::crypto::Sealed;
 use native_db::*;
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 
 /// We build a Trie Node of task id prefixes so that the user can
 /// reference task by prefixes while handling collision gracefully.
-#[native_model(id = 2, version = 1)]
+#[native_model(id = 2, version = 2, from = v1::PrefixTrieNode, with = Sealed)]
 #[native_db]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PrefixTrieNode {
@@ -13,3 +14,40 @@ pub struct PrefixTrieNode {
     pub is_unique: bool,
     pub is_end_of_id: bool,
 }
+
+impl From<v1::PrefixTrieNode> for PrefixTrieNode {
+    fn from(node: v1::PrefixTrieNode) -> Self {
+        Self {
+            prefix: node.prefix,
+            is_unique: node.is_unique,
+            is_end_of_id: node.is_end_of_id,
+        }
+    }
+}
+
+impl From<PrefixTrieNode> for v1::PrefixTrieNode {
+    fn from(node: PrefixTrieNode) -> Self {
+        Self {
+            prefix: node.prefix,
+            is_unique: node.is_unique,
+            is_end_of_id: node.is_end_of_id,
+        }
+    }
+}
+
+pub mod v1 {
+    use native_db::*;
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Prefix trie node before its payload was encrypted.
+    #[native_model(id = 2, version = 1)]
+    #[native_db]
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct PrefixTrieNode {
+        #[primary_key]
+        pub prefix: String,
+        pub is_unique: bool,
+        pub is_end_of_id: bool,
+    }
+}
@@ -1,12 +1,51 @@
+use crate::core::crypto::Sealed;
 use native_db::*;
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[native_model(id = 3, version = 1)]
+#[native_model(id = 3, version = 2, from = v1::ProjectColor, with = Sealed)]
 #[native_db]
 pub struct ProjectColor {
+    /// The project name, blinded once the profile is encrypted, see
+    /// `crypto::blind_key`
     #[primary_key]
+    pub key: String,
     pub project: String,
     pub color_hue: u32,
 }
+
+impl From<v1::ProjectColor> for ProjectColor {
+    fn from(project_color: v1::ProjectColor) -> Self {
+        Self {
+            key: project_color.project.clone(),
+            project: project_color.project,
+            color_hue: project_color.color_hue,
+        }
+    }
+}
+
+impl From<ProjectColor> for v1::ProjectColor {
+    fn from(project_color: ProjectColor) -> Self {
+        Self {
+            project: project_color.project,
+            color_hue: project_color.color_hue,
+        }
+    }
+}
+
+pub mod v1 {
+    use native_db::*;
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Project color before its payload was encrypted.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    #[native_model(id = 3, version = 1)]
+    #[native_db]
+    pub struct ProjectColor {
+        #[primary_key]
+        pub project: String,
+        pub color_hue: u32,
+    }
+}
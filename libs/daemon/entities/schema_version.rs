@@ -0,0 +1,14 @@
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// Schema version the stored records are at, kept as a singleton.
+#[native_model(id = 7, version = 1)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SchemaVersion {
+    #[primary_key]
+    pub id: u32,
+    pub version: u32,
+    pub migrated_at: u64,
+}
@@ -0,0 +1,15 @@
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// Position reached in the operation log of a sync server.
+#[native_model(id = 10, version = 1)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncCursor {
+    /// Url of the sync server
+    #[primary_key]
+    pub url: String,
+    /// Server cursor of the last operation pulled
+    pub cursor: u64,
+}
@@ -0,0 +1,15 @@
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// A device known by the sync server.
+#[native_model(id = 9, version = 1)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncDevice {
+    #[primary_key]
+    pub computer_name: String,
+    /// Sequence number of the last operation received from the device
+    pub last_seq: u64,
+    pub last_push_at: u64,
+}
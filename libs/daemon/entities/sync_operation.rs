@@ -0,0 +1,63 @@
+use crate::repositories::task::defs::TaskAction;
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// A task operation stored by the sync server, in the order it was pushed.
+#[native_model(id = 8, version = 2, from = v1::SyncOperation)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SyncOperation {
+    /// Position in the server log, used by devices as pull cursor
+    #[primary_key]
+    pub cursor: u64,
+    pub computer_name: String,
+    /// Sequence number of the operation on the device that pushed it
+    pub seq: u64,
+    pub action: TaskAction,
+    pub at: u64,
+}
+
+impl From<v1::SyncOperation> for SyncOperation {
+    fn from(operation: v1::SyncOperation) -> Self {
+        Self {
+            cursor: operation.cursor,
+            computer_name: operation.computer_name,
+            seq: operation.seq,
+            action: operation.action.into(),
+            at: operation.at,
+        }
+    }
+}
+
+impl From<SyncOperation> for v1::SyncOperation {
+    fn from(operation: SyncOperation) -> Self {
+        Self {
+            cursor: operation.cursor,
+            computer_name: operation.computer_name,
+            seq: operation.seq,
+            action: operation.action.into(),
+            at: operation.at,
+        }
+    }
+}
+
+pub mod v1 {
+    use crate::entities::task_operation::v2::TaskAction;
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Sync server log entry holding a task before field edits were timestamped.
+    #[native_model(id = 8, version = 1)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SyncOperation {
+        #[primary_key]
+        pub cursor: u64,
+        pub computer_name: String,
+        pub seq: u64,
+        pub action: TaskAction,
+        pub at: u64,
+    }
+}
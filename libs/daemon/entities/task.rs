@@ -5,9 +5,11 @@ use serde::{Deserialize, Serialize};
 use std::hash::{DefaultHasher, Hash, Hasher};
 use typed_builder::TypedBuilder;
 
+use crate::core::crypto::Sealed;
+
 pub type TaskId = String;
 
-#[native_model(id = 1, version = 1)]
+#[native_model(id = 1, version = 5, from = v4::Task, with = Sealed)]
 #[native_db]
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize, TypedBuilder)]
 #[patronus(
@@ -26,12 +28,98 @@ pub struct Task {
     #[secondary_key]
     pub start: u64,
     pub computer_name: String,
-    // needs to be unique for indexing reason
-    #[secondary_key(unique)]
+    /// None while the task is running
+    #[secondary_key]
     #[builder(default = None)]
     pub end: Option<u64>,
+    /// Free text describing what was done
+    #[serde(default)]
+    #[builder(default = None)]
+    pub notes: Option<String>,
+    /// When each field was last edited, to merge edits made on several devices
+    #[serde(default)]
+    #[builder(default)]
+    pub clock: FieldClock,
+}
+
+/// Fields of a task merged independently when it was edited on several devices.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum TaskField {
+    TaskName,
+    Project,
+    Tags,
+    Start,
+    End,
+    Notes,
+}
+
+impl TaskField {
+    pub const ALL: [TaskField; 6] = [
+        TaskField::TaskName,
+        TaskField::Project,
+        TaskField::Tags,
+        TaskField::Start,
+        TaskField::End,
+        TaskField::Notes,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TaskName => "task_name",
+            Self::Project => "project",
+            Self::Tags => "tags",
+            Self::Start => "start",
+            Self::End => "end",
+            Self::Notes => "notes",
+        }
+    }
 }
 
+/// Unix timestamps in ms of the last edit of each field, 0 for tasks created
+/// before edits were tracked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct FieldClock {
+    pub task_name: u64,
+    pub project: u64,
+    pub tags: u64,
+    pub start: u64,
+    pub end: u64,
+    pub notes: u64,
+}
+
+impl FieldClock {
+    pub fn get(&self, field: TaskField) -> u64 {
+        match field {
+            TaskField::TaskName => self.task_name,
+            TaskField::Project => self.project,
+            TaskField::Tags => self.tags,
+            TaskField::Start => self.start,
+            TaskField::End => self.end,
+            TaskField::Notes => self.notes,
+        }
+    }
+
+    pub fn set(&mut self, field: TaskField, at: u64) {
+        let timestamp = match field {
+            TaskField::TaskName => &mut self.task_name,
+            TaskField::Project => &mut self.project,
+            TaskField::Tags => &mut self.tags,
+            TaskField::Start => &mut self.start,
+            TaskField::End => &mut self.end,
+            TaskField::Notes => &mut self.notes,
+        };
+        *timestamp = at;
+    }
+
+    /// Whether every field edit of `other` is already known by this clock.
+    pub fn includes(&self, other: &FieldClock) -> bool {
+        TaskField::ALL
+            .iter()
+            .all(|field| other.get(*field) <= self.get(*field))
+    }
+}
+
+// The clock is left out, two tasks with the same content are in sync
 impl Hash for Task {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.id.hash(state);
@@ -40,6 +128,7 @@ impl Hash for Task {
         self.tags.hash(state);
         self.start.hash(state);
         self.end.hash(state);
+        self.notes.hash(state);
     }
 }
 
@@ -49,6 +138,50 @@ impl Task {
         self.hash(&mut hasher);
         hasher.finish()
     }
+
+    pub fn field_eq(&self, other: &Task, field: TaskField) -> bool {
+        match field {
+            TaskField::TaskName => self.task_name == other.task_name,
+            TaskField::Project => self.project == other.project,
+            TaskField::Tags => self.tags == other.tags,
+            TaskField::Start => self.start == other.start,
+            TaskField::End => self.end == other.end,
+            TaskField::Notes => self.notes == other.notes,
+        }
+    }
+
+    /// Takes the value of a field from `other`, along with its clock.
+    pub fn copy_field(&mut self, other: &Task, field: TaskField) {
+        match field {
+            TaskField::TaskName => self.task_name = other.task_name.clone(),
+            TaskField::Project => self.project = other.project.clone(),
+            TaskField::Tags => self.tags = other.tags.clone(),
+            TaskField::Start => self.start = other.start,
+            TaskField::End => self.end = other.end,
+            TaskField::Notes => self.notes = other.notes.clone(),
+        }
+        self.clock.set(field, other.clock.get(field));
+    }
+
+    /// Marks the fields that differ from `previous` as edited at `at`, every
+    /// field of a new task being edited.
+    pub fn stamp_changes(&mut self, previous: Option<&Task>, at: u64) {
+        match previous {
+            Some(previous) => {
+                self.clock = previous.clock;
+                for field in TaskField::ALL {
+                    if !self.field_eq(previous, field) {
+                        self.clock.set(field, at);
+                    }
+                }
+            }
+            None => {
+                for field in TaskField::ALL {
+                    self.clock.set(field, at);
+                }
+            }
+        }
+    }
 }
 
 impl TaskUpdate {
@@ -61,6 +194,245 @@ impl TaskUpdate {
             tags: self.tags.unwrap_or(task.tags.clone()),
             start: self.start.unwrap_or(task.start),
             end: self.end.unwrap_or(task.end),
+            notes: self.notes.unwrap_or(task.notes.clone()),
+            clock: self.clock.unwrap_or(task.clock),
+        }
+    }
+}
+
+impl From<v4::Task> for Task {
+    fn from(task: v4::Task) -> Self {
+        Self {
+            id: task.id,
+            task_name: task.task_name,
+            project: task.project,
+            tags: task.tags,
+            start: task.start,
+            computer_name: task.computer_name,
+            end: task.end,
+            notes: task.notes,
+            clock: task.clock,
+        }
+    }
+}
+
+impl From<Task> for v4::Task {
+    fn from(task: Task) -> Self {
+        Self {
+            id: task.id,
+            task_name: task.task_name,
+            project: task.project,
+            tags: task.tags,
+            start: task.start,
+            computer_name: task.computer_name,
+            end: task.end,
+            notes: task.notes,
+            clock: task.clock,
+        }
+    }
+}
+
+/// Tasks embedded in the operations stored before field edits were timestamped.
+impl From<v2::Task> for Task {
+    fn from(task: v2::Task) -> Self {
+        v4::Task::from(v3::Task::from(task)).into()
+    }
+}
+
+impl From<Task> for v2::Task {
+    fn from(task: Task) -> Self {
+        v3::Task::from(v4::Task::from(task)).into()
+    }
+}
+
+pub mod v4 {
+    use super::{v3, FieldClock};
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Task before its payload was encrypted.
+    #[native_model(id = 1, version = 4, from = v3::Task)]
+    #[native_db]
+    #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+    pub struct Task {
+        #[primary_key]
+        pub id: String,
+        pub task_name: String,
+        pub project: Option<String>,
+        pub tags: Vec<String>,
+        #[secondary_key]
+        pub start: u64,
+        pub computer_name: String,
+        #[secondary_key]
+        pub end: Option<u64>,
+        pub notes: Option<String>,
+        pub clock: FieldClock,
+    }
+
+    impl From<v3::Task> for Task {
+        fn from(task: v3::Task) -> Self {
+            Self {
+                id: task.id,
+                task_name: task.task_name,
+                project: task.project,
+                tags: task.tags,
+                start: task.start,
+                computer_name: task.computer_name,
+                end: task.end,
+                notes: task.notes,
+                clock: task.clock,
+            }
+        }
+    }
+
+    impl From<Task> for v3::Task {
+        fn from(task: Task) -> Self {
+            Self {
+                id: task.id,
+                task_name: task.task_name,
+                project: task.project,
+                tags: task.tags,
+                start: task.start,
+                computer_name: task.computer_name,
+                end: task.end,
+                notes: task.notes,
+                clock: task.clock,
+            }
         }
     }
 }
+
+pub mod v3 {
+    use super::{v2, FieldClock};
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Task when a single one could run at a time across all computers.
+    #[native_model(id = 1, version = 3, from = v2::Task)]
+    #[native_db]
+    #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+    pub struct Task {
+        #[primary_key]
+        pub id: String,
+        pub task_name: String,
+        pub project: Option<String>,
+        pub tags: Vec<String>,
+        #[secondary_key]
+        pub start: u64,
+        pub computer_name: String,
+        #[secondary_key(unique)]
+        pub end: Option<u64>,
+        pub notes: Option<String>,
+        pub clock: FieldClock,
+    }
+
+    impl From<v2::Task> for Task {
+        fn from(task: v2::Task) -> Self {
+            Self {
+                id: task.id,
+                task_name: task.task_name,
+                project: task.project,
+                tags: task.tags,
+                start: task.start,
+                computer_name: task.computer_name,
+                end: task.end,
+                notes: task.notes,
+                clock: FieldClock::default(),
+            }
+        }
+    }
+
+    impl From<Task> for v2::Task {
+        fn from(task: Task) -> Self {
+            Self {
+                id: task.id,
+                task_name: task.task_name,
+                project: task.project,
+                tags: task.tags,
+                start: task.start,
+                computer_name: task.computer_name,
+                end: task.end,
+                notes: task.notes,
+            }
+        }
+    }
+}
+
+pub mod v2 {
+    use super::v1;
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Task before the edits of each field were timestamped.
+    #[native_model(id = 1, version = 2, from = v1::Task)]
+    #[native_db]
+    #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+    pub struct Task {
+        #[primary_key]
+        pub id: String,
+        pub task_name: String,
+        pub project: Option<String>,
+        pub tags: Vec<String>,
+        #[secondary_key]
+        pub start: u64,
+        pub computer_name: String,
+        #[secondary_key(unique)]
+        pub end: Option<u64>,
+        pub notes: Option<String>,
+    }
+
+    impl From<v1::Task> for Task {
+        fn from(task: v1::Task) -> Self {
+            Self {
+                id: task.id,
+                task_name: task.task_name,
+                project: task.project,
+                tags: task.tags,
+                start: task.start,
+                computer_name: task.computer_name,
+                end: task.end,
+                notes: None,
+            }
+        }
+    }
+
+    impl From<Task> for v1::Task {
+        fn from(task: Task) -> Self {
+            Self {
+                id: task.id,
+                task_name: task.task_name,
+                project: task.project,
+                tags: task.tags,
+                start: task.start,
+                computer_name: task.computer_name,
+                end: task.end,
+            }
+        }
+    }
+}
+
+pub mod v1 {
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Task before notes were added.
+    #[native_model(id = 1, version = 1)]
+    #[native_db]
+    #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+    pub struct Task {
+        #[primary_key]
+        pub id: String,
+        pub task_name: String,
+        pub project: Option<String>,
+        pub tags: Vec<String>,
+        #[secondary_key]
+        pub start: u64,
+        pub computer_name: String,
+        #[secondary_key(unique)]
+        pub end: Option<u64>,
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    core::crypto::Sealed,
+    entities::task::{Task, TaskField},
+};
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// Fields of a task edited at the same time on this device and another one,
+/// waiting for the user to pick which version to keep.
+#[native_model(id = 11, version = 2, from = v1::TaskConflict, with = Sealed)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskConflict {
+    #[primary_key]
+    pub id: String,
+    #[secondary_key]
+    pub task_id: String,
+    pub fields: Vec<TaskField>,
+    /// The task as stored on this device, which kept its values meanwhile
+    pub local: Task,
+    /// The task as received from the other device
+    pub remote: Task,
+    pub detected_at: u64,
+}
+
+impl From<v1::TaskConflict> for TaskConflict {
+    fn from(conflict: v1::TaskConflict) -> Self {
+        Self {
+            id: conflict.id,
+            task_id: conflict.task_id,
+            fields: conflict.fields,
+            local: conflict.local,
+            remote: conflict.remote,
+            detected_at: conflict.detected_at,
+        }
+    }
+}
+
+impl From<TaskConflict> for v1::TaskConflict {
+    fn from(conflict: TaskConflict) -> Self {
+        Self {
+            id: conflict.id,
+            task_id: conflict.task_id,
+            fields: conflict.fields,
+            local: conflict.local,
+            remote: conflict.remote,
+            detected_at: conflict.detected_at,
+        }
+    }
+}
+
+pub mod v1 {
+    use crate::entities::task::{Task, TaskField};
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Conflict before its payload was encrypted, the tasks keeping the same
+    /// serialized layout.
+    #[native_model(id = 11, version = 1)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TaskConflict {
+        #[primary_key]
+        pub id: String,
+        #[secondary_key]
+        pub task_id: String,
+        pub fields: Vec<TaskField>,
+        pub local: Task,
+        pub remote: Task,
+        pub detected_at: u64,
+    }
+}
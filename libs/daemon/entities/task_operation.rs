@@ -0,0 +1,184 @@
+use crate::{core::crypto::Sealed, repositories::task::defs::TaskAction};
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// An entry of the append-only log of task mutations.
+#[native_model(id = 5, version = 4, from = v3::TaskOperation, with = Sealed)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskOperation {
+    /// Monotonically increasing sequence number
+    #[primary_key]
+    pub seq: u64,
+    pub action: TaskAction,
+    pub computer_name: String,
+    #[secondary_key]
+    pub at: u64,
+}
+
+impl From<v3::TaskOperation> for TaskOperation {
+    fn from(operation: v3::TaskOperation) -> Self {
+        Self {
+            seq: operation.seq,
+            action: operation.action,
+            computer_name: operation.computer_name,
+            at: operation.at,
+        }
+    }
+}
+
+impl From<TaskOperation> for v3::TaskOperation {
+    fn from(operation: TaskOperation) -> Self {
+        Self {
+            seq: operation.seq,
+            action: operation.action,
+            computer_name: operation.computer_name,
+            at: operation.at,
+        }
+    }
+}
+
+impl From<v2::TaskAction> for TaskAction {
+    fn from(action: v2::TaskAction) -> Self {
+        match action {
+            v2::TaskAction::Upsert(task) => TaskAction::Upsert(task.into()),
+            v2::TaskAction::Delete(task_id) => TaskAction::Delete(task_id),
+        }
+    }
+}
+
+impl From<TaskAction> for v2::TaskAction {
+    fn from(action: TaskAction) -> Self {
+        match action {
+            TaskAction::Upsert(task) => v2::TaskAction::Upsert(task.into()),
+            TaskAction::Delete(task_id) => v2::TaskAction::Delete(task_id),
+        }
+    }
+}
+
+pub mod v3 {
+    use super::v2;
+    use crate::repositories::task::defs::TaskAction;
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    /// Operation log entry before its payload was encrypted.
+    #[native_model(id = 5, version = 3, from = v2::TaskOperation)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TaskOperation {
+        #[primary_key]
+        pub seq: u64,
+        pub action: TaskAction,
+        pub computer_name: String,
+        #[secondary_key]
+        pub at: u64,
+    }
+
+    impl From<v2::TaskOperation> for TaskOperation {
+        fn from(operation: v2::TaskOperation) -> Self {
+            Self {
+                seq: operation.seq,
+                action: operation.action.into(),
+                computer_name: operation.computer_name,
+                at: operation.at,
+            }
+        }
+    }
+
+    impl From<TaskOperation> for v2::TaskOperation {
+        fn from(operation: TaskOperation) -> Self {
+            Self {
+                seq: operation.seq,
+                action: operation.action.into(),
+                computer_name: operation.computer_name,
+                at: operation.at,
+            }
+        }
+    }
+}
+
+pub mod v2 {
+    use super::v1;
+    use crate::entities::task;
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub enum TaskAction {
+        Upsert(task::v2::Task),
+        Delete(String),
+    }
+
+    /// Operation log entry holding a task before field edits were timestamped.
+    #[native_model(id = 5, version = 2, from = v1::TaskOperation)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TaskOperation {
+        #[primary_key]
+        pub seq: u64,
+        pub action: TaskAction,
+        pub computer_name: String,
+        #[secondary_key]
+        pub at: u64,
+    }
+
+    impl From<v1::TaskOperation> for TaskOperation {
+        fn from(operation: v1::TaskOperation) -> Self {
+            let action = match operation.action {
+                v1::TaskAction::Upsert(task) => TaskAction::Upsert(task.into()),
+                v1::TaskAction::Delete(task_id) => TaskAction::Delete(task_id),
+            };
+            Self {
+                seq: operation.seq,
+                action,
+                computer_name: operation.computer_name,
+                at: operation.at,
+            }
+        }
+    }
+
+    impl From<TaskOperation> for v1::TaskOperation {
+        fn from(operation: TaskOperation) -> Self {
+            let action = match operation.action {
+                TaskAction::Upsert(task) => v1::TaskAction::Upsert(task.into()),
+                TaskAction::Delete(task_id) => v1::TaskAction::Delete(task_id),
+            };
+            Self {
+                seq: operation.seq,
+                action,
+                computer_name: operation.computer_name,
+                at: operation.at,
+            }
+        }
+    }
+}
+
+pub mod v1 {
+    use crate::entities::task;
+    use native_db::{native_db, ToKey};
+    use native_model::{native_model, Model};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub enum TaskAction {
+        Upsert(task::v1::Task),
+        Delete(String),
+    }
+
+    /// Operation log entry holding a task before notes were added.
+    #[native_model(id = 5, version = 1)]
+    #[native_db]
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct TaskOperation {
+        #[primary_key]
+        pub seq: u64,
+        pub action: TaskAction,
+        pub computer_name: String,
+        #[secondary_key]
+        pub at: u64,
+    }
+}
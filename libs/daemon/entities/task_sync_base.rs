@@ -0,0 +1,15 @@
+use crate::entities::task::FieldClock;
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// Field clocks of the last version of a task exchanged with another device,
+/// telling which side edited a field since.
+#[native_model(id = 14, version = 1)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskSyncBase {
+    #[primary_key]
+    pub task_id: String,
+    pub clock: FieldClock,
+}
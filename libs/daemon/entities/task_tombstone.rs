@@ -0,0 +1,17 @@
+use crate::entities::task::FieldClock;
+use native_db::{native_db, ToKey};
+use native_model::{native_model, Model};
+use serde::{Deserialize, Serialize};
+
+/// Deletion of a task, kept so that the copies of the task still held by
+/// other devices don't bring it back.
+#[native_model(id = 13, version = 1)]
+#[native_db]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskTombstone {
+    #[primary_key]
+    pub task_id: String,
+    /// Field clocks of the deleted version, only edits it doesn't include
+    /// bring the task back
+    pub clock: FieldClock,
+}
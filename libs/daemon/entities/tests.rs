@@ -0,0 +1,180 @@
+use super::{schema_version::SchemaVersion, *};
+use crate::core::{migration::Migrator, storage::Storage};
+use crate::repositories::task::defs::TaskAction;
+use tempfile::tempdir;
+
+/// Models of a database created before task notes were added
+static V1_MODELS: Lazy<NamedModels> = Lazy::new(|| {
+    let mut models = NamedModels::new();
+    models.define::<task::v1::Task>("task").unwrap();
+    models
+        .define::<task_operation::v1::TaskOperation>("task_operation")
+        .unwrap();
+    models
+});
+
+fn v1_task() -> task::v1::Task {
+    task::v1::Task {
+        id: "abcdefg".to_string(),
+        task_name: "review".to_string(),
+        project: Some("o324".to_string()),
+        tags: vec!["rust".to_string()],
+        start: 1_000,
+        computer_name: "laptop".to_string(),
+        end: Some(2_000),
+    }
+}
+
+#[test]
+fn test_migrate_v1_records() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.db");
+
+    let storage = Storage::try_new(&path, &V1_MODELS)?;
+    storage.write_txn(|txn| {
+        txn.insert(v1_task())?;
+        txn.insert(task_operation::v1::TaskOperation {
+            seq: 1,
+            action: task_operation::v1::TaskAction::Upsert(v1_task()),
+            computer_name: "laptop".to_string(),
+            at: 2_000,
+        })?;
+        Ok(())
+    })?;
+    drop(storage);
+
+    let storage = Storage::try_new(&path, &MODELS)?;
+    let report = Migrator::new(&storage, &MIGRATIONS).upgrade()?;
+    assert_eq!((report.from_version, report.to_version), (1, 5));
+
+    storage.read_txn(|txn| {
+        let task: task::Task = txn
+            .get()
+            .primary("abcdefg".to_string())?
+            .ok_or_else(|| eyre::eyre!("task not migrated"))?;
+        assert_eq!(task.task_name, "review");
+        assert_eq!(task.end, Some(2_000));
+        assert_eq!(task.notes, None);
+        assert_eq!(task.clock, task::FieldClock::default());
+
+        let operation: task_operation::TaskOperation = txn
+            .get()
+            .primary(1_u64)?
+            .ok_or_else(|| eyre::eyre!("operation not migrated"))?;
+        assert!(matches!(operation.action, TaskAction::Upsert(task) if task.id == "abcdefg"));
+        Ok(())
+    })
+}
+
+#[test]
+fn test_migrate_v2_records() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.db");
+
+    let storage = Storage::try_new(&path, &MODELS)?;
+    storage.write_txn(|txn| {
+        let task = task::v2::Task::from(v1_task());
+        txn.insert(SchemaVersion {
+            id: 0,
+            version: 2,
+            migrated_at: 0,
+        })?;
+        txn.insert(task.clone())?;
+        txn.insert(sync_operation::v1::SyncOperation {
+            cursor: 1,
+            computer_name: "laptop".to_string(),
+            seq: 1,
+            action: task_operation::v2::TaskAction::Upsert(task),
+            at: 2_000,
+        })?;
+        Ok(())
+    })?;
+
+    let report = Migrator::new(&storage, &MIGRATIONS).upgrade()?;
+    assert_eq!((report.from_version, report.to_version), (2, 5));
+
+    storage.read_txn(|txn| {
+        let task: task::Task = txn
+            .get()
+            .primary("abcdefg".to_string())?
+            .ok_or_else(|| eyre::eyre!("task not migrated"))?;
+        assert_eq!(task.task_name, "review");
+        assert_eq!(task.clock, task::FieldClock::default());
+
+        let operation: sync_operation::SyncOperation = txn
+            .get()
+            .primary(1_u64)?
+            .ok_or_else(|| eyre::eyre!("operation not migrated"))?;
+        assert!(matches!(operation.action, TaskAction::Upsert(task) if task.id == "abcdefg"));
+        Ok(())
+    })
+}
+
+#[test]
+fn test_migrate_v3_records() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.db");
+
+    let storage = Storage::try_new(&path, &MODELS)?;
+    storage.write_txn(|txn| {
+        txn.insert(SchemaVersion {
+            id: 0,
+            version: 3,
+            migrated_at: 0,
+        })?;
+        let mut running = task::v3::Task::from(task::v2::Task::from(v1_task()));
+        running.end = None;
+        txn.insert(running)?;
+        txn.insert(activity::v1::Activity {
+            id: "activity".to_string(),
+            app_title: Some("o324 - review".to_string()),
+            app_name: "editor".to_string(),
+            at: 1_500,
+            computer_name: "laptop".to_string(),
+        })?;
+        Ok(())
+    })?;
+
+    let report = Migrator::new(&storage, &MIGRATIONS).upgrade()?;
+    assert_eq!((report.from_version, report.to_version), (3, 5));
+
+    // Several tasks can now be running at once
+    storage.write_txn(|txn| {
+        txn.insert(task::Task {
+            id: "hijklmn".to_string(),
+            computer_name: "desktop".to_string(),
+            ..task::Task::from(task::v2::Task::from(v1_task()))
+        })?;
+        let mut running = txn
+            .get()
+            .primary::<task::Task>("hijklmn".to_string())?
+            .unwrap();
+        running.end = None;
+        txn.upsert(running)?;
+        Ok(())
+    })?;
+    storage.read_txn(|txn| {
+        let running = txn
+            .scan()
+            .secondary::<task::Task>(task::TaskKey::end)?
+            .range(None::<u64>..=None)?
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(running.len(), 2);
+
+        let activity: activity::Activity = txn
+            .get()
+            .primary("activity".to_string())?
+            .ok_or_else(|| eyre::eyre!("activity not migrated"))?;
+        assert_eq!(activity.app_name, "editor");
+        Ok(())
+    })
+}
+
+#[test]
+fn test_migrations_apply_to_new_database() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS)?;
+
+    assert_eq!(Migrator::new(&storage, &MIGRATIONS).dry_run()?.len(), 4);
+    Ok(())
+}
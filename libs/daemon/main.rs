@@ -12,6 +12,9 @@ mod commands {
     pub mod start;
     pub mod version;
     pub mod status;
+    pub mod migrate;
+    pub mod serve_sync;
+    pub mod pair;
 }
 
 #[derive(Subcommand, Debug)]
@@ -22,6 +25,12 @@ pub enum Command {
     Status(commands::status::Command),
     /// Print the daemon version
     Version(commands::version::Command),
+    /// Upgrade the database schema, which the daemon also does when starting
+    Migrate(commands::migrate::Command),
+    /// Serve a sync server storing the task operations of several devices
+    ServeSync(commands::serve_sync::Command),
+    /// Generate the pairing code and key of a p2p synchronization
+    Pair(commands::pair::Command),
 }
 
 impl Command {
@@ -31,6 +40,9 @@ impl Command {
             Self::Start(o) => start::handle(o, conf).await?,
             Self::Status(o) => status::handle(o, conf).await?,
             Self::Version(o) => version::handle(o, conf).await?,
+            Self::Migrate(o) => migrate::handle(o, conf).await?,
+            Self::ServeSync(o) => serve_sync::handle(o, conf).await?,
+            Self::Pair(o) => pair::handle(o, conf).await?,
         };
 
         Ok(())
@@ -14,7 +14,7 @@ pub struct ActivityRepository {
 }
 
 impl ActivityRepositoryInner {
-    pub fn register(&self, activity: defs::StartActivity) -> eyre::Result<()> {
+    pub fn register(&self, activity: defs::StartActivity) -> eyre::Result<Activity> {
         let activity_id = generate_random_id(7);
         let activity = Activity {
             id: activity_id,
@@ -24,9 +24,17 @@ impl ActivityRepositoryInner {
             computer_name: self.computer_name.clone(),
         };
 
-        self.storage.insert(activity)?;
+        self.storage.insert(activity.clone())?;
 
-        Ok(())
+        Ok(activity)
+    }
+
+    pub fn delete(&self, activity_id: String) -> eyre::Result<Option<Activity>> {
+        self.storage
+            .write_txn(|qr| match qr.get().primary::<Activity>(activity_id)? {
+                Some(activity) => Ok(Some(qr.remove(activity)?)),
+                None => Ok(None),
+            })
     }
 
     pub async fn list_activity_range(
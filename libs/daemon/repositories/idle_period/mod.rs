@@ -0,0 +1,70 @@
+use crate::{
+    core::{storage::Storage, utils::generate_random_id},
+    entities::idle_period::{IdlePeriod, IdlePeriodKey},
+};
+use native_db::transaction::RwTransaction;
+use std::sync::Arc;
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+#[wrap_builder(Arc)]
+pub struct IdlePeriodRepository {
+    pub computer_name: String,
+    pub storage: Storage,
+}
+
+impl IdlePeriodRepositoryInner {
+    /// Opens a new idle period, closing any period left open at `at`.
+    pub fn start(&self, at: u64) -> eyre::Result<IdlePeriod> {
+        let period = IdlePeriod {
+            id: generate_random_id(7),
+            start: at,
+            end: None,
+            computer_name: self.computer_name.clone(),
+        };
+
+        self.storage.write_txn(|qr| {
+            self.close_open_periods(qr, at)?;
+            qr.insert(period.clone())?;
+            Ok(())
+        })?;
+
+        Ok(period)
+    }
+
+    /// Closes the current idle period, if any.
+    pub fn end(&self, at: u64) -> eyre::Result<Option<IdlePeriod>> {
+        self.storage.write_txn(|qr| self.close_open_periods(qr, at))
+    }
+
+    /// Sets the end of every open period of this computer, returning the last one.
+    fn close_open_periods(
+        &self,
+        qr: &mut RwTransaction,
+        at: u64,
+    ) -> eyre::Result<Option<IdlePeriod>> {
+        let mut open = qr
+            .scan()
+            .secondary::<IdlePeriod>(IdlePeriodKey::end)?
+            .range(None::<u64>..=None)?
+            .filter(|period| {
+                period
+                    .as_ref()
+                    .map_or(true, |period| period.computer_name == self.computer_name)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        open.sort_by_key(|period| period.start);
+
+        let mut last_closed = None;
+        for period in open {
+            let mut closed = period.clone();
+            closed.end = Some(at.max(period.start));
+            qr.update(period, closed.clone())?;
+            last_closed = Some(closed);
+        }
+
+        Ok(last_closed)
+    }
+}
@@ -0,0 +1,60 @@
+use super::*;
+use crate::entities::MODELS;
+use tempfile::tempdir;
+
+fn setup_repository() -> (tempfile::TempDir, IdlePeriodRepository) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let repository = IdlePeriodRepository::builder()
+        .storage(storage)
+        .computer_name("laptop".to_string())
+        .build();
+    (dir, repository)
+}
+
+#[tokio::test]
+async fn test_start_and_end_idle_period() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+
+    repository.start(1_000)?;
+    let closed = repository
+        .end(5_000)?
+        .expect("an idle period should be open");
+    assert_eq!((closed.start, closed.end), (1_000, Some(5_000)));
+
+    // Nothing left to close
+    assert!(repository.end(6_000)?.is_none());
+    Ok(())
+}
+
+fn get_period(repository: &IdlePeriodRepository, id: &str) -> eyre::Result<IdlePeriod> {
+    repository.storage.read_txn(|qr| {
+        qr.get()
+            .primary::<IdlePeriod>(id.to_string())?
+            .ok_or_else(|| eyre::eyre!("idle period not found"))
+    })
+}
+
+#[tokio::test]
+async fn test_start_closes_dangling_period() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let other = IdlePeriodRepository::builder()
+        .storage(repository.storage.clone())
+        .computer_name("desktop".to_string())
+        .build();
+
+    let dangling = repository.start(1_000)?;
+    let other_period = other.start(2_000)?;
+    let current = repository.start(3_000)?;
+
+    assert_eq!(get_period(&repository, &dangling.id)?.end, Some(3_000));
+    assert_eq!(get_period(&repository, &current.id)?.end, None);
+
+    // The periods of other computers stay open
+    assert_eq!(
+        repository.end(5_000)?.map(|period| period.id),
+        Some(current.id)
+    );
+    assert_eq!(get_period(&repository, &other_period.id)?.end, None);
+    Ok(())
+}
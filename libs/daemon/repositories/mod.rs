@@ -2,3 +2,7 @@ pub mod task_prefix;
 pub mod task;
 pub mod project_color;
 pub mod activity;
+pub mod task_operation;
+pub mod idle_period;
+pub mod sync_server;
+pub mod sync_cursor;
@@ -2,6 +2,7 @@ use crate::{
     core::{
         batch_loader::{BatchCall, BatchLoader},
         color::SpacedRandomGenerator,
+        crypto::blind_key,
         storage::Storage,
     },
     entities::project_color::ProjectColor,
@@ -39,6 +40,7 @@ struct ProjectColorBatcher {
 }
 
 impl BatchCall<String, u32> for ProjectColorBatcher {
+    #[allow(clippy::result_large_err)]
     fn call(
         &self,
         projects: Vec<String>,
@@ -50,7 +52,8 @@ impl BatchCall<String, u32> for ProjectColorBatcher {
             storage.write_txn(move |qr| {
                 let mut results = HashMap::new();
                 for project in projects.into_iter() {
-                    if let Some(existing) = qr.get().primary::<ProjectColor>(project.clone())? {
+                    let key = blind_key(&project)?;
+                    if let Some(existing) = qr.get().primary::<ProjectColor>(key.clone())? {
                         results.insert(existing.project, existing.color_hue);
                     } else {
                         let generator = color_generator.get_or_try_init(
@@ -69,6 +72,7 @@ impl BatchCall<String, u32> for ProjectColorBatcher {
                         let color_hue = generator.next_number();
 
                         qr.insert(ProjectColor {
+                            key,
                             project: project.to_string(),
                             color_hue,
                         })?;
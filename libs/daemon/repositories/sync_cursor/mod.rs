@@ -0,0 +1,30 @@
+use crate::{core::storage::Storage, entities::sync_cursor::SyncCursor};
+use std::sync::Arc;
+use wrap_builder::wrap_builder;
+
+/// Pull cursors of the sync servers this device synchronized with.
+#[wrap_builder(Arc)]
+pub struct SyncCursorRepository {
+    pub storage: Storage,
+}
+
+impl SyncCursorRepositoryInner {
+    pub fn get(&self, url: &str) -> eyre::Result<Option<u64>> {
+        self.storage.read_txn(|qr| {
+            Ok(qr
+                .get()
+                .primary::<SyncCursor>(url.to_string())?
+                .map(|sync_cursor| sync_cursor.cursor))
+        })
+    }
+
+    pub fn set(&self, url: &str, cursor: u64) -> eyre::Result<()> {
+        self.storage.write_txn(|qr| {
+            qr.upsert(SyncCursor {
+                url: url.to_string(),
+                cursor,
+            })?;
+            Ok(())
+        })
+    }
+}
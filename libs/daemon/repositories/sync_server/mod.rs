@@ -0,0 +1,119 @@
+use crate::{
+    core::{storage::Storage, utils},
+    entities::{
+        sync_device::SyncDevice, sync_operation::SyncOperation, task_operation::TaskOperation,
+    },
+};
+use std::sync::Arc;
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+/// Operations of the other devices following a cursor.
+#[derive(Debug, Clone)]
+pub struct PullPage {
+    pub operations: Vec<SyncOperation>,
+    /// Cursor of the last scanned operation, to resume the next pull from
+    pub cursor: Option<u64>,
+    pub has_more: bool,
+}
+
+/// Operation log of the sync server, shared by all devices.
+#[wrap_builder(Arc)]
+pub struct SyncServerRepository {
+    pub storage: Storage,
+}
+
+impl SyncServerRepositoryInner {
+    /// Appends the operations of a device to the log, skipping the ones
+    /// already received. Returns the sequence number of the last operation
+    /// received from the device.
+    pub fn push(
+        &self,
+        computer_name: &str,
+        operations: Vec<TaskOperation>,
+    ) -> eyre::Result<Option<u64>> {
+        let now = utils::unix_now();
+
+        self.storage.write_txn(|qr| {
+            let device = qr.get().primary::<SyncDevice>(computer_name.to_string())?;
+            let mut last_seq = device.as_ref().map(|device| device.last_seq);
+
+            let last_operation = qr
+                .scan()
+                .primary::<SyncOperation>()?
+                .all()?
+                .next_back()
+                .transpose()?;
+            let mut next_cursor = last_operation.map_or(0, |operation| operation.cursor + 1);
+
+            for operation in operations {
+                if last_seq.is_some_and(|seq| operation.seq <= seq) {
+                    continue;
+                }
+
+                qr.insert(SyncOperation {
+                    cursor: next_cursor,
+                    computer_name: computer_name.to_string(),
+                    seq: operation.seq,
+                    action: operation.action,
+                    at: operation.at,
+                })?;
+                next_cursor += 1;
+                last_seq = Some(operation.seq);
+            }
+
+            if let Some(last_seq) = last_seq {
+                qr.upsert(SyncDevice {
+                    computer_name: computer_name.to_string(),
+                    last_seq,
+                    last_push_at: now,
+                })?;
+            }
+
+            Ok(last_seq)
+        })
+    }
+
+    /// Lists the operations pushed by the other devices after `after`,
+    /// scanning at most `count` operations.
+    pub fn pull(
+        &self,
+        computer_name: &str,
+        after: Option<u64>,
+        count: u64,
+    ) -> eyre::Result<PullPage> {
+        self.storage.read_txn(|qr| {
+            let from = after.map_or(0, |cursor| cursor + 1);
+            let scanned = qr
+                .scan()
+                .primary::<SyncOperation>()?
+                .range(from..)?
+                .take(count as usize)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let cursor = scanned.last().map(|operation| operation.cursor).or(after);
+            let has_more = scanned.len() as u64 == count;
+
+            Ok(PullPage {
+                operations: scanned
+                    .into_iter()
+                    .filter(|operation| operation.computer_name != computer_name)
+                    .collect(),
+                cursor,
+                has_more,
+            })
+        })
+    }
+
+    /// Sequence number of the last operation received from a device.
+    pub fn last_seq(&self, computer_name: &str) -> eyre::Result<Option<u64>> {
+        self.storage.read_txn(|qr| {
+            Ok(qr
+                .get()
+                .primary::<SyncDevice>(computer_name.to_string())?
+                .map(|device| device.last_seq))
+        })
+    }
+}
@@ -0,0 +1,77 @@
+use super::*;
+use crate::{
+    entities::{task::Task, MODELS},
+    repositories::task::defs::TaskAction,
+};
+use tempfile::tempdir;
+
+fn setup_repository() -> (tempfile::TempDir, SyncServerRepository) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    (
+        dir,
+        SyncServerRepository::builder().storage(storage).build(),
+    )
+}
+
+fn operation(computer_name: &str, seq: u64, task_id: &str) -> TaskOperation {
+    TaskOperation {
+        seq,
+        action: TaskAction::Upsert(
+            Task::builder()
+                .id(task_id.to_string())
+                .task_name("sample".to_string())
+                .computer_name(computer_name.to_string())
+                .start(0)
+                .build(),
+        ),
+        computer_name: computer_name.to_string(),
+        at: 0,
+    }
+}
+
+#[test]
+fn test_push_skips_received_operations() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+
+    let pushed = vec![
+        operation("laptop", 0, "aaaaaaa"),
+        operation("laptop", 1, "bbbbbbb"),
+    ];
+    assert_eq!(repository.push("laptop", pushed.clone())?, Some(1));
+
+    // A retried push only appends the new operations
+    let retried = [pushed, vec![operation("laptop", 2, "ccccccc")]].concat();
+    assert_eq!(repository.push("laptop", retried)?, Some(2));
+    assert_eq!(repository.last_seq("laptop")?, Some(2));
+    assert_eq!(repository.last_seq("desktop")?, None);
+
+    let page = repository.pull("desktop", None, 10)?;
+    assert_eq!(page.operations.len(), 3);
+    assert_eq!(page.cursor, Some(2));
+    Ok(())
+}
+
+#[test]
+fn test_pull_skips_own_operations() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+
+    repository.push("laptop", vec![operation("laptop", 0, "aaaaaaa")])?;
+    repository.push("desktop", vec![operation("desktop", 0, "bbbbbbb")])?;
+    repository.push("laptop", vec![operation("laptop", 1, "ccccccc")])?;
+
+    let page = repository.pull("laptop", None, 2)?;
+    assert_eq!(page.operations.len(), 1);
+    assert_eq!(page.operations[0].computer_name, "desktop");
+    assert_eq!(page.cursor, Some(1));
+    assert!(page.has_more);
+
+    let page = repository.pull("laptop", page.cursor, 2)?;
+    assert!(page.operations.is_empty());
+    assert_eq!(page.cursor, Some(2));
+    assert!(!page.has_more);
+
+    // Nothing new keeps the cursor in place
+    assert_eq!(repository.pull("laptop", Some(2), 2)?.cursor, Some(2));
+    Ok(())
+}
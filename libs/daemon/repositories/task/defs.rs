@@ -6,6 +6,45 @@ pub struct StartTaskInput {
     pub task_name: String,
     pub project: Option<String>,
     pub tags: Vec<String>,
+    /// Start of the task when tracking began late, now when `None`
+    #[serde(default)]
+    pub at: Option<u64>,
+}
+
+/// A completed task logged after the fact.
+#[derive(Deserialize, Clone, Debug)]
+pub struct AddTaskInput {
+    pub task_name: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// The task taking over a split task from `at`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SplitTaskInput {
+    pub at: u64,
+    pub task_name: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// How to handle existing tasks of the same computer overlapping an added task.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverlapStrategy {
+    /// Fail without changing anything
+    #[default]
+    Reject,
+    /// Shorten, split or remove the overlapping tasks
+    Trim,
+}
+
+/// Version of the conflicting fields kept when resolving a conflict.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Local,
+    Remote,
 }
 
 #[derive(Clone, Debug)]
@@ -28,3 +67,33 @@ pub enum TaskAction {
     Upsert(Task),
     Delete(String),
 }
+
+/// Actions produced by a mutation, along with the actions reverting it.
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+    pub actions: Vec<TaskAction>,
+    /// Actions restoring the previous state, in the order they must be applied
+    pub revert_actions: Vec<TaskAction>,
+}
+
+impl TaskChanges {
+    /// Records the upsert of a task, `previous` being the version it replaced.
+    pub fn upsert(&mut self, task: Task, previous: Option<Task>) {
+        let revert_action = match previous {
+            Some(previous) => TaskAction::Upsert(previous),
+            None => TaskAction::Delete(task.id.clone()),
+        };
+        self.revert_actions.insert(0, revert_action);
+        self.actions.push(TaskAction::Upsert(task));
+    }
+
+    /// Records the deletion of a task.
+    pub fn delete(&mut self, previous: Task) {
+        self.actions.push(TaskAction::Delete(previous.id.clone()));
+        self.revert_actions.insert(0, TaskAction::Upsert(previous));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
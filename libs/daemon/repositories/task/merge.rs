@@ -0,0 +1,56 @@
+use crate::entities::task::{FieldClock, Task, TaskField};
+
+/// A task received from another device merged into the local one.
+#[derive(Debug)]
+pub struct TaskMerge {
+    pub task: Task,
+    /// Fields edited on both devices since the base, which keep the local value
+    pub conflicts: Vec<TaskField>,
+    /// Base of the next merge, the conflicting fields keeping the current one
+    pub base: FieldClock,
+}
+
+/// Merges each field of `remote` into `local` independently. `base` holds the
+/// clocks of the last version exchanged with another device: a field edited
+/// on a single side since then takes the value of that side, a field edited
+/// on both sides is a conflict. Without a base, the most recent edit wins.
+pub fn merge_task(local: &Task, remote: &Task, base: Option<&FieldClock>) -> TaskMerge {
+    let mut task = local.clone();
+    let mut conflicts = Vec::new();
+    let mut next_base = base.copied().unwrap_or(remote.clock);
+
+    for field in TaskField::ALL {
+        let (local_at, remote_at) = (local.clock.get(field), remote.clock.get(field));
+        if local.field_eq(remote, field) {
+            next_base.set(field, local_at.max(remote_at));
+            continue;
+        }
+
+        let edited_since = |at: u64| base.is_some_and(|base| at > base.get(field));
+        match (edited_since(local_at), edited_since(remote_at)) {
+            (true, true) => {
+                conflicts.push(field);
+                continue;
+            }
+            (false, true) => task.copy_field(remote, field),
+            (true, false) => (),
+            // Without a base telling which side edited the field
+            (false, false) if remote_at > local_at => task.copy_field(remote, field),
+            (false, false) if remote_at == local_at => {
+                conflicts.push(field);
+                continue;
+            }
+            (false, false) => (),
+        }
+        next_base.set(
+            field,
+            base.map_or(remote_at, |base| base.get(field).max(remote_at)),
+        );
+    }
+
+    TaskMerge {
+        task,
+        conflicts,
+        base: next_base,
+    }
+}
@@ -1,41 +1,98 @@
 use crate::{
+    config::defs::RunningTaskPolicy,
     core::{
         storage::Storage,
         utils::{self, generate_random_id},
     },
-    entities::task::{Task, TaskId, TaskKey, TaskUpdate},
+    entities::{
+        task::{FieldClock, Task, TaskField, TaskId, TaskKey, TaskUpdate},
+        task_conflict::{TaskConflict, TaskConflictKey},
+        task_sync_base::TaskSyncBase,
+        task_tombstone::TaskTombstone,
+    },
+    repositories::task_operation::append_operations,
 };
-use std::sync::Arc;
+use native_db::transaction::RwTransaction;
+use std::{collections::HashMap, sync::Arc};
 use wrap_builder::wrap_builder;
 
-use defs::{StartTaskInput, TaskAction, TaskRef};
+use defs::{
+    AddTaskInput, ConflictResolution, OverlapStrategy, SplitTaskInput, StartTaskInput, TaskAction,
+    TaskChanges, TaskRef,
+};
+use merge::merge_task;
 
 pub mod defs;
+pub mod merge;
+
+#[cfg(test)]
+mod tests;
 
 #[wrap_builder(Arc)]
 pub struct TaskRepository {
     pub computer_name: String,
     pub storage: Storage,
+    #[builder(default)]
+    pub running_task_policy: RunningTaskPolicy,
 }
 
 impl TaskRepositoryInner {
-    /// Starts a new task. If another task is currently running, it will be stopped.
+    /// Starts a new task, now or at `input.at`. If another task is currently running,
+    /// it will be stopped. When starting in the past, the previous task of this computer
+    /// is ended at the new start so that they don't overlap.
     pub async fn start_new_task(
         &self,
         input: StartTaskInput,
-    ) -> eyre::Result<(Task, Vec<TaskAction>)> {
-        let current_timestamp = utils::unix_now();
-        let mut task_actions = Vec::new();
-
-        let task = self.storage.write_txn(|qr| {
-            // If a task is already running, stop it first by setting its end time.
-            if let Some(mut current) = qr
-                .get()
-                .secondary::<Task>(TaskKey::end, None as Option<u64>)?
-            {
-                current.end = Some(current_timestamp);
-                qr.upsert(current.clone())?;
-                task_actions.push(TaskAction::Upsert(current));
+    ) -> eyre::Result<(Task, TaskChanges)> {
+        let now = utils::unix_now();
+        let current_timestamp = input.at.unwrap_or(now);
+        if current_timestamp > now {
+            eyre::bail!("The task can't start in the future");
+        }
+        let mut changes = TaskChanges::default();
+
+        let task = self.write_changes(&mut changes, |qr, changes| {
+            // If tasks are already running, stop them first by setting their end time.
+            let running: Vec<Task> = running_tasks(qr)?
+                .into_iter()
+                .filter(|task| self.competes(task, &self.computer_name))
+                .collect();
+            if !running.is_empty() {
+                for mut current in running {
+                    if input.at.is_some() && current_timestamp <= current.start {
+                        eyre::bail!(
+                            "The task can't start before the running task '{}' started",
+                            current.task_name
+                        );
+                    }
+                    current.end = Some(current_timestamp.max(current.start));
+                    upsert_task(qr, changes, current)?;
+                }
+            } else if input.at.is_some() {
+                // The scan must be dropped before writing
+                let previous_task = {
+                    let scan = qr.scan().secondary::<Task>(TaskKey::start)?;
+                    let mut last_tasks = scan.all()?.rev();
+                    last_tasks
+                        .find(|task| {
+                            task.as_ref()
+                                .map_or(true, |task| task.computer_name == self.computer_name)
+                        })
+                        .transpose()?
+                };
+
+                if let Some(mut previous_task) = previous_task {
+                    if current_timestamp <= previous_task.start {
+                        eyre::bail!(
+                            "The task can't start before the previous task '{}' started",
+                            previous_task.task_name
+                        );
+                    }
+                    if previous_task.end.is_some_and(|end| end > current_timestamp) {
+                        previous_task.end = Some(current_timestamp);
+                        upsert_task(qr, changes, previous_task)?;
+                    }
+                }
             }
 
             // Create and start the new task with a random ID.
@@ -49,81 +106,339 @@ impl TaskRepositoryInner {
                 .start(current_timestamp)
                 .end(None)
                 .build(); // try_build() already computes the hash
-            qr.upsert(new_task.clone())?;
-            task_actions.push(TaskAction::Upsert(new_task.clone()));
-            Ok(new_task)
+            upsert_task(qr, changes, new_task)
         })?;
 
-        Ok((task, task_actions))
+        Ok((task, changes))
     }
 
-    /// Stops the currently running task by setting its end time.
-    pub async fn stop_current_task(&self) -> eyre::Result<(Option<Task>, Vec<TaskAction>)> {
-        let current_timestamp = utils::unix_now();
-        let mut task_actions = Vec::new();
+    /// Inserts a completed task. Tasks of this computer overlapping it are
+    /// either rejected or trimmed around it depending on `overlap`.
+    pub async fn add_task(
+        &self,
+        input: AddTaskInput,
+        overlap: OverlapStrategy,
+    ) -> eyre::Result<(Task, TaskChanges)> {
+        if input.end <= input.start {
+            eyre::bail!("The task must end after it starts");
+        }
+        if input.end > utils::unix_now() {
+            eyre::bail!("The task can't end in the future");
+        }
+
+        let mut changes = TaskChanges::default();
+
+        let task = self.write_changes(&mut changes, |qr, changes| {
+            let overlapping: Vec<Task> = qr
+                .scan()
+                .secondary::<Task>(TaskKey::start)?
+                .range(0..input.end)?
+                .collect::<Result<Vec<Task>, _>>()?
+                .into_iter()
+                .filter(|task| {
+                    task.computer_name == self.computer_name
+                        && task.end.is_none_or(|end| end > input.start)
+                })
+                .collect();
+
+            if !overlapping.is_empty() && overlap == OverlapStrategy::Reject {
+                let tasks = overlapping
+                    .iter()
+                    .map(|task| format!("'{}' ({})", task.task_name, task.id))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                eyre::bail!("The task overlaps with existing tasks: {tasks}");
+            }
+
+            for task in overlapping {
+                let covers_start = task.start < input.start;
+                let covers_end = task.end.is_none_or(|end| end > input.end);
+
+                match (covers_start, covers_end) {
+                    // Inside the added task
+                    (false, false) => changes.delete(qr.remove(task)?),
+                    // Keep the part before the added task
+                    (true, false) => {
+                        let mut trimmed = task;
+                        trimmed.end = Some(input.start);
+                        upsert_task(qr, changes, trimmed)?;
+                    }
+                    // Keep the part after the added task
+                    (false, true) => {
+                        let mut trimmed = task;
+                        trimmed.start = input.end;
+                        upsert_task(qr, changes, trimmed)?;
+                    }
+                    // Split around the added task
+                    (true, true) => {
+                        let mut before = task.clone();
+                        before.end = Some(input.start);
+                        upsert_task(qr, changes, before)?;
+
+                        let after = Task {
+                            id: generate_random_id(7),
+                            start: input.end,
+                            ..task
+                        };
+                        upsert_task(qr, changes, after)?;
+                    }
+                }
+            }
 
-        let stopped_task = self.storage.write_txn(|qr| {
-            // Find the currently running task by querying for a task with `end: None`.
-            if let Some(mut current_task) = qr
+            let new_task = Task::builder()
+                .id(generate_random_id(7))
+                .task_name(input.task_name)
+                .project(input.project)
+                .computer_name(self.computer_name.clone())
+                .tags(input.tags)
+                .start(input.start)
+                .end(Some(input.end))
+                .build();
+            upsert_task(qr, changes, new_task)
+        })?;
+
+        Ok((task, changes))
+    }
+
+    /// Ends a task at `input.at` and creates the task taking over from there
+    /// until the original end, returning both parts.
+    pub async fn split_task(
+        &self,
+        task_id: TaskId,
+        input: SplitTaskInput,
+    ) -> eyre::Result<(Task, Task, TaskChanges)> {
+        let mut changes = TaskChanges::default();
+        let now = utils::unix_now();
+
+        let (first, second) = self.write_changes(&mut changes, |qr, changes| {
+            let original = qr
                 .get()
-                .secondary::<Task>(TaskKey::end, None as Option<u64>)?
+                .primary::<Task>(task_id.clone())?
+                .ok_or_else(|| eyre::eyre!("Task with ID '{}' not found", &task_id))?;
+
+            if input.at <= original.start || input.at >= original.end.unwrap_or(now) {
+                eyre::bail!(
+                    "The split time must be between the start and the end of '{}'",
+                    original.task_name
+                );
+            }
+
+            let mut first = original.clone();
+            first.end = Some(input.at);
+            let first = upsert_task(qr, changes, first)?;
+
+            let second = Task::builder()
+                .id(generate_random_id(7))
+                .task_name(input.task_name)
+                .project(input.project)
+                .computer_name(original.computer_name)
+                .tags(input.tags)
+                .start(input.at)
+                .end(original.end)
+                .build();
+            let second = upsert_task(qr, changes, second)?;
+
+            Ok((first, second))
+        })?;
+
+        Ok((first, second, changes))
+    }
+
+    /// Merges tasks into the first one, spanning the union of their time ranges.
+    /// The other tasks are deleted. Fails when the merged range would overlap a
+    /// task that isn't merged.
+    pub async fn merge_tasks(&self, task_ids: Vec<TaskId>) -> eyre::Result<(Task, TaskChanges)> {
+        let mut changes = TaskChanges::default();
+
+        let task = self.write_changes(&mut changes, |qr, changes| {
+            let mut tasks: Vec<Task> = Vec::new();
+            for task_id in &task_ids {
+                if tasks.iter().any(|task| &task.id == task_id) {
+                    continue;
+                }
+                let task = qr
+                    .get()
+                    .primary::<Task>(task_id.clone())?
+                    .ok_or_else(|| eyre::eyre!("Task with ID '{}' not found", task_id))?;
+                tasks.push(task);
+            }
+            if tasks.len() < 2 {
+                eyre::bail!("At least two different tasks are needed to merge");
+            }
+
+            let original = tasks[0].clone();
+            if let Some(other) = tasks
+                .iter()
+                .find(|task| task.computer_name != original.computer_name)
             {
+                eyre::bail!(
+                    "'{}' was tracked on another computer ({})",
+                    other.task_name,
+                    other.computer_name
+                );
+            }
+
+            let start = tasks
+                .iter()
+                .map(|task| task.start)
+                .min()
+                .unwrap_or_default();
+            let end = tasks
+                .iter()
+                .map(|task| task.end)
+                .reduce(|a, b| a.zip(b).map(|(a, b)| a.max(b)))
+                .flatten();
+
+            let in_between: Vec<Task> = qr
+                .scan()
+                .secondary::<Task>(TaskKey::start)?
+                .range(0..end.unwrap_or(u64::MAX))?
+                .collect::<Result<Vec<Task>, _>>()?
+                .into_iter()
+                .filter(|task| {
+                    task.computer_name == original.computer_name
+                        && task.end.is_none_or(|task_end| task_end > start)
+                        && !tasks.iter().any(|merged| merged.id == task.id)
+                })
+                .collect();
+            if let Some(task) = in_between.first() {
+                eyre::bail!(
+                    "The merged task would overlap '{}' ({})",
+                    task.task_name,
+                    task.id
+                );
+            }
+
+            for task in &tasks[1..] {
+                changes.delete(qr.remove(task.clone())?);
+            }
+
+            let mut merged = original;
+            merged.start = start;
+            merged.end = end;
+            upsert_task(qr, changes, merged)
+        })?;
+
+        Ok((task, changes))
+    }
+
+    /// Stops the currently running task by setting its end time, never before its start.
+    pub async fn stop_current_task_at(
+        &self,
+        end: u64,
+    ) -> eyre::Result<(Option<Task>, TaskChanges)> {
+        self.stop_current_task_with(|task| Ok(end.max(task.start)))
+    }
+
+    /// Stops the currently running task at a time given by the user, which
+    /// must be between the task start and now.
+    pub async fn stop_current_task_backdated(
+        &self,
+        end: u64,
+    ) -> eyre::Result<(Option<Task>, TaskChanges)> {
+        self.stop_current_task_with(|task| {
+            if end <= task.start {
+                eyre::bail!("The task can't stop before it started");
+            }
+            if end > utils::unix_now() {
+                eyre::bail!("The task can't stop in the future");
+            }
+            Ok(end)
+        })
+    }
+
+    /// Stops the currently running task at the end computed from it.
+    fn stop_current_task_with(
+        &self,
+        end_of: impl FnOnce(&Task) -> eyre::Result<u64>,
+    ) -> eyre::Result<(Option<Task>, TaskChanges)> {
+        let mut changes = TaskChanges::default();
+
+        let stopped_task = self.write_changes(&mut changes, |qr, changes| {
+            // Find the currently running task among the tasks with `end: None`.
+            if let Some(mut current_task) = self.current_task(running_tasks(qr)?) {
                 // Update its end time and recompute the hash.
-                current_task.end = Some(current_timestamp);
+                current_task.end = Some(end_of(&current_task)?);
 
                 // Save it and record the action.
-                qr.upsert(current_task.clone())?;
-                task_actions.push(TaskAction::Upsert(current_task.clone()));
-                Ok(Some(current_task))
+                Ok(Some(upsert_task(qr, changes, current_task)?))
             } else {
                 Ok(None)
             }
         })?;
 
-        Ok((stopped_task, task_actions))
+        Ok((stopped_task, changes))
+    }
+
+    /// Removes a pause from the running task: the task ends at `pause_start`
+    /// and a copy of it starts at `pause_end`.
+    pub async fn pause_current_task(
+        &self,
+        pause_start: u64,
+        pause_end: u64,
+    ) -> eyre::Result<(Option<Task>, TaskChanges)> {
+        let mut changes = TaskChanges::default();
+
+        let resumed_task = self.write_changes(&mut changes, |qr, changes| {
+            let Some(mut current) = self.current_task(running_tasks(qr)?) else {
+                return Ok(None);
+            };
+
+            if pause_start < current.start || pause_end < pause_start {
+                eyre::bail!("The pause doesn't happen while the task is running");
+            }
+
+            current.end = Some(pause_start);
+            let current = upsert_task(qr, changes, current)?;
+
+            let resumed_task = Task {
+                id: generate_random_id(7),
+                start: pause_end,
+                end: None,
+                clock: FieldClock::default(),
+                ..current
+            };
+            Ok(Some(upsert_task(qr, changes, resumed_task)?))
+        })?;
+
+        Ok((resumed_task, changes))
     }
 
     /// Cancels and deletes the currently running task.
-    pub async fn cancel_current_task(&self) -> eyre::Result<(Option<Task>, Vec<TaskAction>)> {
-        let mut task_actions = Vec::new();
+    pub async fn cancel_current_task(&self) -> eyre::Result<(Option<Task>, TaskChanges)> {
+        let mut changes = TaskChanges::default();
 
-        let canceled_task = self.storage.write_txn(|qr| {
+        let canceled_task = self.write_changes(&mut changes, |qr, changes| {
             // Find the currently running task.
-            if let Some(current_task) = qr
-                .get()
-                .secondary::<Task>(TaskKey::end, None as Option<u64>)?
-            {
-                let task_id = current_task.id.clone();
+            if let Some(current_task) = self.current_task(running_tasks(qr)?) {
                 // Remove the task entity itself.
-                qr.remove(current_task.clone())?;
-                task_actions.push(TaskAction::Delete(task_id));
+                changes.delete(qr.remove(current_task.clone())?);
                 Ok(Some(current_task))
             } else {
                 Ok(None)
             }
         })?;
 
-        Ok((canceled_task, task_actions))
+        Ok((canceled_task, changes))
     }
 
     /// Deletes a task by its specific ID.
     pub async fn delete_task(
         &self,
         task_id: String,
-    ) -> eyre::Result<(Option<Task>, Vec<TaskAction>)> {
-        let mut task_actions = Vec::new();
+    ) -> eyre::Result<(Option<Task>, TaskChanges)> {
+        let mut changes = TaskChanges::default();
 
-        let deleted_task = self.storage.write_txn(|qr| {
+        let deleted_task = self.write_changes(&mut changes, |qr, changes| {
             if let Some(task_to_delete) = qr.get().primary::<Task>(task_id.clone())? {
-                qr.remove(task_to_delete.clone())?;
-                task_actions.push(TaskAction::Delete(task_id));
+                changes.delete(qr.remove(task_to_delete.clone())?);
                 Ok(Some(task_to_delete))
             } else {
                 Ok(None)
             }
         })?;
 
-        Ok((deleted_task, task_actions))
+        Ok((deleted_task, changes))
     }
 
     /// Edits an existing task, identified by its ID or as the "current" task.
@@ -131,15 +446,14 @@ impl TaskRepositoryInner {
         &self,
         task_ref: TaskRef,
         update_task: TaskUpdate,
-    ) -> eyre::Result<(Task, Vec<TaskAction>)> {
-        let mut task_actions = Vec::new();
+    ) -> eyre::Result<(Task, TaskChanges)> {
+        let mut changes = TaskChanges::default();
         let current_timestamp = utils::unix_now();
 
-        let task = self.storage.write_txn(|qr| {
+        let task = self.write_changes(&mut changes, |qr, changes| {
             let task_id = match task_ref {
                 TaskRef::Current => {
-                    qr.get()
-                        .secondary::<Task>(TaskKey::end, None as Option<u64>)?
+                    self.current_task(running_tasks(qr)?)
                         .ok_or_else(|| eyre::eyre!("No current task to edit"))?
                         .id
                 }
@@ -159,25 +473,244 @@ impl TaskRepositoryInner {
             // If this edit makes a task running (i.e., resumes it),
             // we must first stop any other task that is currently running.
             if is_now_running && !was_running {
-                if let Some(mut other_current_task) = qr
-                    .get()
-                    .secondary::<Task>(TaskKey::end, None as Option<u64>)?
-                {
-                    if other_current_task.id != new_task.id {
-                        other_current_task.end = Some(current_timestamp);
-                        qr.upsert(other_current_task.clone())?;
-                        task_actions.push(TaskAction::Upsert(other_current_task));
+                let others: Vec<Task> = running_tasks(qr)?
+                    .into_iter()
+                    .filter(|task| {
+                        task.id != new_task.id && self.competes(task, &new_task.computer_name)
+                    })
+                    .collect();
+                for mut other_current_task in others {
+                    other_current_task.end = Some(current_timestamp);
+                    upsert_task(qr, changes, other_current_task)?;
+                }
+            }
+
+            upsert_task(qr, changes, new_task)
+        })?;
+
+        Ok((task, changes))
+    }
+
+    /// Applies actions made on this device to the store. Tasks that are
+    /// already up to date are skipped.
+    pub async fn apply_actions(&self, actions: Vec<TaskAction>) -> eyre::Result<TaskChanges> {
+        let mut changes = TaskChanges::default();
+        self.write_changes(&mut changes, |qr, changes| {
+            apply_local_actions(qr, changes, actions)
+        })?;
+
+        Ok(changes)
+    }
+
+    /// Applies actions reverting or replaying a previous mutation, provided
+    /// the tasks are still in the state left by `expected`. Fails when a task
+    /// was modified since, e.g. by another mutation or a synchronization.
+    pub async fn replay_actions(
+        &self,
+        expected: &[TaskAction],
+        actions: Vec<TaskAction>,
+    ) -> eyre::Result<TaskChanges> {
+        let mut changes = TaskChanges::default();
+        self.write_changes(&mut changes, |qr, changes| {
+            ensure_unchanged(qr, expected)?;
+            apply_local_actions(qr, changes, actions)
+        })?;
+
+        Ok(changes)
+    }
+
+    /// Applies actions received from another device, merging the fields of
+    /// tasks edited on both sides. Fields edited on both sides since they were
+    /// last exchanged keep their local value and are returned as conflicts for
+    /// the user to resolve. Deleted tasks are only brought back by edits the
+    /// deleted version didn't include.
+    pub async fn import_actions(
+        &self,
+        actions: Vec<TaskAction>,
+    ) -> eyre::Result<(TaskChanges, Vec<TaskConflict>)> {
+        let mut changes = TaskChanges::default();
+        let mut conflicts = Vec::new();
+
+        self.storage.write_txn(|qr| {
+            for action in actions {
+                match action {
+                    TaskAction::Upsert(remote) => {
+                        let Some(local) = qr.get().primary::<Task>(remote.id.clone())? else {
+                            let tombstone = qr.get().primary::<TaskTombstone>(remote.id.clone())?;
+                            if let Some(tombstone) = tombstone {
+                                if tombstone.clock.includes(&remote.clock) {
+                                    continue;
+                                }
+                                qr.remove(tombstone)?;
+                            }
+                            set_sync_base(qr, &remote.id, remote.clock)?;
+                            qr.upsert(remote.clone())?;
+                            changes.upsert(remote, None);
+                            continue;
+                        };
+
+                        let base = qr
+                            .get()
+                            .primary::<TaskSyncBase>(local.id.clone())?
+                            .map(|base| base.clock);
+                        let merge = merge_task(&local, &remote, base.as_ref());
+                        set_sync_base(qr, &local.id, merge.base)?;
+                        if local.get_hash() == remote.get_hash() {
+                            remove_conflicts(qr, &local.id)?;
+                            continue;
+                        }
+
+                        let previous_conflicts = remove_conflicts(qr, &local.id)?;
+                        if !merge.conflicts.is_empty() {
+                            // Keep the ID the user may already have seen
+                            let id = previous_conflicts
+                                .into_iter()
+                                .next()
+                                .map_or_else(|| generate_random_id(7), |conflict| conflict.id);
+                            let conflict = TaskConflict {
+                                id,
+                                task_id: local.id.clone(),
+                                fields: merge.conflicts,
+                                local: merge.task.clone(),
+                                remote,
+                                detected_at: utils::unix_now(),
+                            };
+                            qr.insert(conflict.clone())?;
+                            conflicts.push(conflict);
+                        }
+                        if merge.task != local {
+                            qr.upsert(merge.task.clone())?;
+                            changes.upsert(merge.task, Some(local));
+                        }
+                    }
+                    TaskAction::Delete(task_id) => {
+                        remove_conflicts(qr, &task_id)?;
+                        // The deleted version is the one held here, as far as known
+                        if let Some(existing) = qr.get().primary::<Task>(task_id.clone())? {
+                            bury_task(qr, &task_id, existing.clock)?;
+                            changes.delete(qr.remove(existing)?);
+                        } else if qr
+                            .get()
+                            .primary::<TaskTombstone>(task_id.clone())?
+                            .is_none()
+                        {
+                            bury_task(qr, &task_id, FieldClock::default())?;
+                        }
                     }
                 }
             }
+            self.stop_concurrent_tasks(qr, &mut changes)
+        })?;
+
+        Ok((changes, conflicts))
+    }
+
+    pub async fn list_conflicts(&self) -> eyre::Result<Vec<TaskConflict>> {
+        self.storage.read_txn(|qr| {
+            let mut conflicts = qr
+                .scan()
+                .primary::<TaskConflict>()?
+                .all()?
+                .collect::<Result<Vec<_>, _>>()?;
+            conflicts.sort_by_key(|conflict| conflict.detected_at);
+            Ok(conflicts)
+        })
+    }
+
+    /// Keeps the local or remote values of the conflicting fields, marking
+    /// them as edited now so that the choice wins on the other devices.
+    pub async fn resolve_conflict(
+        &self,
+        conflict_id: String,
+        resolution: ConflictResolution,
+    ) -> eyre::Result<(Task, TaskChanges)> {
+        let mut changes = TaskChanges::default();
+        let now = utils::unix_now();
+
+        let task = self.write_changes(&mut changes, |qr, changes| {
+            let conflict = qr
+                .get()
+                .primary::<TaskConflict>(conflict_id.clone())?
+                .ok_or_else(|| eyre::eyre!("Conflict with ID '{}' not found", &conflict_id))?;
+            let current = qr
+                .get()
+                .primary::<Task>(conflict.task_id.clone())?
+                .ok_or_else(|| eyre::eyre!("Task with ID '{}' was deleted", &conflict.task_id))?;
 
-            qr.upsert(new_task.clone())?;
-            task_actions.push(TaskAction::Upsert(new_task.clone()));
+            // The remote edits are settled, only the choice is newer than the base
+            let mut base = qr
+                .get()
+                .primary::<TaskSyncBase>(current.id.clone())?
+                .map_or(conflict.remote.clock, |base| base.clock);
+            let mut task = current.clone();
+            for field in &conflict.fields {
+                if resolution == ConflictResolution::Remote {
+                    task.copy_field(&conflict.remote, *field);
+                }
+                task.clock.set(*field, now);
+                base.set(
+                    *field,
+                    base.get(*field).max(conflict.remote.clock.get(*field)),
+                );
+            }
+            set_sync_base(qr, &task.id, base)?;
 
-            Ok(new_task)
+            qr.remove(conflict)?;
+            qr.upsert(task.clone())?;
+            changes.upsert(task.clone(), Some(current));
+            Ok(task)
         })?;
 
-        Ok((task, task_actions))
+        Ok((task, changes))
+    }
+
+    /// Records the versions of tasks sent to other devices as the base of
+    /// the next merges, e.g. once pushed to a server.
+    pub async fn mark_synced(&self, actions: &[TaskAction]) -> eyre::Result<()> {
+        self.storage.write_txn(|qr| {
+            for action in actions {
+                let TaskAction::Upsert(task) = action else {
+                    continue;
+                };
+                let mut clock = task.clock;
+                if let Some(base) = qr.get().primary::<TaskSyncBase>(task.id.clone())? {
+                    for field in TaskField::ALL {
+                        clock.set(field, clock.get(field).max(base.clock.get(field)));
+                    }
+                }
+                set_sync_base(qr, &task.id, clock)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs a mutation and appends its actions to the operation log in the
+    /// same transaction, so that the log never misses a saved change. The
+    /// deleted tasks are kept as tombstones.
+    fn write_changes<R>(
+        &self,
+        changes: &mut TaskChanges,
+        mutation: impl FnOnce(&mut RwTransaction, &mut TaskChanges) -> eyre::Result<R>,
+    ) -> eyre::Result<R> {
+        self.storage.write_txn(|qr| {
+            let result = mutation(qr, changes)?;
+            for action in &changes.actions {
+                match action {
+                    TaskAction::Upsert(task) => {
+                        if let Some(tombstone) =
+                            qr.get().primary::<TaskTombstone>(task.id.clone())?
+                        {
+                            qr.remove(tombstone)?;
+                        }
+                    }
+                    TaskAction::Delete(task_id) => {
+                        bury_task(qr, task_id, deleted_clock(changes, task_id))?
+                    }
+                }
+            }
+            append_operations(qr, &self.computer_name, &changes.actions)?;
+            Ok(result)
+        })
     }
 
     pub async fn match_prefix(&self, task_id_prefix: TaskId) -> eyre::Result<Vec<Task>> {
@@ -192,6 +725,66 @@ impl TaskRepositoryInner {
         })
     }
 
+    pub async fn get_current_task(&self) -> eyre::Result<Option<Task>> {
+        self.storage.read_txn(|qr| {
+            let running = qr
+                .scan()
+                .secondary::<Task>(TaskKey::end)?
+                .range(None::<u64>..=None)?
+                .collect::<Result<Vec<Task>, _>>()?;
+            Ok(self.current_task(running))
+        })
+    }
+
+    /// Whether `task` has to stop for a task of `computer_name` to run.
+    fn competes(&self, task: &Task, computer_name: &str) -> bool {
+        match self.running_task_policy {
+            RunningTaskPolicy::Single => true,
+            RunningTaskPolicy::PerComputer => task.computer_name == computer_name,
+        }
+    }
+
+    /// The task running for this computer among `running`, the most recent
+    /// one if synchronization left several of them.
+    fn current_task(&self, running: Vec<Task>) -> Option<Task> {
+        running
+            .into_iter()
+            .filter(|task| self.competes(task, &self.computer_name))
+            .max_by(|a, b| (a.start, &a.id).cmp(&(b.start, &b.id)))
+    }
+
+    /// Stops the older of the tasks started at the same time on several
+    /// computers at the start of the newer one. Every device settles them the
+    /// same way, so that they converge.
+    fn stop_concurrent_tasks(
+        &self,
+        qr: &RwTransaction,
+        changes: &mut TaskChanges,
+    ) -> eyre::Result<()> {
+        let mut running = running_tasks(qr)?;
+        // Most recent first, the ID deciding between tasks started at the same time
+        running.sort_by(|a, b| (b.start, &b.id).cmp(&(a.start, &a.id)));
+
+        let mut kept: Vec<Task> = Vec::new();
+        for mut task in running {
+            let Some(newer) = kept
+                .iter()
+                .find(|newer| self.competes(&task, &newer.computer_name))
+            else {
+                kept.push(task);
+                continue;
+            };
+
+            let previous = task.clone();
+            task.end = Some(newer.start.max(task.start));
+            task.clock
+                .set(TaskField::End, task.clock.end.max(newer.clock.start));
+            qr.upsert(task.clone())?;
+            changes.upsert(task, Some(previous));
+        }
+        Ok(())
+    }
+
     pub async fn get_task_by_id(&self, task_id: TaskId) -> eyre::Result<Option<Task>> {
         self.storage
             .read_txn(|qr| Ok(qr.get().primary::<Task>(task_id)?))
@@ -228,3 +821,125 @@ impl TaskRepositoryInner {
         })
     }
 }
+
+/// Saves a task edited on this device, timestamping the fields that changed
+/// so that the edit wins over older ones made elsewhere.
+fn upsert_task(
+    qr: &RwTransaction,
+    changes: &mut TaskChanges,
+    mut task: Task,
+) -> eyre::Result<Task> {
+    let previous = qr.get().primary::<Task>(task.id.clone())?;
+    task.stamp_changes(previous.as_ref(), utils::unix_now());
+    qr.upsert(task.clone())?;
+    changes.upsert(task.clone(), previous);
+    Ok(task)
+}
+
+fn apply_local_actions(
+    qr: &RwTransaction,
+    changes: &mut TaskChanges,
+    actions: Vec<TaskAction>,
+) -> eyre::Result<()> {
+    for action in actions {
+        match action {
+            TaskAction::Upsert(task) => {
+                let existing = qr.get().primary::<Task>(task.id.clone())?;
+                if existing.as_ref().is_some_and(|t| t.get_hash() == task.get_hash()) {
+                    continue;
+                }
+                upsert_task(qr, changes, task)?;
+            }
+            TaskAction::Delete(task_id) => {
+                if let Some(existing) = qr.get().primary::<Task>(task_id)? {
+                    changes.delete(qr.remove(existing)?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that each task touched by `actions` is in the state the last of
+/// them left it in.
+fn ensure_unchanged(qr: &RwTransaction, actions: &[TaskAction]) -> eyre::Result<()> {
+    let mut expected: HashMap<&str, Option<u64>> = HashMap::new();
+    for action in actions {
+        match action {
+            TaskAction::Upsert(task) => expected.insert(&task.id, Some(task.get_hash())),
+            TaskAction::Delete(task_id) => expected.insert(task_id, None),
+        };
+    }
+
+    for (task_id, hash) in expected {
+        let current = qr
+            .get()
+            .primary::<Task>(task_id.to_string())?
+            .map(|task| task.get_hash());
+        if current != hash {
+            eyre::bail!("Task '{task_id}' was modified since, the change can't be replayed");
+        }
+    }
+    Ok(())
+}
+
+/// Tasks without an end, several of them possibly running on different computers.
+fn running_tasks(qr: &RwTransaction) -> eyre::Result<Vec<Task>> {
+    Ok(qr
+        .scan()
+        .secondary::<Task>(TaskKey::end)?
+        .range(None::<u64>..=None)?
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn set_sync_base(qr: &RwTransaction, task_id: &str, clock: FieldClock) -> eyre::Result<()> {
+    qr.upsert(TaskSyncBase {
+        task_id: task_id.to_string(),
+        clock,
+    })?;
+    Ok(())
+}
+
+/// Replaces the sync base of a deleted task by its tombstone, `clock` being
+/// the one of the deleted version.
+fn bury_task(qr: &RwTransaction, task_id: &str, clock: FieldClock) -> eyre::Result<()> {
+    if let Some(base) = qr.get().primary::<TaskSyncBase>(task_id.to_string())? {
+        qr.remove(base)?;
+    }
+    qr.upsert(TaskTombstone {
+        task_id: task_id.to_string(),
+        clock,
+    })?;
+    Ok(())
+}
+
+/// Clock of the version of a task deleted by `changes`, restored by the
+/// latest of its revert actions.
+fn deleted_clock(changes: &TaskChanges, task_id: &str) -> FieldClock {
+    changes
+        .revert_actions
+        .iter()
+        .find_map(|action| match action {
+            TaskAction::Upsert(task) if task.id == task_id => Some(task.clock),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Drops the pending conflicts of a task, returning them.
+fn remove_conflicts(qr: &RwTransaction, task_id: &str) -> eyre::Result<Vec<TaskConflict>> {
+    let conflicts = qr
+        .scan()
+        .secondary::<TaskConflict>(TaskConflictKey::task_id)?
+        .start_with(task_id.to_string())?
+        .filter(|conflict| {
+            conflict
+                .as_ref()
+                .map_or(true, |conflict| conflict.task_id == task_id)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for conflict in &conflicts {
+        qr.remove(conflict.clone())?;
+    }
+    Ok(conflicts)
+}
@@ -0,0 +1,723 @@
+use super::*;
+use crate::entities::MODELS;
+use tempfile::tempdir;
+
+fn setup_repository() -> (tempfile::TempDir, TaskRepository) {
+    setup_repository_with_policy(RunningTaskPolicy::Single)
+}
+
+fn setup_repository_with_policy(policy: RunningTaskPolicy) -> (tempfile::TempDir, TaskRepository) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let repository = TaskRepository::builder()
+        .storage(storage)
+        .computer_name("laptop".to_string())
+        .running_task_policy(policy)
+        .build();
+    (dir, repository)
+}
+
+fn add_input(task_name: &str, start: u64, end: u64) -> AddTaskInput {
+    AddTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        start,
+        end,
+    }
+}
+
+#[tokio::test]
+async fn test_add_task_rejects_invalid_range() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+
+    let reversed = repository
+        .add_task(add_input("meeting", 2_000, 1_000), OverlapStrategy::Reject)
+        .await;
+    assert!(reversed.is_err());
+
+    let future = utils::unix_now() + 3_600_000;
+    let in_future = repository
+        .add_task(add_input("meeting", 1_000, future), OverlapStrategy::Reject)
+        .await;
+    assert!(in_future.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_task_rejects_overlap() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+
+    let err = repository
+        .add_task(add_input("review", 4_000, 8_000), OverlapStrategy::Reject)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("'meeting'"));
+
+    // Adjacent tasks don't overlap
+    repository
+        .add_task(add_input("review", 5_000, 8_000), OverlapStrategy::Reject)
+        .await?;
+    assert_eq!(repository.list_task_range(0, 10_000).await?.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_task_ignores_other_computers() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let other = TaskRepository::builder()
+        .storage(repository.storage.clone())
+        .computer_name("desktop".to_string())
+        .build();
+    other
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+
+    repository
+        .add_task(add_input("review", 2_000, 4_000), OverlapStrategy::Reject)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_task_trims_overlapping_tasks() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let before = repository
+        .add_task(add_input("before", 1_000, 3_000), OverlapStrategy::Reject)
+        .await?
+        .0;
+    let inside = repository
+        .add_task(add_input("inside", 4_000, 5_000), OverlapStrategy::Reject)
+        .await?
+        .0;
+    let after = repository
+        .add_task(add_input("after", 6_000, 9_000), OverlapStrategy::Reject)
+        .await?
+        .0;
+
+    let (added, changes) = repository
+        .add_task(add_input("meeting", 2_000, 7_000), OverlapStrategy::Trim)
+        .await?;
+
+    let before = repository.get_task_by_id(before.id).await?.unwrap();
+    assert_eq!((before.start, before.end), (1_000, Some(2_000)));
+    assert!(repository.get_task_by_id(inside.id).await?.is_none());
+    let after = repository.get_task_by_id(after.id).await?.unwrap();
+    assert_eq!((after.start, after.end), (7_000, Some(9_000)));
+    assert_eq!((added.start, added.end), (2_000, Some(7_000)));
+
+    // Reverting restores the original tasks
+    repository.apply_actions(changes.revert_actions).await?;
+    let tasks = repository.list_task_range(0, 10_000).await?;
+    let names: Vec<_> = tasks.iter().map(|t| t.task_name.as_str()).collect();
+    assert_eq!(names, vec!["before", "inside", "after"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_task_splits_running_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (running, _) = repository
+        .start_new_task(start_input_at("report", None))
+        .await?;
+    let mut moved_back = running.clone();
+    moved_back.start -= 60_000;
+    repository
+        .apply_actions(vec![TaskAction::Upsert(moved_back.clone())])
+        .await?;
+
+    let start = moved_back.start + 10_000;
+    repository
+        .add_task(
+            add_input("call", start, start + 20_000),
+            OverlapStrategy::Trim,
+        )
+        .await?;
+
+    let first = repository.get_task_by_id(running.id).await?.unwrap();
+    assert_eq!(first.end, Some(start));
+    let resumed = repository.get_current_task().await?.unwrap();
+    assert_eq!(resumed.task_name, "report");
+    assert_eq!(resumed.start, start + 20_000);
+    Ok(())
+}
+
+fn start_input_at(task_name: &str, at: Option<u64>) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at,
+    }
+}
+
+#[tokio::test]
+async fn test_start_at_stops_running_task_at_new_start() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let now = utils::unix_now();
+    let (first, _) = repository
+        .start_new_task(start_input_at("report", Some(now - 60_000)))
+        .await?;
+
+    let (second, _) = repository
+        .start_new_task(start_input_at("call", Some(now - 20_000)))
+        .await?;
+    assert_eq!(second.start, now - 20_000);
+
+    let first = repository.get_task_by_id(first.id).await?.unwrap();
+    assert_eq!(first.end, Some(now - 20_000));
+
+    // Can't start before the running task
+    let before = repository
+        .start_new_task(start_input_at("email", Some(now - 30_000)))
+        .await;
+    assert!(before.is_err());
+    assert_eq!(repository.get_current_task().await?.unwrap().id, second.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_start_at_trims_previous_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let now = utils::unix_now();
+    let (previous, _) = repository
+        .add_task(
+            add_input("meeting", now - 60_000, now - 10_000),
+            OverlapStrategy::Reject,
+        )
+        .await?;
+
+    repository
+        .start_new_task(start_input_at("report", Some(now - 30_000)))
+        .await?;
+    let previous = repository.get_task_by_id(previous.id).await?.unwrap();
+    assert_eq!(previous.end, Some(now - 30_000));
+
+    let in_future = repository
+        .start_new_task(start_input_at("later", Some(now + 60_000)))
+        .await;
+    assert!(in_future.is_err());
+    Ok(())
+}
+
+fn split_input(task_name: &str, at: u64) -> SplitTaskInput {
+    SplitTaskInput {
+        at,
+        task_name: task_name.to_string(),
+        project: Some("o324".to_string()),
+        tags: vec!["review".to_string()],
+    }
+}
+
+#[tokio::test]
+async fn test_split_task_at_time() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("coding", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+
+    let (first, second, changes) = repository
+        .split_task(task.id.clone(), split_input("meeting", 3_000))
+        .await?;
+
+    assert_eq!((first.id.clone(), first.end), (task.id, Some(3_000)));
+    assert_eq!(second.task_name, "meeting");
+    assert_eq!(second.project.as_deref(), Some("o324"));
+    assert_eq!((second.start, second.end), (3_000, Some(5_000)));
+    assert_eq!(changes.actions.len(), 2);
+    assert_eq!(repository.list_task_range(0, 10_000).await?.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_split_running_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let start = utils::unix_now() - 3_600_000;
+    let (task, _) = repository
+        .start_new_task(start_input_at("coding", Some(start)))
+        .await?;
+
+    let (first, second, _) = repository
+        .split_task(task.id, split_input("meeting", start + 60_000))
+        .await?;
+
+    assert_eq!(first.end, Some(start + 60_000));
+    assert_eq!(second.end, None);
+    let current = repository.get_current_task().await?.unwrap();
+    assert_eq!(current.id, second.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_split_task_rejects_time_outside_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("coding", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+
+    for at in [1_000, 500, 5_000, 9_000] {
+        let split = repository
+            .split_task(task.id.clone(), split_input("meeting", at))
+            .await;
+        assert!(split.is_err(), "splitting at {at} should fail");
+    }
+    assert_eq!(repository.list_task_range(0, 10_000).await?.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_tasks_spans_union() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (first, _) = repository
+        .add_task(add_input("coding", 3_000, 4_000), OverlapStrategy::Reject)
+        .await?;
+    let (second, _) = repository
+        .add_task(
+            add_input("coding again", 1_000, 2_000),
+            OverlapStrategy::Reject,
+        )
+        .await?;
+    let (third, _) = repository
+        .add_task(
+            add_input("more coding", 4_500, 6_000),
+            OverlapStrategy::Reject,
+        )
+        .await?;
+
+    let (merged, changes) = repository
+        .merge_tasks(vec![first.id.clone(), second.id.clone(), third.id.clone()])
+        .await?;
+
+    assert_eq!(merged.id, first.id);
+    assert_eq!(merged.task_name, "coding");
+    assert_eq!((merged.start, merged.end), (1_000, Some(6_000)));
+    assert_eq!(changes.actions.len(), 3);
+    let tasks = repository.list_task_range(0, 10_000).await?;
+    assert_eq!(tasks.len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_tasks_keeps_running_task_running() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let start = utils::unix_now() - 3_600_000;
+    let (previous, _) = repository
+        .add_task(
+            add_input("coding", start, start + 60_000),
+            OverlapStrategy::Reject,
+        )
+        .await?;
+    let (running, _) = repository
+        .start_new_task(start_input_at("coding", Some(start + 120_000)))
+        .await?;
+
+    let (merged, _) = repository
+        .merge_tasks(vec![previous.id.clone(), running.id])
+        .await?;
+
+    assert_eq!((merged.start, merged.end), (start, None));
+    assert_eq!(
+        repository.get_current_task().await?.unwrap().id,
+        previous.id
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_tasks_rejects_tasks_in_between() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (first, _) = repository
+        .add_task(add_input("coding", 1_000, 2_000), OverlapStrategy::Reject)
+        .await?;
+    repository
+        .add_task(add_input("meeting", 2_000, 3_000), OverlapStrategy::Reject)
+        .await?;
+    let (last, _) = repository
+        .add_task(add_input("coding", 3_000, 4_000), OverlapStrategy::Reject)
+        .await?;
+
+    let merged = repository
+        .merge_tasks(vec![first.id.clone(), last.id.clone()])
+        .await;
+    assert!(merged.is_err());
+    assert!(repository
+        .merge_tasks(vec![first.id.clone(), first.id])
+        .await
+        .is_err());
+    assert_eq!(repository.list_task_range(0, 10_000).await?.len(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_edit_task_stamps_changed_fields() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    let created_at = task.clock.task_name;
+    assert!(TaskField::ALL
+        .iter()
+        .all(|field| task.clock.get(*field) == created_at));
+
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let update = TaskUpdate {
+        task_name: Some("review".to_string()),
+        ..Default::default()
+    };
+    let (edited, _) = repository
+        .edit_task(TaskRef::Id(task.id.clone()), update)
+        .await?;
+
+    assert!(edited.clock.task_name > created_at);
+    assert_eq!(edited.clock.start, created_at);
+    assert_eq!(edited.clock.end, created_at);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pause_keeps_every_field_of_the_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let other = TaskRepository::builder()
+        .storage(repository.storage.clone())
+        .computer_name("desktop".to_string())
+        .build();
+    let now = utils::unix_now();
+    let (task, _) = other
+        .start_new_task(StartTaskInput {
+            task_name: "report".to_string(),
+            project: Some("o324".to_string()),
+            tags: vec!["writing".to_string()],
+            at: Some(now - 10_000),
+        })
+        .await?;
+    let update = TaskUpdate {
+        notes: Some(Some("first draft".to_string())),
+        ..Default::default()
+    };
+    other
+        .edit_task(TaskRef::Id(task.id.clone()), update)
+        .await?;
+
+    // Paused from this computer, the running task being shared
+    let (resumed, _) = repository
+        .pause_current_task(now - 8_000, now - 2_000)
+        .await?;
+    let resumed = resumed.expect("the running task should be resumed");
+
+    assert_ne!(resumed.id, task.id);
+    assert_eq!((resumed.start, resumed.end), (now - 2_000, None));
+    assert_eq!(resumed.task_name, "report");
+    assert_eq!(resumed.project.as_deref(), Some("o324"));
+    assert_eq!(resumed.tags, vec!["writing".to_string()]);
+    assert_eq!(resumed.notes.as_deref(), Some("first draft"));
+    assert_eq!(resumed.computer_name, "desktop");
+    Ok(())
+}
+
+/// A copy of `task` as edited on another device.
+fn remote_edit(task: &Task, field: TaskField, at: u64, edit: impl FnOnce(&mut Task)) -> Task {
+    let mut remote = task.clone();
+    edit(&mut remote);
+    remote.clock.set(field, at);
+    remote
+}
+
+#[tokio::test]
+async fn test_import_merges_fields_edited_on_both_devices() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let update = TaskUpdate {
+        project: Some(Some("o324".to_string())),
+        ..Default::default()
+    };
+    let (local, _) = repository
+        .edit_task(TaskRef::Id(task.id.clone()), update)
+        .await?;
+
+    // The other device renamed the task later, but has an older project
+    let remote = remote_edit(&task, TaskField::TaskName, u64::MAX, |remote| {
+        remote.task_name = "review".to_string();
+    });
+    let (changes, conflicts) = repository
+        .import_actions(vec![TaskAction::Upsert(remote)])
+        .await?;
+
+    assert!(conflicts.is_empty());
+    assert_eq!(changes.actions.len(), 1);
+    let merged = repository.get_task_by_id(task.id).await?.unwrap();
+    assert_eq!(merged.task_name, "review");
+    assert_eq!(merged.project, local.project);
+    assert_eq!(merged.clock.task_name, u64::MAX);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_records_concurrent_edits_as_conflict() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    repository
+        .mark_synced(&[TaskAction::Upsert(task.clone())])
+        .await?;
+
+    // Both devices renamed the synced task, the other one first
+    let remote = remote_edit(
+        &task,
+        TaskField::TaskName,
+        task.clock.task_name + 1,
+        |remote| {
+            remote.task_name = "review".to_string();
+        },
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let update = TaskUpdate {
+        task_name: Some("standup".to_string()),
+        ..Default::default()
+    };
+    let (local, _) = repository
+        .edit_task(TaskRef::Id(task.id.clone()), update)
+        .await?;
+    assert!(local.clock.task_name > remote.clock.task_name);
+
+    let (changes, conflicts) = repository
+        .import_actions(vec![TaskAction::Upsert(remote.clone())])
+        .await?;
+
+    assert!(changes.is_empty());
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].fields, vec![TaskField::TaskName]);
+    assert_eq!(repository.list_conflicts().await?.len(), 1);
+    let stored = repository.get_task_by_id(task.id.clone()).await?.unwrap();
+    assert_eq!(stored.task_name, "standup");
+
+    // Receiving the same values again doesn't duplicate the conflict
+    repository
+        .import_actions(vec![TaskAction::Upsert(remote.clone())])
+        .await?;
+    assert_eq!(repository.list_conflicts().await?.len(), 1);
+
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let (resolved, changes) = repository
+        .resolve_conflict(conflicts[0].id.clone(), ConflictResolution::Local)
+        .await?;
+    assert_eq!(resolved.task_name, "standup");
+    assert!(resolved.clock.task_name > local.clock.task_name);
+    assert_eq!(changes.actions.len(), 1);
+    assert!(repository.list_conflicts().await?.is_empty());
+
+    // The settled remote edit doesn't raise the conflict again
+    let (_, conflicts) = repository
+        .import_actions(vec![TaskAction::Upsert(remote)])
+        .await?;
+    assert!(conflicts.is_empty());
+    let stored = repository.get_task_by_id(task.id).await?.unwrap();
+    assert_eq!(stored.task_name, "standup");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_keeps_edits_made_on_a_single_side() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    repository
+        .mark_synced(&[TaskAction::Upsert(task.clone())])
+        .await?;
+
+    // The other device renamed the task before this one edited its project,
+    // each field being edited on a single side
+    let remote = remote_edit(
+        &task,
+        TaskField::TaskName,
+        task.clock.task_name + 1,
+        |remote| {
+            remote.task_name = "review".to_string();
+        },
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let update = TaskUpdate {
+        project: Some(Some("o324".to_string())),
+        ..Default::default()
+    };
+    repository
+        .edit_task(TaskRef::Id(task.id.clone()), update)
+        .await?;
+
+    let (_, conflicts) = repository
+        .import_actions(vec![TaskAction::Upsert(remote)])
+        .await?;
+
+    assert!(conflicts.is_empty());
+    let merged = repository.get_task_by_id(task.id).await?.unwrap();
+    assert_eq!(merged.task_name, "review");
+    assert_eq!(merged.project.as_deref(), Some("o324"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_doesnt_restore_deleted_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    repository.delete_task(task.id.clone()).await?;
+
+    // A device which didn't receive the deletion yet sends its copy
+    let (changes, _) = repository
+        .import_actions(vec![TaskAction::Upsert(task.clone())])
+        .await?;
+    assert!(changes.is_empty());
+    assert!(repository.get_task_by_id(task.id.clone()).await?.is_none());
+
+    // An edit made after the deletion brings the task back
+    let edited = remote_edit(&task, TaskField::Notes, u64::MAX, |remote| {
+        remote.notes = Some("still needed".to_string());
+    });
+    let (changes, _) = repository
+        .import_actions(vec![TaskAction::Upsert(edited)])
+        .await?;
+    assert_eq!(changes.actions.len(), 1);
+    assert!(repository.get_task_by_id(task.id).await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_restores_task_edited_after_remote_deletion() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    repository
+        .import_actions(vec![TaskAction::Delete(task.id.clone())])
+        .await?;
+
+    // A copy held by a third device stays deleted
+    let (changes, _) = repository
+        .import_actions(vec![TaskAction::Upsert(task.clone())])
+        .await?;
+    assert!(changes.is_empty());
+
+    // An edit the deleted version didn't include brings the task back, even
+    // when received long after the deletion
+    let edited = remote_edit(&task, TaskField::Notes, task.clock.notes + 1, |remote| {
+        remote.notes = Some("still needed".to_string());
+    });
+    let (changes, _) = repository
+        .import_actions(vec![TaskAction::Upsert(edited)])
+        .await?;
+    assert_eq!(changes.actions.len(), 1);
+    assert!(repository.get_task_by_id(task.id).await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_clears_conflict_once_converged() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (task, _) = repository
+        .add_task(add_input("meeting", 1_000, 5_000), OverlapStrategy::Reject)
+        .await?;
+    let remote = remote_edit(&task, TaskField::TaskName, task.clock.task_name, |remote| {
+        remote.task_name = "review".to_string();
+    });
+    repository
+        .import_actions(vec![TaskAction::Upsert(remote)])
+        .await?;
+
+    // The other device resolved the conflict by keeping this device's name
+    let resolved = remote_edit(&task, TaskField::TaskName, u64::MAX, |_| {});
+    repository
+        .import_actions(vec![TaskAction::Upsert(resolved)])
+        .await?;
+
+    assert!(repository.list_conflicts().await?.is_empty());
+    Ok(())
+}
+
+fn start_input(task_name: &str, at: u64) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at: Some(at),
+    }
+}
+
+/// A task started on another computer.
+fn desktop_task(id: &str, start: u64) -> Task {
+    let mut task = Task::builder()
+        .id(id.to_string())
+        .task_name("deploy".to_string())
+        .computer_name("desktop".to_string())
+        .start(start)
+        .build();
+    task.stamp_changes(None, start);
+    task
+}
+
+#[tokio::test]
+async fn test_import_stops_older_running_task() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let (local, _) = repository
+        .start_new_task(start_input("review", 1_000))
+        .await?;
+
+    let (changes, _) = repository
+        .import_actions(vec![TaskAction::Upsert(desktop_task("desktop1", 2_000))])
+        .await?;
+
+    assert_eq!(changes.actions.len(), 2);
+    let local = repository.get_task_by_id(local.id).await?.unwrap();
+    assert_eq!(local.end, Some(2_000));
+    let current = repository.get_current_task().await?.unwrap();
+    assert_eq!(current.id, "desktop1");
+
+    // The newer local task keeps running when an older one is received
+    let (newer, _) = repository
+        .start_new_task(start_input("write", 3_000))
+        .await?;
+    repository
+        .import_actions(vec![TaskAction::Upsert(desktop_task("desktop2", 2_500))])
+        .await?;
+    let older = repository
+        .get_task_by_id("desktop2".to_string())
+        .await?
+        .unwrap();
+    assert_eq!(older.end, Some(3_000));
+    assert_eq!(repository.get_current_task().await?.unwrap().id, newer.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_running_task_per_computer() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository_with_policy(RunningTaskPolicy::PerComputer);
+    let (local, _) = repository
+        .start_new_task(start_input("review", 1_000))
+        .await?;
+
+    repository
+        .import_actions(vec![TaskAction::Upsert(desktop_task("desktop1", 2_000))])
+        .await?;
+    assert_eq!(repository.get_current_task().await?.unwrap().id, local.id);
+
+    // Starting and stopping only affect the tasks of this computer
+    let (next, _) = repository
+        .start_new_task(start_input("write", 3_000))
+        .await?;
+    let (stopped, _) = repository.stop_current_task_at(4_000).await?;
+    assert_eq!(stopped.unwrap().id, next.id);
+
+    let desktop = repository
+        .get_task_by_id("desktop1".to_string())
+        .await?
+        .unwrap();
+    assert_eq!(desktop.end, None);
+    Ok(())
+}
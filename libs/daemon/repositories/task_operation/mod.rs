@@ -0,0 +1,105 @@
+use crate::{
+    core::{storage::Storage, utils},
+    entities::task_operation::TaskOperation,
+    repositories::task::defs::TaskAction,
+};
+use native_db::transaction::RwTransaction;
+use std::{collections::HashSet, sync::Arc};
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+#[wrap_builder(Arc)]
+pub struct TaskOperationRepository {
+    pub computer_name: String,
+    pub storage: Storage,
+}
+
+impl TaskOperationRepositoryInner {
+    /// Appends actions to the operation log, each one receiving the next sequence number.
+    pub async fn append(&self, actions: &[TaskAction]) -> eyre::Result<Vec<TaskOperation>> {
+        if actions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.storage
+            .write_txn(|qr| append_operations(qr, &self.computer_name, actions))
+    }
+
+    /// Sequence number of the last operation of the log.
+    pub async fn last_seq(&self) -> eyre::Result<Option<u64>> {
+        self.storage.read_txn(|qr| {
+            let last_operation = qr
+                .scan()
+                .primary::<TaskOperation>()?
+                .all()?
+                .next_back()
+                .transpose()?;
+
+            Ok(last_operation.map(|op| op.seq))
+        })
+    }
+
+    /// IDs of the tasks with at least one operation in the log.
+    pub async fn list_task_ids(&self) -> eyre::Result<HashSet<String>> {
+        self.storage.read_txn(|qr| {
+            let mut task_ids = HashSet::new();
+            for operation in qr.scan().primary::<TaskOperation>()?.all()? {
+                task_ids.insert(match operation?.action {
+                    TaskAction::Upsert(task) => task.id,
+                    TaskAction::Delete(task_id) => task_id,
+                });
+            }
+
+            Ok(task_ids)
+        })
+    }
+
+    /// Lists at most `count` operations with a sequence number greater or equal to `from_seq`.
+    pub async fn list_since(&self, from_seq: u64, count: u64) -> eyre::Result<Vec<TaskOperation>> {
+        self.storage.read_txn(|qr| {
+            let operations = qr
+                .scan()
+                .primary::<TaskOperation>()?
+                .range(from_seq..)?
+                .take(count as usize)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(operations)
+        })
+    }
+}
+
+/// Appends actions to the operation log within a transaction of the mutation
+/// that produced them, each one receiving the next sequence number.
+pub fn append_operations(
+    qr: &RwTransaction,
+    computer_name: &str,
+    actions: &[TaskAction],
+) -> eyre::Result<Vec<TaskOperation>> {
+    let last_seq = qr
+        .scan()
+        .primary::<TaskOperation>()?
+        .all()?
+        .next_back()
+        .transpose()?
+        .map(|op| op.seq);
+
+    let first_seq = last_seq.map_or(0, |seq| seq + 1);
+    let current_timestamp = utils::unix_now();
+    let mut operations = Vec::new();
+
+    for (seq, action) in (first_seq..).zip(actions) {
+        let operation = TaskOperation {
+            seq,
+            action: action.clone(),
+            computer_name: computer_name.to_string(),
+            at: current_timestamp,
+        };
+        qr.insert(operation.clone())?;
+        operations.push(operation);
+    }
+
+    Ok(operations)
+}
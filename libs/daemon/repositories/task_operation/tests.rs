@@ -0,0 +1,89 @@
+use super::*;
+use crate::{
+    entities::{task::Task, MODELS},
+    repositories::task::{defs::StartTaskInput, TaskRepository},
+};
+use tempfile::tempdir;
+
+fn setup_repository() -> (tempfile::TempDir, TaskOperationRepository) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let repository = TaskOperationRepository::builder()
+        .storage(storage)
+        .computer_name("laptop".to_string())
+        .build();
+    (dir, repository)
+}
+
+fn sample_task(id: &str) -> Task {
+    Task::builder()
+        .id(id.to_string())
+        .task_name("sample".to_string())
+        .computer_name("laptop".to_string())
+        .start(0)
+        .build()
+}
+
+#[tokio::test]
+async fn test_sequence_numbers_are_monotonic() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+
+    let first = repository
+        .append(&[
+            TaskAction::Upsert(sample_task("aaaaaaa")),
+            TaskAction::Upsert(sample_task("bbbbbbb")),
+        ])
+        .await?;
+    let second = repository
+        .append(&[TaskAction::Delete("aaaaaaa".to_string())])
+        .await?;
+
+    let seqs: Vec<u64> = first.iter().chain(second.iter()).map(|op| op.seq).collect();
+    assert_eq!(seqs, vec![0, 1, 2]);
+    assert!(second.iter().all(|op| op.computer_name == "laptop"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_since() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+
+    for id in ["aaaaaaa", "bbbbbbb", "ccccccc"] {
+        repository
+            .append(&[TaskAction::Upsert(sample_task(id))])
+            .await?;
+    }
+
+    let operations = repository.list_since(1, 10).await?;
+    assert_eq!(operations.len(), 2);
+    assert!(matches!(&operations[0].action, TaskAction::Upsert(t) if t.id == "bbbbbbb"));
+
+    assert_eq!(repository.list_since(0, 1).await?.len(), 1);
+    assert!(repository.list_since(3, 10).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_task_mutations_are_logged() -> eyre::Result<()> {
+    let (_dir, repository) = setup_repository();
+    let task_repository = TaskRepository::builder()
+        .storage(repository.storage.clone())
+        .computer_name("laptop".to_string())
+        .build();
+
+    let (task, _) = task_repository
+        .start_new_task(StartTaskInput {
+            task_name: "write report".to_string(),
+            project: None,
+            tags: vec![],
+            at: None,
+        })
+        .await?;
+    task_repository.delete_task(task.id.clone()).await?;
+
+    let operations = repository.list_since(0, 10).await?;
+    assert_eq!(operations.len(), 2);
+    assert!(matches!(&operations[0].action, TaskAction::Upsert(t) if t.id == task.id));
+    assert!(matches!(&operations[1].action, TaskAction::Delete(id) if *id == task.id));
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use crate::core::cache_strategy::InMemoryCache;
-use crate::core::repository::{Cacheable, Repository};
+use crate::core::repository::{Cacheable, CachedTransaction, Repository};
 use crate::core::storage::Storage;
 use crate::entities::prefix_trie_node::PrefixTrieNode;
+use crate::entities::task::Task;
+use crate::repositories::task::defs::TaskAction;
 
 #[cfg(test)]
 mod tests;
@@ -12,6 +14,9 @@ impl Cacheable for PrefixTrieNode {
     }
 }
 
+type PrefixTransaction<'a, 'b> =
+    CachedTransaction<'a, 'b, PrefixTrieNode, InMemoryCache<PrefixTrieNode>>;
+
 /// The main interface for the prefix index.
 #[derive(Clone)]
 pub struct TaskPrefixRepository {
@@ -31,51 +36,64 @@ impl TaskPrefixRepository {
     pub fn add_ids(&self, ids: &[String]) -> eyre::Result<()> {
         self.repo.write_cached(|cached_txn| {
             for id in ids {
-                if let Some(node) = cached_txn.get(id)? {
-                    if node.is_end_of_id {
-                        continue;
-                    }
-                }
+                add_id(cached_txn, id)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Removes ids from the trie. The prefixes they no longer share are marked
+    /// unique again and the prefixes left without any id are dropped.
+    pub fn remove_ids(&self, ids: &[String]) -> eyre::Result<()> {
+        self.repo.write_cached(|cached_txn| {
+            for id in ids {
+                remove_id(cached_txn, id)?;
+            }
+            Ok(())
+        })
+    }
 
-                for i in 1..=id.len() {
-                    let prefix = &id[..i];
-                    let is_final_node = i == id.len();
-
-                    if prefix.len() < 2 && !is_final_node {
-                        continue;
-                    }
-
-                    let prefix_str = prefix.to_string();
-                    let node_lookup = cached_txn.get(&prefix_str)?;
-
-                    if let Some(mut existing_node) = node_lookup {
-                        let mut changed = false;
-                        if existing_node.is_unique {
-                            existing_node.is_unique = false;
-                            changed = true;
-                        }
-                        if is_final_node && !existing_node.is_end_of_id {
-                            existing_node.is_end_of_id = true;
-                            changed = true;
-                        }
-
-                        if changed {
-                            cached_txn.upsert(existing_node)?;
-                        }
-                    } else {
-                        let new_node = PrefixTrieNode {
-                            prefix: prefix_str,
-                            is_unique: true,
-                            is_end_of_id: is_final_node,
-                        };
-                        cached_txn.insert(new_node)?;
-                    }
+    /// Follows the tasks upserted and deleted by `actions`, in order.
+    pub fn apply_actions(&self, actions: &[TaskAction]) -> eyre::Result<()> {
+        self.repo.write_cached(|cached_txn| {
+            for action in actions {
+                match action {
+                    TaskAction::Upsert(task) => add_id(cached_txn, &task.id)?,
+                    TaskAction::Delete(task_id) => remove_id(cached_txn, task_id)?,
                 }
             }
             Ok(())
         })
     }
 
+    /// Regenerates the trie from the ids of the `Task` table.
+    pub fn rebuild(&self) -> eyre::Result<()> {
+        self.repo.write_cached(|cached_txn| {
+            let nodes = cached_txn
+                .txn
+                .scan()
+                .primary::<PrefixTrieNode>()?
+                .all()?
+                .collect::<Result<Vec<_>, _>>()?;
+            let tasks = cached_txn
+                .txn
+                .scan()
+                .primary::<Task>()?
+                .all()?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for node in nodes {
+                cached_txn.remove(node)?;
+            }
+            cached_txn.clear_cache();
+
+            for task in &tasks {
+                add_id(cached_txn, &task.id)?;
+            }
+            Ok(())
+        })
+    }
+
     pub fn contains(&self, id: &str) -> eyre::Result<bool> {
         Ok(self.repo.get(id)?.is_some_and(|node| node.is_end_of_id))
     }
@@ -102,3 +120,95 @@ impl TaskPrefixRepository {
         Ok(id.into())
     }
 }
+
+fn add_id(cached_txn: &mut PrefixTransaction, id: &str) -> eyre::Result<()> {
+    if let Some(node) = cached_txn.get(id)? {
+        if node.is_end_of_id {
+            return Ok(());
+        }
+    }
+
+    for i in 1..=id.len() {
+        let prefix = &id[..i];
+        let is_final_node = i == id.len();
+
+        if prefix.len() < 2 && !is_final_node {
+            continue;
+        }
+
+        let prefix_str = prefix.to_string();
+        let node_lookup = cached_txn.get(&prefix_str)?;
+
+        if let Some(mut existing_node) = node_lookup {
+            let mut changed = false;
+            if existing_node.is_unique {
+                existing_node.is_unique = false;
+                changed = true;
+            }
+            if is_final_node && !existing_node.is_end_of_id {
+                existing_node.is_end_of_id = true;
+                changed = true;
+            }
+
+            if changed {
+                cached_txn.upsert(existing_node)?;
+            }
+        } else {
+            let new_node = PrefixTrieNode {
+                prefix: prefix_str,
+                is_unique: true,
+                is_end_of_id: is_final_node,
+            };
+            cached_txn.insert(new_node)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_id(cached_txn: &mut PrefixTransaction, id: &str) -> eyre::Result<()> {
+    match cached_txn.get(id)? {
+        Some(node) if node.is_end_of_id => (),
+        _ => return Ok(()),
+    }
+
+    // Ids sharing a prefix with the removed one, the scan must be dropped before writing
+    let remaining_ids: Vec<String> = {
+        let shortest_prefix = &id[..id.len().min(2)];
+        let nodes = cached_txn
+            .txn
+            .scan()
+            .primary::<PrefixTrieNode>()?
+            .start_with(shortest_prefix.to_string())?
+            .collect::<Result<Vec<_>, _>>()?;
+        nodes
+            .into_iter()
+            .filter(|node| node.is_end_of_id && node.prefix != id)
+            .map(|node| node.prefix)
+            .collect()
+    };
+
+    for i in 1..=id.len() {
+        let prefix = &id[..i];
+        let Some(mut node) = cached_txn.get(prefix)? else {
+            continue;
+        };
+
+        let sharing = remaining_ids
+            .iter()
+            .filter(|other| other.starts_with(prefix))
+            .count();
+        match sharing {
+            0 => cached_txn.remove(node)?,
+            _ => {
+                let is_unique = sharing == 1;
+                let is_end_of_id = node.is_end_of_id && prefix != id;
+                if node.is_unique != is_unique || node.is_end_of_id != is_end_of_id {
+                    node.is_unique = is_unique;
+                    node.is_end_of_id = is_end_of_id;
+                    cached_txn.upsert(node)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use super::*;
+use crate::entities::MODELS;
+use crate::repositories::task::TaskRepository;
+use tempfile::tempdir;
+
+fn setup_repository() -> (tempfile::TempDir, TaskPrefixRepository) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    (dir, TaskPrefixRepository::new(storage))
+}
+
+#[test]
+fn test_contains_added_ids() -> eyre::Result<()> {
+    let (_dir, repo) = setup_repository();
+    repo.add_ids(&["abcdefg".to_string()])?;
+
+    assert!(repo.contains("abcdefg")?);
+    assert!(!repo.contains("abcdef")?);
+    assert!(!repo.contains("zzzzzzz")?);
+    Ok(())
+}
+
+#[test]
+fn test_shortest_unique_prefix() -> eyre::Result<()> {
+    let (_dir, repo) = setup_repository();
+    repo.add_ids(&["abcdefg".to_string()])?;
+    assert_eq!(repo.find_shortest_unique_prefix("abcdefg")?, "ab");
+
+    repo.add_ids(&["abcxyzw".to_string()])?;
+    assert_eq!(repo.find_shortest_unique_prefix("abcdefg")?, "abcd");
+    assert_eq!(repo.find_shortest_unique_prefix("abcxyzw")?, "abcx");
+    Ok(())
+}
+
+#[test]
+fn test_adding_same_id_twice_is_noop() -> eyre::Result<()> {
+    let (_dir, repo) = setup_repository();
+    repo.add_ids(&["abcdefg".to_string()])?;
+    repo.add_ids(&["abcdefg".to_string()])?;
+
+    assert_eq!(repo.find_shortest_unique_prefix("abcdefg")?, "ab");
+    Ok(())
+}
+
+#[test]
+fn test_unknown_id_returns_full_id() -> eyre::Result<()> {
+    let (_dir, repo) = setup_repository();
+    assert_eq!(repo.find_shortest_unique_prefix("qwertyu")?, "qwertyu");
+    Ok(())
+}
+
+#[test]
+fn test_removed_id_frees_shared_prefixes() -> eyre::Result<()> {
+    let (_dir, repo) = setup_repository();
+    repo.add_ids(&["abcdefg".to_string(), "abcxyzw".to_string()])?;
+    repo.remove_ids(&["abcxyzw".to_string()])?;
+
+    assert!(!repo.contains("abcxyzw")?);
+    assert!(repo.search_by_prefix("abcx")?.is_empty());
+    assert_eq!(repo.find_shortest_unique_prefix("abcdefg")?, "ab");
+    Ok(())
+}
+
+#[test]
+fn test_removing_id_keeps_longer_ids() -> eyre::Result<()> {
+    let (_dir, repo) = setup_repository();
+    repo.add_ids(&["abc".to_string(), "abcd".to_string(), "abxy".to_string()])?;
+    repo.remove_ids(&["abc".to_string()])?;
+
+    assert!(!repo.contains("abc")?);
+    assert!(repo.contains("abcd")?);
+    assert_eq!(repo.find_shortest_unique_prefix("abcd")?, "abc");
+    assert_eq!(repo.find_shortest_unique_prefix("abxy")?, "abx");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rebuild_indexes_stored_tasks_only() -> eyre::Result<()> {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let repo = TaskPrefixRepository::new(storage.clone());
+    let task_repository = TaskRepository::builder()
+        .storage(storage)
+        .computer_name("laptop".to_string())
+        .build();
+
+    task_repository
+        .apply_actions(vec![TaskAction::Upsert(
+            Task::builder()
+                .id("abcdefg".to_string())
+                .task_name("review".to_string())
+                .computer_name("laptop".to_string())
+                .start(1_000)
+                .end(Some(2_000))
+                .build(),
+        )])
+        .await?;
+    repo.add_ids(&["abcxyzw".to_string()])?;
+
+    repo.rebuild()?;
+
+    assert!(repo.contains("abcdefg")?);
+    assert!(!repo.contains("abcxyzw")?);
+    assert_eq!(repo.find_shortest_unique_prefix("abcdefg")?, "ab");
+    Ok(())
+}
@@ -2,11 +2,18 @@ use crate::core::utils::unix_now;
 use crate::entities::activity::Activity;
 use crate::repositories::activity::defs::StartActivity;
 use crate::repositories::activity::ActivityRepository;
+use crate::repositories::idle_period::IdlePeriodRepository;
+use crate::services::afk::AfkService;
+use crate::services::events::{DaemonEvent, EventBus};
+use crate::services::nudge::NudgeService;
 use crate::services::task::TaskService;
 use std::sync::Arc;
+use std::time::Duration;
 
 use thiserror::Error;
 use tracing::{error, info};
+use window_tracker::IdleEvent;
+use window_tracker::IdleTracker;
 use window_tracker::WindowEvent;
 use window_tracker::WindowTracker;
 use window_tracker::WindowTrackerError;
@@ -19,6 +26,12 @@ pub enum Error {
 
     #[error("Failed to start monitoring window events")]
     MonitoringStart(WindowTrackerError),
+
+    #[error("Failed to initialize the idle tracker")]
+    IdleTrackerInitialization(WindowTrackerError),
+
+    #[error("Failed to start monitoring idle events")]
+    IdleMonitoringStart(WindowTrackerError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -28,6 +41,14 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct ActivityService {
     task_service: TaskService,
     activity_repository: ActivityRepository,
+    idle_period_repository: IdlePeriodRepository,
+    /// Inactivity after which the user is considered idle
+    idle_timeout: Duration,
+    afk_service: AfkService,
+    #[builder(default)]
+    event_bus: EventBus,
+    #[builder(default)]
+    nudge_service: Option<NudgeService>,
 }
 
 async fn build_window_tracker() -> Result<WindowTracker> {
@@ -49,11 +70,18 @@ impl ActivityService {
         info!("Window event: {:#?}", event);
         match event {
             WindowEvent::WindowFocused(info) | WindowEvent::WindowTitleChanged(info) => {
-                self.activity_repository.register(StartActivity {
+                let at = unix_now();
+                let activity = self.activity_repository.register(StartActivity {
                     app_title: Some(info.title.clone()),
                     app_name: info.app_name.clone(),
-                    at: unix_now(),
+                    at,
                 })?;
+                self.event_bus
+                    .publish(DaemonEvent::ActivityRecorded(activity));
+
+                if let Some(nudge_service) = &self.nudge_service {
+                    nudge_service.record_activity(at)?;
+                }
             }
             _ => (),
         }
@@ -85,14 +113,69 @@ impl ActivityService {
         Ok(())
     }
 
+    async fn handle_idle_event(&self, event: IdleEvent) -> eyre::Result<()> {
+        info!("Idle event: {:?}", event);
+        match event {
+            IdleEvent::Idle { idle_for } => {
+                // The notification only fires once the timeout elapsed
+                let idle_start = unix_now().saturating_sub(idle_for.as_millis() as u64);
+                self.idle_period_repository.start(idle_start)?;
+
+                if let Some(nudge_service) = &self.nudge_service {
+                    nudge_service.record_idle()?;
+                }
+            }
+            IdleEvent::Resumed => {
+                let at = unix_now();
+                if let Some(period) = self.idle_period_repository.end(at)? {
+                    self.afk_service
+                        .handle_idle_period(period.start, at)
+                        .await?;
+                }
+
+                if let Some(nudge_service) = &self.nudge_service {
+                    nudge_service.record_activity(at)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn start_idle_monitoring(&self) -> Result<()> {
+        let idle_tracker = IdleTracker::try_new().map_err(|e| {
+            error!("Failed to initialize IdleTracker: {}", e);
+            Error::IdleTrackerInitialization(e)
+        })?;
+
+        info!(
+            "Starting idle monitoring (timeout: {}s).",
+            self.idle_timeout.as_secs()
+        );
+        let mut events = idle_tracker
+            .start_monitoring(self.idle_timeout)
+            .await
+            .map_err(|e| {
+                error!("Could not start idle monitoring: {}", e);
+                Error::IdleMonitoringStart(e)
+            })?;
+
+        while let Some(event) = events.recv().await {
+            if let Err(err) = self.handle_idle_event(event).await {
+                tracing::warn!("An error occured while handling an idle event: {err}");
+            }
+        }
+
+        info!("Idle event monitoring stream has ended.");
+        Ok(())
+    }
+
     pub async fn list_activity_range(
         &self,
         start_timestamp: u64,
         end_timestamp: u64,
     ) -> eyre::Result<Vec<Activity>> {
-        Ok(self
-            .activity_repository
+        self.activity_repository
             .list_activity_range(start_timestamp, end_timestamp)
-            .await?)
+            .await
     }
 }
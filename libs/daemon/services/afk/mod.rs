@@ -0,0 +1,146 @@
+use crate::{
+    config::defs::AfkPolicy,
+    entities::task::Task,
+    services::{
+        events::{DaemonEvent, EventBus},
+        task::TaskService,
+    },
+};
+use std::{
+    sync::{Arc, MutexGuard},
+    time::Duration,
+};
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+/// An idle span that happened while a task was running.
+#[derive(Debug, Clone)]
+pub struct AfkPeriod {
+    pub task: Task,
+    pub idle_start: u64,
+    pub idle_end: u64,
+}
+
+/// What to do with the idle time of an AFK period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AfkResolution {
+    /// Count the idle time in the task
+    Keep,
+    /// Stop the task when the user went idle
+    Discard,
+    /// Stop the task when the user went idle and resume it on return
+    Split,
+}
+
+/// Detects idle spans during a running task and removes them from it
+/// according to the configured policy.
+#[wrap_builder(Arc)]
+pub struct AfkService {
+    task_service: TaskService,
+    event_bus: EventBus,
+    /// Idle duration after which the running task is considered AFK
+    threshold: Duration,
+    policy: AfkPolicy,
+    /// Period waiting for the user's decision when the policy is `Ask`
+    #[builder(default)]
+    pending: std::sync::Mutex<Option<AfkPeriod>>,
+}
+
+impl AfkServiceInner {
+    fn pending(&self) -> eyre::Result<MutexGuard<'_, Option<AfkPeriod>>> {
+        self.pending
+            .lock()
+            .map_err(|_| eyre::eyre!("The pending AFK period lock is poisoned"))
+    }
+
+    /// Handles the end of an idle period.
+    pub async fn handle_idle_period(&self, idle_start: u64, idle_end: u64) -> eyre::Result<()> {
+        let idle_for = Duration::from_millis(idle_end.saturating_sub(idle_start));
+        if idle_for < self.threshold {
+            return Ok(());
+        }
+
+        let Some(current) = self.task_service.get_current_task().await? else {
+            return Ok(());
+        };
+
+        // The task was started after the user went idle, nothing to remove
+        if current.task.start > idle_start {
+            return Ok(());
+        }
+
+        let period = AfkPeriod {
+            task: current.task,
+            idle_start,
+            idle_end,
+        };
+
+        tracing::info!(
+            "Task '{}' was running during {}s of inactivity.",
+            period.task.task_name,
+            idle_for.as_secs()
+        );
+
+        match self.policy {
+            AfkPolicy::Split => self.apply(&period, AfkResolution::Split).await,
+            AfkPolicy::Stop => self.apply(&period, AfkResolution::Discard).await,
+            AfkPolicy::Ask => {
+                *self.pending()? = Some(period.clone());
+                self.event_bus.publish(DaemonEvent::AfkDetected(period));
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the period waiting for a decision, if any.
+    pub fn get_pending(&self) -> eyre::Result<Option<AfkPeriod>> {
+        Ok(self.pending()?.clone())
+    }
+
+    /// Resolves the pending period, returning it or None if nothing was pending.
+    /// The period stays pending when the resolution can't be applied.
+    pub async fn resolve(&self, resolution: AfkResolution) -> eyre::Result<Option<AfkPeriod>> {
+        let Some(period) = self.pending()?.take() else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self.apply(&period, resolution).await {
+            // Unless a newer period was detected meanwhile
+            self.pending()?.get_or_insert(period);
+            return Err(e);
+        }
+        Ok(Some(period))
+    }
+
+    async fn apply(&self, period: &AfkPeriod, resolution: AfkResolution) -> eyre::Result<()> {
+        if resolution == AfkResolution::Keep {
+            return Ok(());
+        }
+
+        let current = self.task_service.get_current_task().await?;
+        if current.is_none_or(|current| current.task.id != period.task.id) {
+            eyre::bail!(
+                "Task '{}' isn't running anymore, edit it instead",
+                period.task.task_name
+            );
+        }
+
+        match resolution {
+            AfkResolution::Discard => {
+                self.task_service
+                    .stop_current_task_at(period.idle_start)
+                    .await?;
+            }
+            AfkResolution::Split => {
+                self.task_service
+                    .pause_current_task(period.idle_start, period.idle_end)
+                    .await?;
+            }
+            AfkResolution::Keep => {}
+        }
+
+        Ok(())
+    }
+}
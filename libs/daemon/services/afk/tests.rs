@@ -0,0 +1,134 @@
+use super::*;
+use crate::{
+    core::{storage::Storage, testing::build_task_service},
+    entities::MODELS,
+    repositories::task::defs::StartTaskInput,
+};
+use tempfile::{tempdir, TempDir};
+
+const THRESHOLD_MS: u64 = 60_000;
+
+fn setup(policy: AfkPolicy) -> (TempDir, TaskService, AfkService, EventBus) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let task_service = build_task_service(storage, "laptop");
+    let event_bus = EventBus::default();
+
+    let afk_service = AfkService::builder()
+        .task_service(task_service.clone())
+        .event_bus(event_bus.clone())
+        .threshold(Duration::from_millis(THRESHOLD_MS))
+        .policy(policy)
+        .build();
+
+    (dir, task_service, afk_service, event_bus)
+}
+
+async fn start_task(task_service: &TaskService) -> eyre::Result<Task> {
+    Ok(task_service
+        .start_new_task(StartTaskInput {
+            task_name: "report".to_string(),
+            project: Some("work".to_string()),
+            tags: vec!["writing".to_string()],
+            at: None,
+        })
+        .await?
+        .task)
+}
+
+#[tokio::test]
+async fn test_split_policy_removes_idle_time() -> eyre::Result<()> {
+    let (_dir, task_service, afk_service, _) = setup(AfkPolicy::Split);
+    let task = start_task(&task_service).await?;
+
+    let idle_start = task.start + 1_000;
+    let idle_end = idle_start + THRESHOLD_MS;
+    afk_service.handle_idle_period(idle_start, idle_end).await?;
+
+    let stopped = task_service.get_task(task.id.clone()).await?.unwrap().task;
+    assert_eq!(stopped.end, Some(idle_start));
+
+    let resumed = task_service.get_current_task().await?.unwrap().task;
+    assert_ne!(resumed.id, task.id);
+    assert_eq!(resumed.start, idle_end);
+    assert_eq!(resumed.task_name, "report");
+    assert_eq!(resumed.tags, vec!["writing".to_string()]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stop_policy_stops_at_idle_start() -> eyre::Result<()> {
+    let (_dir, task_service, afk_service, _) = setup(AfkPolicy::Stop);
+    let task = start_task(&task_service).await?;
+
+    let idle_start = task.start + 1_000;
+    afk_service
+        .handle_idle_period(idle_start, idle_start + THRESHOLD_MS)
+        .await?;
+
+    assert!(task_service.get_current_task().await?.is_none());
+    let stopped = task_service.get_task(task.id).await?.unwrap().task;
+    assert_eq!(stopped.end, Some(idle_start));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ask_policy_waits_for_resolution() -> eyre::Result<()> {
+    let (_dir, task_service, afk_service, event_bus) = setup(AfkPolicy::Ask);
+    let mut events = event_bus.subscribe();
+    let task = start_task(&task_service).await?;
+
+    let idle_start = task.start + 1_000;
+    afk_service
+        .handle_idle_period(idle_start, idle_start + THRESHOLD_MS)
+        .await?;
+
+    let Ok(DaemonEvent::AfkDetected(period)) = events.try_recv() else {
+        panic!("an AFK period should have been published");
+    };
+    assert_eq!(period.task.id, task.id);
+    assert!(afk_service.get_pending()?.is_some());
+
+    // Nothing happens until the user answers
+    assert!(task_service.get_current_task().await?.is_some());
+
+    let resolved = afk_service.resolve(AfkResolution::Discard).await?;
+    assert_eq!(resolved.unwrap().idle_start, idle_start);
+    assert!(task_service.get_current_task().await?.is_none());
+    assert!(afk_service.resolve(AfkResolution::Keep).await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_short_idle_is_ignored() -> eyre::Result<()> {
+    let (_dir, task_service, afk_service, _) = setup(AfkPolicy::Stop);
+    let task = start_task(&task_service).await?;
+
+    let idle_start = task.start + 1_000;
+    afk_service
+        .handle_idle_period(idle_start, idle_start + THRESHOLD_MS - 1)
+        .await?;
+
+    assert!(task_service.get_current_task().await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_resolution_stays_pending() -> eyre::Result<()> {
+    let (_dir, task_service, afk_service, _) = setup(AfkPolicy::Ask);
+    let task = start_task(&task_service).await?;
+
+    let idle_start = task.start + 1_000;
+    afk_service
+        .handle_idle_period(idle_start, idle_start + THRESHOLD_MS)
+        .await?;
+
+    // The task was stopped before the user answered
+    task_service.stop_current_task().await?;
+    assert!(afk_service.resolve(AfkResolution::Split).await.is_err());
+    assert!(afk_service.get_pending()?.is_some());
+
+    assert!(afk_service.resolve(AfkResolution::Keep).await?.is_some());
+    assert!(afk_service.get_pending()?.is_none());
+    Ok(())
+}
@@ -1,23 +1,44 @@
 use o324_dbus::{dto, O324ServiceInterface};
+use std::time::Duration;
 use typed_builder::TypedBuilder;
-use zbus::{fdo, interface};
+use zbus::{fdo, interface, object_server::SignalEmitter};
 
 use crate::services::{
     activity::ActivityService,
+    afk::AfkService,
+    doctor::DoctorService,
     storage_bridge::{DbOperation, StorageBridgeService},
     task::TaskService,
+    vault::VaultService,
 };
 
 #[derive(TypedBuilder)]
 pub struct O324Service {
     task_service: TaskService,
     activity_service: ActivityService,
+    afk_service: AfkService,
+    doctor_service: DoctorService,
     storage_bridge_service: StorageBridgeService,
+    vault_service: VaultService,
+}
+
+impl O324Service {
+    /// Refuses the requests reading or writing records while the storage is
+    /// locked.
+    fn check_unlocked(&self) -> fdo::Result<()> {
+        match self.vault_service.is_locked() {
+            true => Err(fdo::Error::AccessDenied(
+                "The storage is locked, run `o324 unlock` first".to_string(),
+            )),
+            false => Ok(()),
+        }
+    }
 }
 
 #[interface(name = "org.o324.Service1")]
 impl O324ServiceInterface for O324Service {
     async fn start_new_task(&self, input: dto::StartTaskInputDto) -> fdo::Result<dto::TaskDto> {
+        self.check_unlocked()?;
         self.task_service
             .start_new_task(input.into())
             .await
@@ -25,15 +46,54 @@ impl O324ServiceInterface for O324Service {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
-    async fn stop_current_task(&self) -> fdo::Result<Option<dto::TaskDto>> {
+    async fn add_task(
+        &self,
+        input: dto::AddTaskInputDto,
+        overlap: dto::OverlapStrategyDto,
+    ) -> fdo::Result<dto::TaskDto> {
+        self.check_unlocked()?;
         self.task_service
-            .stop_current_task()
+            .add_task(input.into(), overlap.into())
             .await
+            .map(|task| task.into())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn split_task(
+        &self,
+        task_id: String,
+        input: dto::SplitTaskInputDto,
+    ) -> fdo::Result<dto::TaskDto> {
+        self.check_unlocked()?;
+        self.task_service
+            .split_task(task_id, input.into())
+            .await
+            .map(|task| task.into())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn merge_tasks(&self, task_ids: Vec<String>) -> fdo::Result<dto::TaskDto> {
+        self.check_unlocked()?;
+        self.task_service
+            .merge_tasks(task_ids)
+            .await
+            .map(|task| task.into())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn stop_current_task(&self, at: Option<u64>) -> fdo::Result<Option<dto::TaskDto>> {
+        self.check_unlocked()?;
+        let stopped_task = match at {
+            Some(at) => self.task_service.stop_current_task_backdated(at).await,
+            None => self.task_service.stop_current_task().await,
+        };
+        stopped_task
             .map(|x| x.map(|t| t.into()))
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
     async fn cancel_current_task(&self) -> fdo::Result<Option<dto::TaskDto>> {
+        self.check_unlocked()?;
         self.task_service
             .cancel_current_task()
             .await
@@ -42,6 +102,7 @@ impl O324ServiceInterface for O324Service {
     }
 
     async fn delete_task(&self, task_id: String) -> fdo::Result<Option<dto::TaskDto>> {
+        self.check_unlocked()?;
         self.task_service
             .delete_task(task_id)
             .await
@@ -53,6 +114,7 @@ impl O324ServiceInterface for O324Service {
         &self,
         task_ref: String,
     ) -> fdo::Result<dto::TaskByPrefixDtoPacked> {
+        self.check_unlocked()?;
         let tasks = self
             .task_service
             .match_prefix(task_ref)
@@ -76,6 +138,7 @@ impl O324ServiceInterface for O324Service {
         task_ref_str: String,
         update: dto::TaskUpdateDto,
     ) -> fdo::Result<dto::TaskDto> {
+        self.check_unlocked()?;
         self.task_service
             .edit_task(task_ref_str.as_str().into(), update.into())
             .await
@@ -84,6 +147,7 @@ impl O324ServiceInterface for O324Service {
     }
 
     async fn list_last_tasks(&self, offset: u64, count: u64) -> fdo::Result<Vec<dto::TaskDto>> {
+        self.check_unlocked()?;
         self.task_service
             .list_last_tasks(offset, count)
             .await
@@ -96,6 +160,7 @@ impl O324ServiceInterface for O324Service {
         start_timestamp: u64,
         end_timestamp: u64,
     ) -> fdo::Result<Vec<dto::TaskDto>> {
+        self.check_unlocked()?;
         self.task_service
             .list_task_range(start_timestamp, end_timestamp)
             .await
@@ -107,6 +172,7 @@ impl O324ServiceInterface for O324Service {
         &self,
         operation: dto::DbOperationDto,
     ) -> fdo::Result<dto::DbResultDtoPacked> {
+        self.check_unlocked()?;
         let internal_operation =
             DbOperation::try_from(operation).map_err(fdo::Error::InvalidArgs)?;
 
@@ -124,6 +190,7 @@ impl O324ServiceInterface for O324Service {
         start_timestamp: u64,
         end_timestamp: u64,
     ) -> fdo::Result<Vec<dto::ActivityDto>> {
+        self.check_unlocked()?;
         self.activity_service
             .list_activity_range(start_timestamp, end_timestamp)
             .await
@@ -131,7 +198,143 @@ impl O324ServiceInterface for O324Service {
             .map_err(|e| fdo::Error::Failed(e.to_string()))
     }
 
+    async fn get_pending_afk(&self) -> fdo::Result<Option<dto::AfkPeriodDto>> {
+        self.check_unlocked()?;
+        self.afk_service
+            .get_pending()
+            .map(|x| x.map(|period| period.into()))
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn resolve_afk(
+        &self,
+        resolution: dto::AfkResolutionDto,
+    ) -> fdo::Result<Option<dto::AfkPeriodDto>> {
+        self.check_unlocked()?;
+        self.afk_service
+            .resolve(resolution.into())
+            .await
+            .map(|x| x.map(|period| period.into()))
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn check_integrity(
+        &self,
+        max_task_duration_secs: u64,
+    ) -> fdo::Result<Vec<dto::IntegrityIssueDto>> {
+        self.check_unlocked()?;
+        self.doctor_service
+            .scan(Duration::from_secs(max_task_duration_secs))
+            .await
+            .map(|issues| issues.into_iter().map(|issue| issue.into()).collect())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn fix_integrity_issues(
+        &self,
+        issue_ids: Vec<String>,
+    ) -> fdo::Result<Vec<dto::IntegrityIssueDto>> {
+        self.check_unlocked()?;
+        self.doctor_service
+            .fix(&issue_ids)
+            .await
+            .map(|issues| issues.into_iter().map(|issue| issue.into()).collect())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn list_conflicts(&self) -> fdo::Result<Vec<dto::TaskConflictDto>> {
+        self.check_unlocked()?;
+        self.task_service
+            .list_conflicts()
+            .await
+            .map(|conflicts| {
+                conflicts
+                    .into_iter()
+                    .map(|conflict| conflict.into())
+                    .collect()
+            })
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn resolve_conflict(
+        &self,
+        conflict_id: String,
+        resolution: dto::ConflictResolutionDto,
+    ) -> fdo::Result<dto::TaskDto> {
+        self.check_unlocked()?;
+        self.task_service
+            .resolve_conflict(conflict_id, resolution.into())
+            .await
+            .map(|task| task.into())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn undo(&self) -> fdo::Result<Option<dto::HistoryEntryDto>> {
+        self.check_unlocked()?;
+        self.task_service
+            .undo()
+            .await
+            .map(|x| x.map(|entry| entry.into()))
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn redo(&self) -> fdo::Result<Option<dto::HistoryEntryDto>> {
+        self.check_unlocked()?;
+        self.task_service
+            .redo()
+            .await
+            .map(|x| x.map(|entry| entry.into()))
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn sync(&self) -> fdo::Result<u64> {
+        self.check_unlocked()?;
+        self.task_service
+            .sync()
+            .await
+            .map(|actions| actions.len() as u64)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn unlock(&self, passphrase: String) -> fdo::Result<()> {
+        self.vault_service
+            .unlock(passphrase)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    async fn get_lock_state(&self) -> fdo::Result<dto::LockStateDto> {
+        self.vault_service
+            .state()
+            .map(|state| state.into())
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
     async fn ping(&self) -> fdo::Result<String> {
         Ok("pong".into())
     }
+
+    #[zbus(signal)]
+    async fn task_changed(emitter: &SignalEmitter<'_>, task: dto::TaskDto) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn task_deleted(emitter: &SignalEmitter<'_>, task_id: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn current_task_changed(
+        emitter: &SignalEmitter<'_>,
+        task: Option<dto::TaskDto>,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn activity_recorded(
+        emitter: &SignalEmitter<'_>,
+        activity: dto::ActivityDto,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn afk_detected(
+        emitter: &SignalEmitter<'_>,
+        period: dto::AfkPeriodDto,
+    ) -> zbus::Result<()>;
 }
@@ -1,38 +1,99 @@
+use o324_dbus::{dto, O324ServiceInterface};
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
 use wrap_builder::wrap_builder;
-use zbus::connection;
+use zbus::{connection, object_server::SignalEmitter, Connection};
 
-use crate::services::{activity::ActivityService, storage_bridge::StorageBridgeService};
+use crate::services::{
+    activity::ActivityService,
+    afk::AfkService,
+    doctor::DoctorService,
+    events::{DaemonEvent, EventBus},
+    storage_bridge::StorageBridgeService,
+    vault::VaultService,
+};
 
 use super::task::TaskService;
 
 pub mod interface;
 pub mod transforms;
 
+use interface::O324Service;
+
+const SERVICE_PATH: &str = "/org/o324/Service";
+
 #[wrap_builder(Arc)]
 pub struct DbusService {
     task_service: TaskService,
     activity_service: ActivityService,
+    afk_service: AfkService,
+    doctor_service: DoctorService,
     storage_bridge_service: StorageBridgeService,
+    vault_service: VaultService,
+    event_bus: EventBus,
 }
 
 impl DbusServiceInner {
     pub async fn serve(&self) -> eyre::Result<()> {
-        let _conn = connection::Builder::session()?
+        let conn = connection::Builder::session()?
             .name("org.o324.Service")?
             .serve_at(
-                "/org/o324/Service",
-                interface::O324Service::builder()
+                SERVICE_PATH,
+                O324Service::builder()
                     .task_service(self.task_service.clone())
                     .activity_service(self.activity_service.clone())
+                    .afk_service(self.afk_service.clone())
+                    .doctor_service(self.doctor_service.clone())
                     .storage_bridge_service(self.storage_bridge_service.clone())
+                    .vault_service(self.vault_service.clone())
                     .build(),
             )?
             .build()
             .await?;
 
         tracing::info!("D-Bus service running. Waiting for calls.");
-        std::future::pending::<()>().await;
+
+        // Forward the daemon events as signals
+        let mut events = self.event_bus.subscribe();
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = self.emit_event(&conn, event).await {
+                        tracing::warn!("Failed to emit D-Bus signal: {e}");
+                    }
+                }
+                Err(RecvError::Lagged(count)) => {
+                    tracing::warn!("{count} daemon event(s) dropped before being signaled");
+                }
+                Err(RecvError::Closed) => std::future::pending::<()>().await,
+            }
+        }
+    }
+
+    async fn emit_event(&self, conn: &Connection, event: DaemonEvent) -> eyre::Result<()> {
+        let emitter = SignalEmitter::new(conn, SERVICE_PATH)?;
+        match event {
+            DaemonEvent::TaskChanged(task) => {
+                let task: dto::TaskDto = self.task_service.task_with_meta(task).await?.into();
+                O324Service::task_changed(&emitter, task).await?;
+            }
+            DaemonEvent::TaskDeleted(task_id) => {
+                O324Service::task_deleted(&emitter, task_id).await?;
+            }
+            DaemonEvent::CurrentTaskChanged(task) => {
+                let task: Option<dto::TaskDto> = match task {
+                    Some(task) => Some(self.task_service.task_with_meta(task).await?.into()),
+                    None => None,
+                };
+                O324Service::current_task_changed(&emitter, task).await?;
+            }
+            DaemonEvent::ActivityRecorded(activity) => {
+                O324Service::activity_recorded(&emitter, activity.into()).await?;
+            }
+            DaemonEvent::AfkDetected(period) => {
+                O324Service::afk_detected(&emitter, period.into()).await?;
+            }
+        }
         Ok(())
     }
 }
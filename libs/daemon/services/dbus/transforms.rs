@@ -2,11 +2,18 @@ use crate::{
     entities::{
         activity::Activity,
         task::{Task, TaskUpdate},
+        task_conflict::TaskConflict,
+    },
+    repositories::task::defs::{
+        AddTaskInput, ConflictResolution, OverlapStrategy, SplitTaskInput, StartTaskInput,
+        TaskAction,
     },
-    repositories::task::defs::{StartTaskInput, TaskAction},
     services::{
+        afk::{AfkPeriod, AfkResolution},
+        doctor::{IntegrityIssue, IssueKind},
         storage_bridge::{DbOperation, DbResult},
-        task::TaskWithMeta,
+        task::{history::HistoryEntry, TaskWithMeta},
+        vault::LockState,
     },
 };
 use o324_dbus::dto::{self};
@@ -24,6 +31,8 @@ impl From<TaskWithMeta> for dto::TaskDto {
             computer_name: v.task.computer_name,
             start: v.task.start,
             end: v.task.end,
+            notes: v.task.notes,
+            project_color_hue: v.project_color_hue,
         }
     }
 }
@@ -35,6 +44,39 @@ impl From<dto::StartTaskInputDto> for StartTaskInput {
             task_name: dto.task_name,
             project: dto.project,
             tags: dto.tags,
+            at: dto.at,
+        }
+    }
+}
+
+impl From<dto::AddTaskInputDto> for AddTaskInput {
+    fn from(dto: dto::AddTaskInputDto) -> Self {
+        Self {
+            task_name: dto.task_name,
+            project: dto.project,
+            tags: dto.tags,
+            start: dto.start,
+            end: dto.end,
+        }
+    }
+}
+
+impl From<dto::SplitTaskInputDto> for SplitTaskInput {
+    fn from(dto: dto::SplitTaskInputDto) -> Self {
+        Self {
+            at: dto.at,
+            task_name: dto.task_name,
+            project: dto.project,
+            tags: dto.tags,
+        }
+    }
+}
+
+impl From<dto::OverlapStrategyDto> for OverlapStrategy {
+    fn from(value: dto::OverlapStrategyDto) -> Self {
+        match value {
+            dto::OverlapStrategyDto::Reject => Self::Reject,
+            dto::OverlapStrategyDto::Trim => Self::Trim,
         }
     }
 }
@@ -48,6 +90,7 @@ impl From<dto::TaskUpdateDto> for TaskUpdate {
             .set_opt_tags(dto.tags)
             .set_opt_start(dto.start)
             .set_opt_end(dto.end)
+            .set_opt_notes(dto.notes)
     }
 }
 
@@ -60,6 +103,20 @@ impl From<TaskAction> for dto::TaskActionDto {
     }
 }
 
+impl From<HistoryEntry> for dto::HistoryEntryDto {
+    fn from(entry: HistoryEntry) -> Self {
+        Self {
+            description: entry.description,
+            actions: entry
+                .changes
+                .actions
+                .into_iter()
+                .map(|action| dto::TaskActionDto::from(action).pack())
+                .collect(),
+        }
+    }
+}
+
 impl From<Task> for dto::TaskActionUpsertDto {
     fn from(value: Task) -> Self {
         Self {
@@ -71,6 +128,42 @@ impl From<Task> for dto::TaskActionUpsertDto {
             computer_name: value.computer_name,
             start: value.start,
             end: value.end,
+            notes: value.notes,
+        }
+    }
+}
+
+impl From<TaskConflict> for dto::TaskConflictDto {
+    fn from(value: TaskConflict) -> Self {
+        Self {
+            id: value.id,
+            fields: value
+                .fields
+                .iter()
+                .map(|field| field.name().to_string())
+                .collect(),
+            local: value.local.into(),
+            remote: value.remote.into(),
+            detected_at: value.detected_at,
+        }
+    }
+}
+
+impl From<dto::ConflictResolutionDto> for ConflictResolution {
+    fn from(value: dto::ConflictResolutionDto) -> Self {
+        match value {
+            dto::ConflictResolutionDto::Local => ConflictResolution::Local,
+            dto::ConflictResolutionDto::Remote => ConflictResolution::Remote,
+        }
+    }
+}
+
+impl From<LockState> for dto::LockStateDto {
+    fn from(state: LockState) -> Self {
+        match state {
+            LockState::Unlocked => dto::LockStateDto::Unlocked,
+            LockState::Locked => dto::LockStateDto::Locked,
+            LockState::Uninitialized => dto::LockStateDto::Uninitialized,
         }
     }
 }
@@ -114,3 +207,44 @@ impl From<Activity> for dto::ActivityDto {
         }
     }
 }
+
+impl From<AfkPeriod> for dto::AfkPeriodDto {
+    fn from(value: AfkPeriod) -> Self {
+        Self {
+            task_id: value.task.id,
+            task_name: value.task.task_name,
+            idle_start: value.idle_start,
+            idle_end: value.idle_end,
+        }
+    }
+}
+
+impl From<dto::AfkResolutionDto> for AfkResolution {
+    fn from(value: dto::AfkResolutionDto) -> Self {
+        match value {
+            dto::AfkResolutionDto::Keep => AfkResolution::Keep,
+            dto::AfkResolutionDto::Discard => AfkResolution::Discard,
+            dto::AfkResolutionDto::Split => AfkResolution::Split,
+        }
+    }
+}
+
+impl From<IntegrityIssue> for dto::IntegrityIssueDto {
+    fn from(value: IntegrityIssue) -> Self {
+        let kind = match value.kind {
+            IssueKind::Overlap => dto::IntegrityIssueKindDto::Overlap,
+            IssueKind::Gap => dto::IntegrityIssueKindDto::Gap,
+            IssueKind::InvertedRange => dto::IntegrityIssueKindDto::InvertedRange,
+            IssueKind::LongTask => dto::IntegrityIssueKindDto::LongTask,
+            IssueKind::FutureActivity => dto::IntegrityIssueKindDto::FutureActivity,
+            IssueKind::OrphanPrefix => dto::IntegrityIssueKindDto::OrphanPrefix,
+        };
+
+        Self {
+            fix: value.fix.map(|fix| fix.description().to_string()),
+            id: value.id,
+            kind,
+            description: value.description,
+        }
+    }
+}
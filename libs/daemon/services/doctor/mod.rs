@@ -0,0 +1,351 @@
+use crate::{
+    core::utils::{generate_random_id, unix_now},
+    entities::{activity::Activity, task::Task},
+    repositories::{
+        activity::ActivityRepository, task::defs::TaskAction, task::TaskRepository,
+        task_prefix::TaskPrefixRepository,
+    },
+    services::task::TaskService,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+/// Untracked time between two tasks short enough to be a leftover of an edit.
+const MAX_GAP_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Two tasks of the same computer share some time
+    Overlap,
+    /// A few seconds are left untracked between two tasks of the same computer
+    Gap,
+    /// A task ends before it starts
+    InvertedRange,
+    /// A task lasts longer than the configured threshold
+    LongTask,
+    /// An activity was recorded after the current time
+    FutureActivity,
+    /// The prefix index references a task that doesn't exist
+    OrphanPrefix,
+}
+
+/// How an issue can be repaired.
+#[derive(Debug, Clone)]
+pub enum IssueFix {
+    Tasks {
+        description: String,
+        actions: Vec<TaskAction>,
+    },
+    DeleteActivity {
+        description: String,
+        activity_id: String,
+    },
+    /// Regenerates the prefix index from the stored tasks
+    RebuildPrefixes { description: String },
+}
+
+impl IssueFix {
+    pub fn description(&self) -> &str {
+        match self {
+            Self::Tasks { description, .. }
+            | Self::DeleteActivity { description, .. }
+            | Self::RebuildPrefixes { description } => description,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntegrityIssue {
+    /// Identifies the issue across scans, to select the issues to fix
+    pub id: String,
+    pub kind: IssueKind,
+    pub description: String,
+    pub fix: Option<IssueFix>,
+}
+
+/// Scans the store for inconsistent tasks and activities, and repairs them.
+#[wrap_builder(Arc)]
+pub struct DoctorService {
+    task_service: TaskService,
+    task_repository: TaskRepository,
+    activity_repository: ActivityRepository,
+    task_prefix_repository: TaskPrefixRepository,
+}
+
+impl DoctorServiceInner {
+    /// Lists the integrity issues, tasks longer than `long_task_threshold` being reported.
+    pub async fn scan(&self, long_task_threshold: Duration) -> eyre::Result<Vec<IntegrityIssue>> {
+        let now = unix_now();
+        let tasks = self.task_repository.list_task_range(0, u64::MAX).await?;
+        let activities = self
+            .activity_repository
+            .list_activity_range(0, u64::MAX)
+            .await?;
+
+        let mut issues = Vec::new();
+        issues.extend(tasks.iter().filter_map(inverted_range_issue));
+        issues.extend(overlap_issues(&tasks, now));
+        issues.extend(gap_issues(&tasks, now));
+        issues.extend(
+            tasks
+                .iter()
+                .filter_map(|task| long_task_issue(task, long_task_threshold, now)),
+        );
+        issues.extend(
+            activities
+                .iter()
+                .filter(|activity| activity.at > now)
+                .map(future_activity_issue),
+        );
+        issues.extend(self.orphan_prefix_issues(&tasks)?);
+
+        Ok(issues)
+    }
+
+    /// Repairs the issues with the given ids, returning the repaired ones. Issues
+    /// are fixed one at a time and rescanned in between since fixes may overlap.
+    pub async fn fix(&self, issue_ids: &[String]) -> eyre::Result<Vec<IntegrityIssue>> {
+        let mut fixed = Vec::new();
+
+        for issue_id in issue_ids {
+            let issues = self.scan(Duration::MAX).await?;
+            let Some(issue) = issues.into_iter().find(|issue| &issue.id == issue_id) else {
+                continue;
+            };
+
+            match &issue.fix {
+                Some(IssueFix::Tasks {
+                    description,
+                    actions,
+                }) => {
+                    self.task_service
+                        .repair(description.clone(), actions.clone())
+                        .await?;
+                }
+                Some(IssueFix::DeleteActivity { activity_id, .. }) => {
+                    self.activity_repository.delete(activity_id.clone())?;
+                }
+                Some(IssueFix::RebuildPrefixes { .. }) => {
+                    self.task_prefix_repository.rebuild()?;
+                }
+                None => continue,
+            }
+            fixed.push(issue);
+        }
+
+        Ok(fixed)
+    }
+
+    fn orphan_prefix_issues(&self, tasks: &[Task]) -> eyre::Result<Vec<IntegrityIssue>> {
+        let task_ids: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+
+        Ok(self
+            .task_prefix_repository
+            .search_by_prefix("")?
+            .into_iter()
+            .filter(|node| node.is_end_of_id && !task_ids.contains(node.prefix.as_str()))
+            .map(|node| IntegrityIssue {
+                id: format!("orphan-prefix:{}", node.prefix),
+                kind: IssueKind::OrphanPrefix,
+                description: format!("The prefix index references deleted task {}", node.prefix),
+                fix: Some(IssueFix::RebuildPrefixes {
+                    description: "Rebuild the prefix index".to_string(),
+                }),
+            })
+            .collect())
+    }
+}
+
+fn describe(task: &Task) -> String {
+    format!("'{}' ({})", task.task_name, task.id)
+}
+
+fn format_duration(ms: u64) -> String {
+    let minutes = ms / 60_000;
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h{:02}m", minutes % 60),
+    }
+}
+
+fn inverted_range_issue(task: &Task) -> Option<IntegrityIssue> {
+    let end = task.end.filter(|end| *end < task.start)?;
+
+    let mut swapped = task.clone();
+    swapped.start = end;
+    swapped.end = Some(task.start);
+
+    Some(IntegrityIssue {
+        id: format!("inverted:{}", task.id),
+        kind: IssueKind::InvertedRange,
+        description: format!("{} ends before it starts", describe(task)),
+        fix: Some(IssueFix::Tasks {
+            description: format!("Swap the start and end of {}", describe(task)),
+            actions: vec![TaskAction::Upsert(swapped)],
+        }),
+    })
+}
+
+/// Groups the tasks by computer, sorted by start. Inverted tasks are left out.
+fn tasks_by_computer(tasks: &[Task]) -> Vec<Vec<&Task>> {
+    let mut by_computer: HashMap<&str, Vec<&Task>> = HashMap::new();
+    for task in tasks
+        .iter()
+        .filter(|task| task.end.is_none_or(|end| end >= task.start))
+    {
+        by_computer
+            .entry(task.computer_name.as_str())
+            .or_default()
+            .push(task);
+    }
+
+    let mut by_computer: Vec<Vec<&Task>> = by_computer.into_values().collect();
+    for computer_tasks in &mut by_computer {
+        computer_tasks.sort_by_key(|task| task.start);
+    }
+    by_computer
+}
+
+/// Finds the overlapping tasks of each computer, a running task lasting until `now`.
+fn overlap_issues(tasks: &[Task], now: u64) -> Vec<IntegrityIssue> {
+    let mut issues = Vec::new();
+    for computer_tasks in tasks_by_computer(tasks) {
+        // Task reaching the furthest so far, the one later tasks may overlap
+        let mut furthest: Option<&Task> = None;
+        for task in computer_tasks {
+            if let Some(previous) = furthest {
+                let previous_end = previous.end.unwrap_or(now);
+                if task.start < previous_end {
+                    issues.push(overlap_issue(previous, task, now));
+                }
+                if task.end.unwrap_or(now) > previous_end {
+                    furthest = Some(task);
+                }
+            } else {
+                furthest = Some(task);
+            }
+        }
+    }
+
+    issues
+}
+
+/// Finds the tasks of each computer starting shortly after the previous
+/// one ended, a running task lasting until `now`.
+fn gap_issues(tasks: &[Task], now: u64) -> Vec<IntegrityIssue> {
+    let mut issues = Vec::new();
+    for computer_tasks in tasks_by_computer(tasks) {
+        // Task reaching the furthest so far, the one the next task follows
+        let mut furthest: Option<&Task> = None;
+        for task in computer_tasks {
+            if let Some(previous) = furthest {
+                let previous_end = previous.end.unwrap_or(now);
+                let gap = task.start.saturating_sub(previous_end);
+                if previous.end.is_some() && gap > 0 && gap < MAX_GAP_MS {
+                    issues.push(gap_issue(previous, task, gap));
+                }
+                if task.end.unwrap_or(now) <= previous_end {
+                    continue;
+                }
+            }
+            furthest = Some(task);
+        }
+    }
+
+    issues
+}
+
+/// Ends `first` when `second` starts.
+fn gap_issue(first: &Task, second: &Task, gap: u64) -> IntegrityIssue {
+    let mut extended = first.clone();
+    extended.end = Some(second.start);
+
+    IntegrityIssue {
+        id: format!("gap:{}:{}", first.id, second.id),
+        kind: IssueKind::Gap,
+        description: format!(
+            "{}s are untracked between {} and {}",
+            gap.div_ceil(1000),
+            describe(first),
+            describe(second)
+        ),
+        fix: Some(IssueFix::Tasks {
+            description: format!("End {} when {} starts", describe(first), describe(second)),
+            actions: vec![TaskAction::Upsert(extended)],
+        }),
+    }
+}
+
+/// Ends `first` when `second` starts, keeping the part of `first` after
+/// `second` as a new task when `second` is contained in it.
+fn overlap_issue(first: &Task, second: &Task, now: u64) -> IntegrityIssue {
+    let first_end = first.end.unwrap_or(now);
+    let second_end = second.end.unwrap_or(now);
+    let overlap = first_end.min(second_end).saturating_sub(second.start);
+
+    let mut trimmed = first.clone();
+    trimmed.end = Some(second.start);
+    let mut actions = vec![TaskAction::Upsert(trimmed)];
+
+    let description = if second.end.is_some() && first_end > second_end {
+        actions.push(TaskAction::Upsert(Task {
+            id: generate_random_id(7),
+            start: second_end,
+            ..first.clone()
+        }));
+        format!("Split {} around {}", describe(first), describe(second))
+    } else {
+        format!("End {} when {} starts", describe(first), describe(second))
+    };
+
+    IntegrityIssue {
+        id: format!("overlap:{}:{}", first.id, second.id),
+        kind: IssueKind::Overlap,
+        description: format!(
+            "{} overlaps {} by {}",
+            describe(first),
+            describe(second),
+            format_duration(overlap)
+        ),
+        fix: Some(IssueFix::Tasks {
+            description,
+            actions,
+        }),
+    }
+}
+
+fn long_task_issue(task: &Task, threshold: Duration, now: u64) -> Option<IntegrityIssue> {
+    let duration = task.end.unwrap_or(now).saturating_sub(task.start);
+    if u128::from(duration) <= threshold.as_millis() {
+        return None;
+    }
+
+    Some(IntegrityIssue {
+        id: format!("long:{}", task.id),
+        kind: IssueKind::LongTask,
+        description: format!("{} lasts {}", describe(task), format_duration(duration)),
+        fix: None,
+    })
+}
+
+fn future_activity_issue(activity: &Activity) -> IntegrityIssue {
+    IntegrityIssue {
+        id: format!("future-activity:{}", activity.id),
+        kind: IssueKind::FutureActivity,
+        description: format!(
+            "Activity '{}' ({}) is recorded in the future",
+            activity.app_name, activity.id
+        ),
+        fix: Some(IssueFix::DeleteActivity {
+            description: "Delete the activity".to_string(),
+            activity_id: activity.id.clone(),
+        }),
+    }
+}
@@ -0,0 +1,214 @@
+use super::*;
+use crate::{
+    core::{storage::Storage, testing::build_task_service},
+    entities::MODELS,
+    repositories::activity::defs::StartActivity,
+};
+use tempfile::{tempdir, TempDir};
+
+const HOUR_MS: u64 = 3_600_000;
+
+struct Setup {
+    _dir: TempDir,
+    task_service: TaskService,
+    task_repository: TaskRepository,
+    activity_repository: ActivityRepository,
+    task_prefix_repository: TaskPrefixRepository,
+    doctor_service: DoctorService,
+}
+
+fn setup() -> Setup {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let task_service = build_task_service(storage.clone(), "laptop");
+    let task_repository = TaskRepository::builder()
+        .storage(storage.clone())
+        .computer_name("laptop".to_string())
+        .build();
+    let activity_repository = ActivityRepository::builder()
+        .storage(storage.clone())
+        .computer_name("laptop".to_string())
+        .build();
+    let task_prefix_repository = TaskPrefixRepository::new(storage);
+
+    let doctor_service = DoctorService::builder()
+        .task_service(task_service.clone())
+        .task_repository(task_repository.clone())
+        .activity_repository(activity_repository.clone())
+        .task_prefix_repository(task_prefix_repository.clone())
+        .build();
+
+    Setup {
+        _dir: dir,
+        task_service,
+        task_repository,
+        activity_repository,
+        task_prefix_repository,
+        doctor_service,
+    }
+}
+
+fn task(id: &str, computer_name: &str, start: u64, end: Option<u64>) -> Task {
+    Task::builder()
+        .id(id.to_string())
+        .task_name(format!("task {id}"))
+        .computer_name(computer_name.to_string())
+        .start(start)
+        .end(end)
+        .build()
+}
+
+fn kinds(issues: &[IntegrityIssue]) -> Vec<IssueKind> {
+    let mut kinds: Vec<IssueKind> = issues.iter().map(|issue| issue.kind).collect();
+    kinds.sort_by_key(|kind| format!("{kind:?}"));
+    kinds
+}
+
+#[tokio::test]
+async fn test_clean_store_has_no_issues() -> eyre::Result<()> {
+    let setup = setup();
+    setup
+        .task_repository
+        .apply_actions(vec![
+            TaskAction::Upsert(task("aaaaaaa", "laptop", 0, Some(HOUR_MS))),
+            TaskAction::Upsert(task("bbbbbbb", "laptop", HOUR_MS, Some(2 * HOUR_MS))),
+            // Other computers may track at the same time
+            TaskAction::Upsert(task("ccccccc", "desktop", 0, Some(2 * HOUR_MS - 1))),
+        ])
+        .await?;
+
+    let issues = setup
+        .doctor_service
+        .scan(Duration::from_secs(12 * 3600))
+        .await?;
+    assert!(issues.is_empty(), "{issues:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_and_fix_issues() -> eyre::Result<()> {
+    let setup = setup();
+    setup
+        .task_repository
+        .apply_actions(vec![
+            TaskAction::Upsert(task("aaaaaaa", "laptop", 0, Some(4 * HOUR_MS))),
+            TaskAction::Upsert(task("bbbbbbb", "laptop", HOUR_MS, Some(2 * HOUR_MS))),
+            TaskAction::Upsert(task("ccccccc", "laptop", 6 * HOUR_MS, Some(5 * HOUR_MS))),
+            TaskAction::Upsert(task("ddddddd", "laptop", 10 * HOUR_MS, Some(30 * HOUR_MS))),
+        ])
+        .await?;
+    setup.activity_repository.register(StartActivity {
+        app_title: None,
+        app_name: "firefox".to_string(),
+        at: unix_now() + HOUR_MS,
+    })?;
+    setup
+        .task_prefix_repository
+        .add_ids(&["eeeeeee".to_string()])?;
+
+    let issues = setup
+        .doctor_service
+        .scan(Duration::from_secs(12 * 3600))
+        .await?;
+    assert_eq!(
+        kinds(&issues),
+        vec![
+            IssueKind::FutureActivity,
+            IssueKind::InvertedRange,
+            IssueKind::LongTask,
+            IssueKind::OrphanPrefix,
+            IssueKind::Overlap,
+        ]
+    );
+
+    let fixable: Vec<String> = issues
+        .iter()
+        .filter(|issue| issue.fix.is_some())
+        .map(|issue| issue.id.clone())
+        .collect();
+    let fixed = setup.doctor_service.fix(&fixable).await?;
+    assert_eq!(fixed.len(), 4);
+
+    // The overlap is split around the inner task
+    let first = setup
+        .task_repository
+        .get_task_by_id("aaaaaaa".to_string())
+        .await?
+        .unwrap();
+    assert_eq!(first.end, Some(HOUR_MS));
+    let remainder = setup
+        .task_repository
+        .list_task_range(2 * HOUR_MS, 2 * HOUR_MS + 1)
+        .await?;
+    assert_eq!(remainder[0].task_name, "task aaaaaaa");
+    assert_eq!(remainder[0].end, Some(4 * HOUR_MS));
+
+    let swapped = setup
+        .task_repository
+        .get_task_by_id("ccccccc".to_string())
+        .await?
+        .unwrap();
+    assert_eq!(
+        (swapped.start, swapped.end),
+        (5 * HOUR_MS, Some(6 * HOUR_MS))
+    );
+
+    let issues = setup
+        .doctor_service
+        .scan(Duration::from_secs(12 * 3600))
+        .await?;
+    assert_eq!(kinds(&issues), vec![IssueKind::LongTask]);
+
+    // Repairs are regular mutations that can be undone
+    assert!(setup.task_service.undo().await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_short_gap_is_closed() -> eyre::Result<()> {
+    let setup = setup();
+    setup
+        .task_repository
+        .apply_actions(vec![
+            TaskAction::Upsert(task("aaaaaaa", "laptop", 0, Some(HOUR_MS))),
+            TaskAction::Upsert(task(
+                "bbbbbbb",
+                "laptop",
+                HOUR_MS + 30_000,
+                Some(2 * HOUR_MS),
+            )),
+            // A break between two tasks isn't an issue
+            TaskAction::Upsert(task("ccccccc", "laptop", 3 * HOUR_MS, Some(4 * HOUR_MS))),
+        ])
+        .await?;
+
+    let issues = setup
+        .doctor_service
+        .scan(Duration::from_secs(12 * 3600))
+        .await?;
+    assert_eq!(kinds(&issues), vec![IssueKind::Gap]);
+
+    setup.doctor_service.fix(&[issues[0].id.clone()]).await?;
+    let first = setup
+        .task_repository
+        .get_task_by_id("aaaaaaa".to_string())
+        .await?
+        .unwrap();
+    assert_eq!(first.end, Some(HOUR_MS + 30_000));
+    assert!(setup
+        .doctor_service
+        .scan(Duration::from_secs(12 * 3600))
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_overlap_with_task_starting_in_the_future() {
+    let now = 10 * HOUR_MS;
+    let first = task("aaaaaaa", "laptop", now - HOUR_MS, None);
+    let second = task("bbbbbbb", "desktop", now + HOUR_MS, None);
+
+    let issue = overlap_issue(&first, &second, now);
+    assert_eq!(issue.kind, IssueKind::Overlap);
+}
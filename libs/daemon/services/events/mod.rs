@@ -0,0 +1,81 @@
+use crate::{
+    entities::{activity::Activity, task::Task},
+    repositories::task::defs::TaskAction,
+    services::afk::AfkPeriod,
+};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+#[cfg(test)]
+mod tests;
+
+/// Capacity of the channel, slow subscribers miss the oldest events.
+const EVENT_BUS_CAPACITY: usize = 64;
+
+/// Events published by the services, forwarded as D-Bus signals.
+#[derive(Debug, Clone)]
+pub enum DaemonEvent {
+    /// A task was created or updated
+    TaskChanged(Task),
+    /// A task was deleted
+    TaskDeleted(String),
+    /// The running task started, stopped or was edited
+    CurrentTaskChanged(Option<Task>),
+    /// The focused window changed
+    ActivityRecorded(Activity),
+    /// A task was running during an idle span longer than the AFK threshold
+    AfkDetected(AfkPeriod),
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DaemonEvent>,
+    /// Last running task published, to only signal actual changes
+    current_task: Arc<Mutex<Option<Task>>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_BUS_CAPACITY).0,
+            current_task: Arc::default(),
+        }
+    }
+}
+
+impl EventBus {
+    /// Publishes an event, dropping it when nobody is subscribed.
+    pub fn publish(&self, event: DaemonEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Publishes the events of a task mutation, `current_task` being the
+    /// running task once the actions are applied.
+    pub fn publish_task_actions(&self, actions: &[TaskAction], current_task: Option<Task>) {
+        for action in actions {
+            self.publish(match action {
+                TaskAction::Upsert(task) => DaemonEvent::TaskChanged(task.clone()),
+                TaskAction::Delete(task_id) => DaemonEvent::TaskDeleted(task_id.clone()),
+            });
+        }
+
+        let Ok(mut last_current_task) = self.current_task.lock() else {
+            tracing::warn!("The current task lock is poisoned, skipping the current task signal");
+            return;
+        };
+        let changed = match (last_current_task.as_ref(), current_task.as_ref()) {
+            (Some(last), Some(current)) => last.get_hash() != current.get_hash(),
+            (None, None) => false,
+            _ => true,
+        };
+
+        if changed {
+            *last_current_task = current_task.clone();
+            self.publish(DaemonEvent::CurrentTaskChanged(current_task));
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DaemonEvent> {
+        self.sender.subscribe()
+    }
+}
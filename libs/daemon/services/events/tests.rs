@@ -0,0 +1,46 @@
+use super::*;
+
+fn sample_task(id: &str, end: Option<u64>) -> Task {
+    Task::builder()
+        .id(id.to_string())
+        .task_name("sample".to_string())
+        .computer_name("laptop".to_string())
+        .start(0)
+        .end(end)
+        .build()
+}
+
+fn drain(events: &mut broadcast::Receiver<DaemonEvent>) -> Vec<DaemonEvent> {
+    std::iter::from_fn(|| events.try_recv().ok()).collect()
+}
+
+#[test]
+fn test_current_task_changes_are_deduplicated() {
+    let event_bus = EventBus::default();
+    let mut events = event_bus.subscribe();
+
+    let running = sample_task("aaaaaaa", None);
+    event_bus.publish_task_actions(
+        &[TaskAction::Upsert(running.clone())],
+        Some(running.clone()),
+    );
+    let published = drain(&mut events);
+    assert!(matches!(&published[..], [
+        DaemonEvent::TaskChanged(_),
+        DaemonEvent::CurrentTaskChanged(Some(task)),
+    ] if task.id == "aaaaaaa"));
+
+    // Editing another task doesn't change the running one
+    let other = sample_task("bbbbbbb", Some(10));
+    event_bus.publish_task_actions(&[TaskAction::Upsert(other)], Some(running));
+    assert!(matches!(
+        &drain(&mut events)[..],
+        [DaemonEvent::TaskChanged(_)]
+    ));
+
+    event_bus.publish_task_actions(&[TaskAction::Delete("aaaaaaa".to_string())], None);
+    assert!(matches!(&drain(&mut events)[..], [
+        DaemonEvent::TaskDeleted(id),
+        DaemonEvent::CurrentTaskChanged(None),
+    ] if id == "aaaaaaa"));
+}
@@ -0,0 +1,394 @@
+use crate::{
+    config::defs::GitSyncConfig,
+    entities::task::Task,
+    repositories::task::{defs::TaskAction, TaskRepository},
+    services::task::import::TaskImporter,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    process::Command,
+    sync::{Mutex, Notify},
+};
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+/// Directory of the working tree where tasks are exported, one file per task.
+const TASKS_DIR: &str = "tasks";
+
+/// Delay letting a burst of task mutations end up in a single commit.
+const COMMIT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Keeps the task store in sync with a git remote. Task mutations are
+/// exported to the working tree and committed in the background, remote
+/// changes are merged and re-imported into the database.
+#[wrap_builder(Arc)]
+pub struct GitSyncService {
+    config: GitSyncConfig,
+    working_tree: PathBuf,
+    computer_name: String,
+    task_repository: TaskRepository,
+    task_importer: TaskImporter,
+    /// Serializes git operations on the working tree
+    #[builder(default)]
+    lock: Mutex<()>,
+    /// Actions waiting to be exported and committed
+    #[builder(default)]
+    pending: std::sync::Mutex<Vec<TaskAction>>,
+    #[builder(default)]
+    pending_notify: Notify,
+}
+
+impl GitSyncServiceInner {
+    /// Periodically synchronizes the task store with the remote, and commits
+    /// the recorded actions meanwhile.
+    pub async fn serve(&self) -> eyre::Result<()> {
+        let interval = self.config.get_interval();
+        tracing::info!(
+            "Git synchronization enabled with remote '{}' (every {}s).",
+            self.config.remote,
+            interval.as_secs()
+        );
+
+        tokio::join!(self.sync_loop(interval), self.commit_loop());
+        Ok(())
+    }
+
+    async fn sync_loop(&self, interval: Duration) {
+        loop {
+            match self.sync().await {
+                Ok(imported) => {
+                    tracing::info!(
+                        "Git synchronization done, {} change(s) imported.",
+                        imported.len()
+                    )
+                }
+                Err(e) => tracing::warn!("Git synchronization failed: {e}"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    async fn commit_loop(&self) {
+        loop {
+            self.pending_notify.notified().await;
+            tokio::time::sleep(COMMIT_DEBOUNCE).await;
+
+            let _guard = self.lock.lock().await;
+            if let Err(e) = self.commit_pending().await {
+                tracing::warn!("Failed to commit task changes for git synchronization: {e}");
+            }
+        }
+    }
+
+    /// Queues the given actions to be exported to the working tree and
+    /// committed, without waiting for git.
+    pub fn record(&self, actions: &[TaskAction]) -> eyre::Result<()> {
+        if actions.is_empty() {
+            return Ok(());
+        }
+
+        self.pending
+            .lock()
+            .map_err(|_| eyre::eyre!("The pending git actions lock is poisoned"))?
+            .extend_from_slice(actions);
+        self.pending_notify.notify_one();
+        Ok(())
+    }
+
+    /// Exports and commits the recorded actions, the working tree lock being held.
+    async fn commit_pending(&self) -> eyre::Result<()> {
+        self.ensure_initialized().await?;
+
+        let actions = std::mem::take(
+            &mut *self
+                .pending
+                .lock()
+                .map_err(|_| eyre::eyre!("The pending git actions lock is poisoned"))?,
+        );
+        if actions.is_empty() {
+            return Ok(());
+        }
+
+        for action in &actions {
+            self.write_action(action)?;
+        }
+
+        self.commit(&describe_actions(&actions)).await?;
+        self.task_repository.mark_synced(&actions).await
+    }
+
+    /// Merges the remote branch, imports remote changes into the database
+    /// and pushes local commits. Returns the actions applied to the store.
+    pub async fn sync(&self) -> eyre::Result<Vec<TaskAction>> {
+        let _guard = self.lock.lock().await;
+        self.commit_pending().await?;
+
+        let branch = self.config.get_branch();
+        let remote_ref = format!("origin/{branch}");
+        self.git(&["fetch", "origin"]).await?;
+
+        let mut imported = Vec::new();
+        if self
+            .git_succeeds(&["rev-parse", "--verify", "-q", &remote_ref])
+            .await?
+        {
+            let previous_head = self.head().await?;
+            let concurrent = self.merge(&remote_ref).await?;
+            let mut remote_actions = self.changed_since(previous_head.as_deref()).await?;
+            remote_actions.extend(concurrent.iter().cloned());
+            let upserted: Vec<String> = remote_actions
+                .iter()
+                .filter_map(|action| match action {
+                    TaskAction::Upsert(task) => Some(task.id.clone()),
+                    TaskAction::Delete(_) => None,
+                })
+                .collect();
+            imported = self.task_importer.import(remote_actions).await?;
+
+            // Export the tasks as the import left them, or as deleted when the
+            // import didn't bring them back
+            let mut exported = Vec::new();
+            for task_id in upserted {
+                match self.task_repository.get_task_by_id(task_id.clone()).await? {
+                    Some(task) => exported.push(TaskAction::Upsert(task)),
+                    None => exported.push(TaskAction::Delete(task_id)),
+                }
+            }
+            if !exported.is_empty() {
+                for action in &exported {
+                    self.write_action(action)?;
+                }
+                self.commit("Merge tasks changed on several devices")
+                    .await?;
+                self.task_repository.mark_synced(&exported).await?;
+            }
+        }
+
+        if self.head().await?.is_some() {
+            self.git(&["push", "origin", &format!("HEAD:refs/heads/{branch}")])
+                .await?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Creates the working tree on first use and exports every local task.
+    async fn ensure_initialized(&self) -> eyre::Result<()> {
+        if self.working_tree.join(".git").exists() {
+            return Ok(());
+        }
+
+        std::fs::create_dir_all(self.working_tree.join(TASKS_DIR))?;
+        self.git(&["init", "-q", "-b", &self.config.get_branch()])
+            .await?;
+        self.git(&["remote", "add", "origin", &self.config.remote])
+            .await?;
+
+        let actions: Vec<TaskAction> = self
+            .task_repository
+            .list_last_tasks(0, u64::MAX)
+            .await?
+            .into_iter()
+            .map(TaskAction::Upsert)
+            .collect();
+        for action in &actions {
+            self.write_action(action)?;
+        }
+
+        self.commit(&format!("Initial export from {}", self.computer_name))
+            .await?;
+        self.task_repository.mark_synced(&actions).await
+    }
+
+    fn task_path(&self, task_id: &str) -> PathBuf {
+        self.working_tree
+            .join(TASKS_DIR)
+            .join(format!("{task_id}.json"))
+    }
+
+    fn write_action(&self, action: &TaskAction) -> eyre::Result<()> {
+        match action {
+            TaskAction::Upsert(task) => {
+                let content = serde_json::to_string_pretty(task)?;
+                std::fs::write(self.task_path(&task.id), content + "\n")?;
+            }
+            TaskAction::Delete(task_id) => {
+                let path = self.task_path(task_id);
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn commit(&self, message: &str) -> eyre::Result<()> {
+        self.git(&["add", "-A", TASKS_DIR]).await?;
+
+        // Nothing staged, e.g. an upsert that didn't change the file
+        if self.git_succeeds(&["diff", "--cached", "--quiet"]).await? {
+            return Ok(());
+        }
+
+        self.git(&["commit", "-q", "-m", message]).await?;
+        Ok(())
+    }
+
+    async fn head(&self) -> eyre::Result<Option<String>> {
+        if !self
+            .git_succeeds(&["rev-parse", "--verify", "-q", "HEAD"])
+            .await?
+        {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.git(&["rev-parse", "HEAD"]).await?.trim().to_string(),
+        ))
+    }
+
+    /// Merges the remote branch. A task edited on both sides keeps its local
+    /// file and is returned in its remote version for the import to settle.
+    /// A task edited on one side and deleted on the other is kept, the import
+    /// then deciding whether the edit brings it back.
+    async fn merge(&self, remote_ref: &str) -> eyre::Result<Vec<TaskAction>> {
+        let merged = self
+            .git_succeeds(&[
+                "merge",
+                "-q",
+                "--no-edit",
+                "--allow-unrelated-histories",
+                remote_ref,
+            ])
+            .await?;
+
+        if merged {
+            return Ok(Vec::new());
+        }
+
+        let mut concurrent = Vec::new();
+        let unmerged = self
+            .git(&["diff", "--name-only", "--diff-filter=U"])
+            .await?;
+        for path in unmerged.lines().filter(|l| !l.is_empty()) {
+            let ours = format!(":2:{path}");
+            let theirs = format!(":3:{path}");
+            let edited_on_both_sides = self.git_succeeds(&["cat-file", "-e", &ours]).await?
+                && self.git_succeeds(&["cat-file", "-e", &theirs]).await?;
+
+            if edited_on_both_sides {
+                let content = self.git(&["show", &theirs]).await?;
+                let task: Task = serde_json::from_str(&content)
+                    .map_err(|e| eyre::eyre!("Invalid task file '{path}': {e}"))?;
+                concurrent.push(TaskAction::Upsert(task));
+                self.git(&["checkout", "--ours", "--", path]).await?;
+                self.git(&["add", "--", path]).await?;
+            } else if self.working_tree.join(path).exists() {
+                self.git(&["add", "--", path]).await?;
+            } else {
+                self.git(&["rm", "-q", "--cached", "--", path]).await?;
+            }
+        }
+
+        self.git(&["commit", "-q", "--no-edit"]).await?;
+        Ok(concurrent)
+    }
+
+    /// Lists the task changes introduced since `previous_head`.
+    async fn changed_since(&self, previous_head: Option<&str>) -> eyre::Result<Vec<TaskAction>> {
+        let changes: Vec<(char, String)> = match previous_head {
+            Some(head) => self
+                .git(&[
+                    "diff",
+                    "--no-renames",
+                    "--name-status",
+                    head,
+                    "HEAD",
+                    "--",
+                    TASKS_DIR,
+                ])
+                .await?
+                .lines()
+                .filter_map(|line| {
+                    let (status, path) = line.split_once('\t')?;
+                    Some((status.chars().next()?, path.to_string()))
+                })
+                .collect(),
+            None => self
+                .git(&["ls-files", "--", TASKS_DIR])
+                .await?
+                .lines()
+                .map(|path| ('A', path.to_string()))
+                .collect(),
+        };
+
+        let mut actions = Vec::new();
+        for (status, path) in changes {
+            let Some(task_id) = Path::new(&path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string())
+            else {
+                continue;
+            };
+
+            if status == 'D' {
+                actions.push(TaskAction::Delete(task_id));
+            } else {
+                let content = std::fs::read_to_string(self.working_tree.join(&path))?;
+                let task: Task = serde_json::from_str(&content)
+                    .map_err(|e| eyre::eyre!("Invalid task file '{path}': {e}"))?;
+                actions.push(TaskAction::Upsert(task));
+            }
+        }
+
+        Ok(actions)
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("git");
+        command
+            .arg("-C")
+            .arg(&self.working_tree)
+            .args(["-c", &format!("user.name={}", self.computer_name)])
+            .args(["-c", &format!("user.email={}@o324", self.computer_name)])
+            .args(args);
+        command
+    }
+
+    /// Runs a git command and returns its stdout, failing on a non-zero exit code.
+    async fn git(&self, args: &[&str]) -> eyre::Result<String> {
+        let output = self.command(args).output().await?;
+
+        if !output.status.success() {
+            eyre::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Runs a git command and returns whether it exited successfully.
+    async fn git_succeeds(&self, args: &[&str]) -> eyre::Result<bool> {
+        Ok(self.command(args).output().await?.status.success())
+    }
+}
+
+fn describe_actions(actions: &[TaskAction]) -> String {
+    actions
+        .iter()
+        .map(|action| match action {
+            TaskAction::Upsert(task) => format!("Update task {}", task.id),
+            TaskAction::Delete(task_id) => format!("Delete task {task_id}"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,246 @@
+use super::*;
+use crate::{
+    core::testing::SyncDevice,
+    entities::task::TaskUpdate,
+    repositories::task::defs::{StartTaskInput, TaskRef},
+    services::task::TaskService,
+};
+use tempfile::{tempdir, TempDir};
+
+struct Device {
+    _device: SyncDevice,
+    task_service: TaskService,
+    git_sync_service: GitSyncService,
+}
+
+fn setup_device(computer_name: &str, remote: &Path) -> Device {
+    let device = SyncDevice::new(computer_name);
+
+    let git_sync_service = GitSyncService::builder()
+        .config(GitSyncConfig {
+            remote: remote.to_string_lossy().to_string(),
+            branch: None,
+            working_tree: None,
+            interval_secs: None,
+        })
+        .working_tree(device.dir.path().join("git-sync"))
+        .computer_name(computer_name.to_string())
+        .task_repository(device.task_repository.clone())
+        .task_importer(device.task_importer())
+        .build();
+
+    Device {
+        task_service: device.task_service(Some(git_sync_service.clone())),
+        git_sync_service,
+        _device: device,
+    }
+}
+
+fn setup_remote() -> TempDir {
+    let dir = tempdir().unwrap();
+    let status = std::process::Command::new("git")
+        .args(["init", "-q", "--bare"])
+        .arg(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    dir
+}
+
+fn start_input(task_name: &str) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at: None,
+    }
+}
+
+#[tokio::test]
+async fn test_tasks_propagate_between_devices() -> eyre::Result<()> {
+    let remote = setup_remote();
+    let laptop = setup_device("laptop", remote.path());
+    let desktop = setup_device("desktop", remote.path());
+
+    let task = laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?
+        .task;
+    laptop.task_service.stop_current_task().await?;
+    laptop.git_sync_service.sync().await?;
+
+    let imported = desktop.git_sync_service.sync().await?;
+    assert_eq!(imported.len(), 1);
+
+    let synced = desktop
+        .task_service
+        .get_task(task.id.clone())
+        .await?
+        .expect("task should have been imported");
+    assert_eq!(synced.task.task_name, "write report");
+    assert_eq!(synced.task.computer_name, "laptop");
+    assert!(synced.task.end.is_some());
+
+    // Deleting on one device removes the task on the other
+    desktop.task_service.delete_task(task.id.clone()).await?;
+    desktop.git_sync_service.sync().await?;
+    laptop.git_sync_service.sync().await?;
+
+    assert!(laptop.task_service.get_task(task.id).await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_existing_tasks_are_exported_on_first_sync() -> eyre::Result<()> {
+    let remote = setup_remote();
+    let laptop = setup_device("laptop", remote.path());
+    let desktop = setup_device("desktop", remote.path());
+
+    let laptop_task = laptop
+        .task_service
+        .start_new_task(start_input("laptop task"))
+        .await?
+        .task;
+    laptop.task_service.stop_current_task().await?;
+
+    let desktop_task = desktop
+        .task_service
+        .start_new_task(start_input("desktop task"))
+        .await?
+        .task;
+
+    laptop.git_sync_service.sync().await?;
+    desktop.git_sync_service.sync().await?;
+    laptop.git_sync_service.sync().await?;
+
+    assert!(desktop
+        .task_service
+        .get_task(laptop_task.id)
+        .await?
+        .is_some());
+    assert!(laptop
+        .task_service
+        .get_task(desktop_task.id)
+        .await?
+        .is_some());
+
+    // A second synchronization without changes imports nothing
+    assert!(laptop.git_sync_service.sync().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_task_edited_on_both_devices_keeps_both_edits() -> eyre::Result<()> {
+    let remote = setup_remote();
+    let laptop = setup_device("laptop", remote.path());
+    let desktop = setup_device("desktop", remote.path());
+
+    let task = laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?
+        .task;
+    laptop.task_service.stop_current_task().await?;
+    laptop.git_sync_service.sync().await?;
+    desktop.git_sync_service.sync().await?;
+
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    let rename = TaskUpdate {
+        task_name: Some("write the report".to_string()),
+        ..Default::default()
+    };
+    laptop
+        .task_service
+        .edit_task(TaskRef::Id(task.id.clone()), rename)
+        .await?;
+    let set_project = TaskUpdate {
+        project: Some(Some("o324".to_string())),
+        ..Default::default()
+    };
+    desktop
+        .task_service
+        .edit_task(TaskRef::Id(task.id.clone()), set_project)
+        .await?;
+
+    laptop.git_sync_service.sync().await?;
+    desktop.git_sync_service.sync().await?;
+    laptop.git_sync_service.sync().await?;
+
+    for device in [&laptop, &desktop] {
+        let synced = device
+            .task_service
+            .get_task(task.id.clone())
+            .await?
+            .unwrap();
+        assert_eq!(synced.task.task_name, "write the report");
+        assert_eq!(synced.task.project.as_deref(), Some("o324"));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_task_deleted_on_one_device_and_edited_on_the_other() -> eyre::Result<()> {
+    let remote = setup_remote();
+    let laptop = setup_device("laptop", remote.path());
+    let desktop = setup_device("desktop", remote.path());
+    let rename = |task_name: &str| TaskUpdate {
+        task_name: Some(task_name.to_string()),
+        ..Default::default()
+    };
+
+    let revived = laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?
+        .task;
+    let deleted = laptop
+        .task_service
+        .start_new_task(start_input("review"))
+        .await?
+        .task;
+    laptop.task_service.stop_current_task().await?;
+    laptop.git_sync_service.sync().await?;
+    desktop.git_sync_service.sync().await?;
+
+    // The desktop renames the second task before the laptop renames then
+    // deletes it, its edit being older than the deleted version
+    desktop
+        .task_service
+        .edit_task(TaskRef::Id(deleted.id.clone()), rename("review code"))
+        .await?;
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    laptop
+        .task_service
+        .edit_task(TaskRef::Id(deleted.id.clone()), rename("review docs"))
+        .await?;
+    laptop.task_service.delete_task(deleted.id.clone()).await?;
+    laptop.task_service.delete_task(revived.id.clone()).await?;
+
+    // The desktop renames the first task after the laptop deleted it
+    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    desktop
+        .task_service
+        .edit_task(TaskRef::Id(revived.id.clone()), rename("write the report"))
+        .await?;
+
+    laptop.git_sync_service.sync().await?;
+    desktop.git_sync_service.sync().await?;
+    laptop.git_sync_service.sync().await?;
+    desktop.git_sync_service.sync().await?;
+
+    for device in [&laptop, &desktop] {
+        let task = device
+            .task_service
+            .get_task(revived.id.clone())
+            .await?
+            .expect("the edit made after the deletion should bring the task back");
+        assert_eq!(task.task.task_name, "write the report");
+        assert!(device
+            .task_service
+            .get_task(deleted.id.clone())
+            .await?
+            .is_none());
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use crate::{
+    config::defs::ServerSyncConfig,
+    repositories::{
+        sync_cursor::SyncCursorRepository,
+        task::{defs::TaskAction, TaskRepository},
+        task_operation::TaskOperationRepository,
+    },
+    services::{
+        sync_server::protocol::{PullQuery, PullResponse, PushRequest, PushResponse},
+        task::import::TaskImporter,
+    },
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+/// Number of local operations sent per push.
+const PUSH_BATCH_SIZE: u64 = 500;
+
+/// Keeps the task store in sync through an `o324-daemon serve-sync` server.
+/// The operations recorded locally are pushed after the last one the server
+/// received from this computer, and the operations of the other computers
+/// are pulled after the last stored cursor.
+#[wrap_builder(Arc)]
+pub struct HttpSyncService {
+    config: ServerSyncConfig,
+    computer_name: String,
+    task_repository: TaskRepository,
+    task_importer: TaskImporter,
+    task_operation_repository: TaskOperationRepository,
+    sync_cursor_repository: SyncCursorRepository,
+    #[builder(default)]
+    client: reqwest::Client,
+    /// Prevents concurrent synchronizations from pushing the same operations
+    #[builder(default)]
+    lock: Mutex<()>,
+}
+
+impl HttpSyncServiceInner {
+    /// Periodically synchronizes the task store with the server.
+    pub async fn serve(&self) -> eyre::Result<()> {
+        let interval = self.config.get_interval();
+        tracing::info!(
+            "Server synchronization enabled with '{}' (every {}s).",
+            self.config.url,
+            interval.as_secs()
+        );
+
+        loop {
+            match self.sync().await {
+                Ok(imported) => {
+                    tracing::info!(
+                        "Server synchronization done, {} change(s) imported.",
+                        imported.len()
+                    )
+                }
+                Err(e) => tracing::warn!("Server synchronization failed: {e}"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Imports the operations of the other computers, then pushes the local
+    /// ones. Returns the actions applied to the store.
+    pub async fn sync(&self) -> eyre::Result<Vec<TaskAction>> {
+        let _guard = self.lock.lock().await;
+
+        let mut imported = Vec::new();
+        let mut cursor = self.sync_cursor_repository.get(&self.config.url)?;
+        let last_seq = loop {
+            let page = self.pull(cursor).await?;
+            let actions = page
+                .operations
+                .into_iter()
+                .map(|operation| operation.action)
+                .collect();
+            imported.extend(self.task_importer.import(actions).await?);
+
+            if let Some(page_cursor) = page.cursor.filter(|c| Some(*c) != cursor) {
+                self.sync_cursor_repository
+                    .set(&self.config.url, page_cursor)?;
+                cursor = Some(page_cursor);
+            }
+            if !page.has_more {
+                break page.last_seq;
+            }
+        };
+
+        self.push(last_seq).await?;
+        Ok(imported)
+    }
+
+    /// Pushes the operations recorded after `last_seq`, the last one the
+    /// server received. The whole log is pushed when the server never
+    /// received anything from this computer.
+    async fn push(&self, last_seq: Option<u64>) -> eyre::Result<()> {
+        let local_last_seq = self.task_operation_repository.last_seq().await?;
+        let mut from_seq = match last_seq {
+            Some(seq) if local_last_seq.is_none_or(|local| local < seq) => eyre::bail!(
+                "The sync server received more operations from '{}' than this device \
+                recorded, was its database reset? Use another `computer_name` to \
+                synchronize it.",
+                self.computer_name
+            ),
+            Some(seq) => seq + 1,
+            None => self.export().await?,
+        };
+
+        loop {
+            let operations = self
+                .task_operation_repository
+                .list_since(from_seq, PUSH_BATCH_SIZE)
+                .await?;
+            let Some(last_operation) = operations.last() else {
+                return Ok(());
+            };
+            let next_seq = last_operation.seq + 1;
+            let is_last_batch = (operations.len() as u64) < PUSH_BATCH_SIZE;
+            let actions: Vec<TaskAction> = operations
+                .iter()
+                .map(|operation| operation.action.clone())
+                .collect();
+
+            let response: PushResponse = self
+                .request(reqwest::Method::POST, "push")?
+                .json(&PushRequest { operations })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            if response.last_seq != Some(next_seq - 1) {
+                eyre::bail!("The sync server didn't acknowledge the pushed operations");
+            }
+            self.task_repository.mark_synced(&actions).await?;
+
+            if is_last_batch {
+                return Ok(());
+            }
+            from_seq = next_seq;
+        }
+    }
+
+    /// Records the tasks of this computer missing from the operation log,
+    /// e.g. created before it existed, so that pushing the whole log sends
+    /// them. Imported tasks are left to the computers that created them.
+    /// Returns the sequence number to push from.
+    async fn export(&self) -> eyre::Result<u64> {
+        let logged = self.task_operation_repository.list_task_ids().await?;
+        let upserts: Vec<TaskAction> = self
+            .task_repository
+            .list_last_tasks(0, u64::MAX)
+            .await?
+            .into_iter()
+            .filter(|task| task.computer_name == self.computer_name && !logged.contains(&task.id))
+            .map(TaskAction::Upsert)
+            .collect();
+
+        self.task_operation_repository.append(&upserts).await?;
+        Ok(0)
+    }
+
+    async fn pull(&self, after: Option<u64>) -> eyre::Result<PullResponse> {
+        Ok(self
+            .request(reqwest::Method::GET, "pull")?
+            .query(&PullQuery { after, limit: None })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+    ) -> eyre::Result<reqwest::RequestBuilder> {
+        let mut url = reqwest::Url::parse(&self.config.url)
+            .map_err(|e| eyre::eyre!("Invalid sync server url '{}': {e}", self.config.url))?;
+        url.path_segments_mut()
+            .map_err(|_| eyre::eyre!("Invalid sync server url '{}'", self.config.url))?
+            .pop_if_empty()
+            .extend(["v1", "devices", &self.computer_name, endpoint]);
+
+        let request = self.client.request(method, url);
+        Ok(match &self.config.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use super::*;
+use crate::{
+    core::{storage::Storage, testing::SyncDevice},
+    entities::MODELS,
+    repositories::{sync_server::SyncServerRepository, task::defs::StartTaskInput},
+    services::{sync_server::SyncServerService, task::TaskService},
+};
+use tempfile::{tempdir, TempDir};
+use tokio::net::TcpListener;
+
+struct Server {
+    _dir: TempDir,
+    url: String,
+}
+
+struct Device {
+    _device: SyncDevice,
+    task_service: TaskService,
+    task_operation_repository: TaskOperationRepository,
+    http_sync_service: HttpSyncService,
+}
+
+async fn setup_server(token: Option<&str>) -> Server {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("sync-server.db"), &MODELS).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let server = SyncServerService::builder()
+        .repository(SyncServerRepository::builder().storage(storage).build())
+        .token(token.map(str::to_string))
+        .build();
+    tokio::spawn(async move { server.serve(listener).await });
+
+    Server { _dir: dir, url }
+}
+
+fn setup_device(computer_name: &str, server: &Server, token: Option<&str>) -> Device {
+    let device = SyncDevice::new(computer_name);
+
+    let http_sync_service = HttpSyncService::builder()
+        .config(ServerSyncConfig {
+            url: server.url.clone(),
+            token: token.map(str::to_string),
+            interval_secs: None,
+        })
+        .computer_name(computer_name.to_string())
+        .task_repository(device.task_repository.clone())
+        .task_importer(device.task_importer())
+        .task_operation_repository(device.task_operation_repository())
+        .sync_cursor_repository(device.sync_cursor_repository())
+        .build();
+
+    Device {
+        task_service: device.task_service(None),
+        task_operation_repository: device.task_operation_repository(),
+        http_sync_service,
+        _device: device,
+    }
+}
+
+fn start_input(task_name: &str) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at: None,
+    }
+}
+
+async fn task_names(device: &Device) -> eyre::Result<Vec<String>> {
+    let mut names: Vec<String> = device
+        .task_service
+        .list_last_tasks(0, u64::MAX)
+        .await?
+        .into_iter()
+        .map(|task| task.task.task_name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[tokio::test]
+async fn test_devices_converge_through_server() -> eyre::Result<()> {
+    let server = setup_server(None).await;
+    let laptop = setup_device("laptop", &server, None);
+    let desktop = setup_device("desktop", &server, None);
+
+    // Operations recorded before the first synchronization are pushed
+    let task = laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?
+        .task;
+    laptop.task_service.stop_current_task().await?;
+    desktop
+        .task_service
+        .start_new_task(start_input("review"))
+        .await?;
+
+    laptop.http_sync_service.sync().await?;
+    // The start and the stop of the task
+    assert_eq!(desktop.http_sync_service.sync().await?.len(), 2);
+    assert_eq!(laptop.http_sync_service.sync().await?.len(), 1);
+
+    assert_eq!(task_names(&laptop).await?, vec!["review", "write report"]);
+    assert_eq!(task_names(&desktop).await?, task_names(&laptop).await?);
+
+    // Edits and deletions propagate, and are not sent back to their origin
+    desktop.task_service.delete_task(task.id.clone()).await?;
+    desktop.http_sync_service.sync().await?;
+    assert_eq!(laptop.http_sync_service.sync().await?.len(), 1);
+    assert!(laptop.task_service.get_task(task.id).await?.is_none());
+    assert!(desktop.http_sync_service.sync().await?.is_empty());
+    assert!(laptop.http_sync_service.sync().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronization_doesnt_grow_operation_log() -> eyre::Result<()> {
+    let server = setup_server(None).await;
+    let laptop = setup_device("laptop", &server, None);
+    let desktop = setup_device("desktop", &server, None);
+
+    laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?;
+    laptop.task_service.stop_current_task().await?;
+    let last_seq = laptop.task_operation_repository.last_seq().await?;
+
+    // The first push sends the log as is, the tasks being already in it
+    laptop.http_sync_service.sync().await?;
+    assert_eq!(laptop.task_operation_repository.last_seq().await?, last_seq);
+
+    // Imported tasks aren't recorded as local edits to push back
+    assert_eq!(desktop.http_sync_service.sync().await?.len(), 2);
+    assert!(desktop
+        .task_operation_repository
+        .last_seq()
+        .await?
+        .is_none());
+    assert!(laptop.http_sync_service.sync().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_server_rejects_invalid_token() -> eyre::Result<()> {
+    let server = setup_server(Some("secret")).await;
+    let laptop = setup_device("laptop", &server, Some("secret"));
+    let intruder = setup_device("intruder", &server, Some("guess"));
+
+    laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?;
+
+    laptop.http_sync_service.sync().await?;
+    assert!(intruder.http_sync_service.sync().await.is_err());
+    assert!(task_names(&intruder).await?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::{core::utils::unix_now, services::task::TaskService};
+use futures::StreamExt;
+use std::{sync::Arc, time::Duration};
+use wrap_builder::wrap_builder;
+use zbus::{proxy, zvariant::OwnedFd, Connection};
+
+#[cfg(test)]
+mod tests;
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LoginManager {
+    fn inhibit(&self, what: &str, who: &str, why: &str, mode: &str) -> zbus::Result<OwnedFd>;
+
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn prepare_for_shutdown(&self, start: bool) -> zbus::Result<()>;
+}
+
+/// Stops the running task when the computer goes to sleep or shuts down.
+/// A delay inhibitor lock gives the daemon the time to record the sleep
+/// time before the system suspends.
+#[wrap_builder(Arc)]
+pub struct LogindService {
+    task_service: TaskService,
+    /// Sleep duration after which the running task is stopped at the sleep time
+    grace_period: Duration,
+    /// Connection to the system bus, overridden in tests
+    #[builder(default)]
+    connection: Option<Connection>,
+}
+
+impl LogindServiceInner {
+    pub async fn serve(&self) -> eyre::Result<()> {
+        let connection = match &self.connection {
+            Some(connection) => connection.clone(),
+            None => Connection::system().await?,
+        };
+
+        let manager = LoginManagerProxy::new(&connection).await?;
+        let mut sleep_signals = manager.receive_prepare_for_sleep().await?;
+        let mut shutdown_signals = manager.receive_prepare_for_shutdown().await?;
+
+        // The lock is released when dropped, letting the system proceed
+        let mut inhibitor = Some(inhibit(&manager).await?);
+        let mut sleeping_since: Option<u64> = None;
+
+        tracing::info!(
+            "Watching logind sleep and shutdown events (grace period: {}s).",
+            self.grace_period.as_secs()
+        );
+
+        loop {
+            tokio::select! {
+                Some(signal) = sleep_signals.next() => {
+                    if signal.args()?.start {
+                        tracing::info!("System is going to sleep.");
+                        sleeping_since = Some(unix_now());
+                        inhibitor.take();
+                    } else {
+                        tracing::info!("System resumed from sleep.");
+                        if let Some(slept_at) = sleeping_since.take() {
+                            self.handle_resume(slept_at, unix_now()).await?;
+                        }
+                        inhibitor = Some(inhibit(&manager).await?);
+                    }
+                }
+                Some(signal) = shutdown_signals.next() => {
+                    if signal.args()?.start {
+                        tracing::info!("System is shutting down.");
+                        self.task_service.stop_current_task_at(unix_now()).await?;
+                        inhibitor.take();
+                    } else {
+                        // The shutdown was cancelled
+                        inhibitor = Some(inhibit(&manager).await?);
+                    }
+                }
+                else => break,
+            }
+        }
+
+        tracing::info!("Logind signal streams have ended.");
+        Ok(())
+    }
+
+    /// Stops the running task at `slept_at` if the computer slept longer than the grace period.
+    async fn handle_resume(&self, slept_at: u64, resumed_at: u64) -> eyre::Result<()> {
+        let slept_for = Duration::from_millis(resumed_at.saturating_sub(slept_at));
+        if slept_for < self.grace_period {
+            return Ok(());
+        }
+
+        if let Some(task) = self.task_service.stop_current_task_at(slept_at).await? {
+            tracing::info!(
+                "Stopped task '{}' at sleep time after {}s of sleep.",
+                task.task.task_name,
+                slept_for.as_secs()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+async fn inhibit(manager: &LoginManagerProxy<'_>) -> eyre::Result<OwnedFd> {
+    Ok(manager
+        .inhibit(
+            "sleep:shutdown",
+            "o324",
+            "Stop the running task before sleep or shutdown",
+            "delay",
+        )
+        .await?)
+}
@@ -0,0 +1,180 @@
+use super::*;
+use crate::{
+    core::{
+        storage::Storage,
+        testing::{build_task_service, PrivateBus},
+    },
+    entities::MODELS,
+    repositories::task::defs::StartTaskInput,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::{tempdir, TempDir};
+use zbus::{fdo, interface, object_server::SignalEmitter};
+
+const LOGIN_PATH: &str = "/org/freedesktop/login1";
+
+struct MockLoginManager {
+    inhibit_calls: Arc<AtomicUsize>,
+}
+
+#[interface(name = "org.freedesktop.login1.Manager")]
+impl MockLoginManager {
+    fn inhibit(&self, _what: &str, _who: &str, _why: &str, _mode: &str) -> fdo::Result<OwnedFd> {
+        self.inhibit_calls.fetch_add(1, Ordering::SeqCst);
+        let file =
+            std::fs::File::open("/dev/null").map_err(|e| fdo::Error::IOError(e.to_string()))?;
+        Ok(std::os::fd::OwnedFd::from(file).into())
+    }
+
+    #[zbus(signal)]
+    async fn prepare_for_sleep(emitter: &SignalEmitter<'_>, start: bool) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn prepare_for_shutdown(emitter: &SignalEmitter<'_>, start: bool) -> zbus::Result<()>;
+}
+
+struct Setup {
+    _bus: PrivateBus,
+    _dir: TempDir,
+    task_service: TaskService,
+    logind: Connection,
+    inhibit_calls: Arc<AtomicUsize>,
+}
+
+impl Setup {
+    async fn emitter(&self) -> zbus::Result<SignalEmitter<'static>> {
+        SignalEmitter::new(&self.logind, LOGIN_PATH)
+    }
+
+    /// Waits for the service to take its `count`-th inhibitor lock.
+    async fn wait_for_inhibit(&self, count: usize) {
+        for _ in 0..100 {
+            if self.inhibit_calls.load(Ordering::SeqCst) >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("service didn't take inhibitor lock #{count}");
+    }
+}
+
+async fn setup(grace_period: Duration) -> Setup {
+    let bus = PrivateBus::start();
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+
+    let task_service = build_task_service(storage, "laptop");
+
+    let inhibit_calls = Arc::new(AtomicUsize::new(0));
+    let logind = zbus::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .name("org.freedesktop.login1")
+        .unwrap()
+        .serve_at(
+            LOGIN_PATH,
+            MockLoginManager {
+                inhibit_calls: inhibit_calls.clone(),
+            },
+        )
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+
+    let service_connection = bus.connect().await;
+
+    let logind_service = LogindService::builder()
+        .task_service(task_service.clone())
+        .grace_period(grace_period)
+        .connection(Some(service_connection))
+        .build();
+    tokio::spawn(async move { logind_service.serve().await });
+
+    let setup = Setup {
+        _bus: bus,
+        _dir: dir,
+        task_service,
+        logind,
+        inhibit_calls,
+    };
+    setup.wait_for_inhibit(1).await;
+    setup
+}
+
+fn start_input(task_name: &str) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at: None,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires dbus-daemon, run with `cargo test -- --ignored`"]
+async fn test_long_sleep_stops_task_at_sleep_time() -> eyre::Result<()> {
+    let setup = setup(Duration::from_millis(200)).await;
+
+    let task = setup
+        .task_service
+        .start_new_task(start_input("report"))
+        .await?
+        .task;
+
+    let emitter = setup.emitter().await?;
+    MockLoginManager::prepare_for_sleep(&emitter, true).await?;
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let resumed_at = unix_now();
+    MockLoginManager::prepare_for_sleep(&emitter, false).await?;
+    setup.wait_for_inhibit(2).await;
+
+    let stopped = setup.task_service.get_task(task.id).await?.unwrap().task;
+    let end = stopped.end.expect("task should have been stopped");
+    assert!(end >= task.start && end + 300 <= resumed_at);
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires dbus-daemon, run with `cargo test -- --ignored`"]
+async fn test_short_sleep_keeps_task_running() -> eyre::Result<()> {
+    let setup = setup(Duration::from_secs(180)).await;
+
+    let task = setup
+        .task_service
+        .start_new_task(start_input("report"))
+        .await?
+        .task;
+
+    let emitter = setup.emitter().await?;
+    MockLoginManager::prepare_for_sleep(&emitter, true).await?;
+    MockLoginManager::prepare_for_sleep(&emitter, false).await?;
+    setup.wait_for_inhibit(2).await;
+
+    let task = setup.task_service.get_task(task.id).await?.unwrap().task;
+    assert!(task.end.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires dbus-daemon, run with `cargo test -- --ignored`"]
+async fn test_shutdown_stops_task() -> eyre::Result<()> {
+    let setup = setup(Duration::from_secs(180)).await;
+
+    let task = setup
+        .task_service
+        .start_new_task(start_input("report"))
+        .await?
+        .task;
+
+    let emitter = setup.emitter().await?;
+    MockLoginManager::prepare_for_shutdown(&emitter, true).await?;
+
+    for _ in 0..100 {
+        let current = setup.task_service.get_task(task.id.clone()).await?.unwrap();
+        if current.task.end.is_some() {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("task wasn't stopped on shutdown");
+}
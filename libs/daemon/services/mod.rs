@@ -2,3 +2,13 @@ pub mod dbus;
 pub mod task;
 pub mod activity;
 pub mod storage_bridge;
+pub mod git_sync;
+pub mod logind;
+pub mod nudge;
+pub mod afk;
+pub mod events;
+pub mod doctor;
+pub mod sync_server;
+pub mod http_sync;
+pub mod p2p_sync;
+pub mod vault;
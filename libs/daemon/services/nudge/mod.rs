@@ -0,0 +1,265 @@
+use crate::{
+    core::utils::unix_now, repositories::task::defs::StartTaskInput, services::task::TaskService,
+};
+use chrono::{Local, NaiveTime};
+use futures::StreamExt;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, MutexGuard},
+    time::Duration,
+};
+use wrap_builder::wrap_builder;
+use zbus::{proxy, zvariant::Value, Connection};
+
+#[cfg(test)]
+mod tests;
+
+/// Interval between two checks of the activity state.
+const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Action key of the notification button starting the suggested task.
+const START_ACTION: &str = "start";
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+}
+
+/// Local time range during which no nudge is sent, may wrap around midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = eyre::Report;
+
+    /// Parses a range such as "22:00-08:00".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| eyre::eyre!("Invalid quiet hours '{s}', expected e.g. '22:00-08:00'"))?;
+
+        Ok(Self {
+            start: NaiveTime::parse_from_str(start.trim(), "%H:%M")?,
+            end: NaiveTime::parse_from_str(end.trim(), "%H:%M")?,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NudgeState {
+    /// Start of the current stretch of activity without a running task, None while idle
+    active_since: Option<u64>,
+    last_nudge_at: Option<u64>,
+    /// Id of the last notification, replaced by the next nudge
+    last_notification_id: Option<u32>,
+    /// Task suggested by the last notification
+    suggestion: Option<(u32, StartTaskInput)>,
+}
+
+/// Sends a desktop notification when the user has been working for a while
+/// without a running task.
+#[wrap_builder(Arc)]
+pub struct NudgeService {
+    task_service: TaskService,
+    /// Sustained activity without a running task before a nudge is sent
+    threshold: Duration,
+    /// Minimum delay between two nudges
+    cooldown: Duration,
+    quiet_hours: Option<QuietHours>,
+    #[builder(default = DEFAULT_CHECK_INTERVAL)]
+    check_interval: Duration,
+    /// Connection to the session bus, overridden in tests
+    #[builder(default)]
+    connection: Option<Connection>,
+    #[builder(default)]
+    state: std::sync::Mutex<NudgeState>,
+}
+
+impl NudgeServiceInner {
+    fn state(&self) -> eyre::Result<MutexGuard<'_, NudgeState>> {
+        self.state
+            .lock()
+            .map_err(|_| eyre::eyre!("The nudge state lock is poisoned"))
+    }
+
+    /// Records user activity, e.g. a window focus change.
+    pub fn record_activity(&self, at: u64) -> eyre::Result<()> {
+        self.state()?.active_since.get_or_insert(at);
+        Ok(())
+    }
+
+    /// Records that the user went idle, ending the current stretch of activity.
+    pub fn record_idle(&self) -> eyre::Result<()> {
+        self.state()?.active_since = None;
+        Ok(())
+    }
+
+    pub async fn serve(&self) -> eyre::Result<()> {
+        let connection = match &self.connection {
+            Some(connection) => connection.clone(),
+            None => Connection::session().await?,
+        };
+
+        let notifications = NotificationsProxy::new(&connection).await?;
+        let mut actions = notifications.receive_action_invoked().await?;
+        let mut interval = tokio::time::interval(self.check_interval);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.check(&notifications).await {
+                        tracing::warn!("Failed to send a nudge notification: {e}");
+                    }
+                }
+                Some(signal) = actions.next() => {
+                    if let Err(e) = self.handle_action(signal).await {
+                        tracing::warn!("Failed to handle a nudge notification action: {e}");
+                    }
+                }
+                else => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self, notifications: &NotificationsProxy<'_>) -> eyre::Result<()> {
+        let now = unix_now();
+        let last_task = self
+            .task_service
+            .list_last_tasks(0, 1)
+            .await?
+            .into_iter()
+            .next()
+            .map(|t| t.task);
+
+        let (active_for, replaces_id) = {
+            let mut state = self.state()?;
+
+            // Time spent on a running task doesn't count towards a nudge
+            if last_task.as_ref().is_some_and(|t| t.end.is_none()) {
+                if let Some(since) = state.active_since.as_mut() {
+                    *since = now;
+                }
+                return Ok(());
+            }
+
+            let cooled_down = state
+                .last_nudge_at
+                .is_none_or(|at| now.saturating_sub(at) >= self.cooldown.as_millis() as u64);
+
+            match state.active_since {
+                Some(since) if cooled_down => (
+                    Duration::from_millis(now.saturating_sub(since)),
+                    state.last_notification_id.unwrap_or(0),
+                ),
+                _ => return Ok(()),
+            }
+        };
+
+        if active_for < self.threshold {
+            return Ok(());
+        }
+
+        if self
+            .quiet_hours
+            .is_some_and(|quiet_hours| quiet_hours.contains(Local::now().time()))
+        {
+            return Ok(());
+        }
+
+        let suggestion = last_task.map(|task| StartTaskInput {
+            task_name: task.task_name,
+            project: task.project,
+            tags: task.tags,
+            at: None,
+        });
+
+        let body = format!(
+            "You have been active for {} minutes with no running task.",
+            active_for.as_secs() / 60
+        );
+        let action_label = suggestion
+            .as_ref()
+            .map(|s| format!("Start '{}'", s.task_name));
+        let actions: Vec<&str> = match &action_label {
+            Some(label) => vec![START_ACTION, label.as_str()],
+            None => vec![],
+        };
+
+        let id = notifications
+            .notify(
+                "o324",
+                replaces_id,
+                "",
+                "Working without a timer?",
+                &body,
+                &actions,
+                HashMap::new(),
+                -1,
+            )
+            .await?;
+
+        // The previous notification and its suggestion are replaced by this one
+        let mut state = self.state()?;
+        state.last_nudge_at = Some(now);
+        state.last_notification_id = Some(id);
+        state.suggestion = suggestion.map(|suggestion| (id, suggestion));
+
+        Ok(())
+    }
+
+    async fn handle_action(&self, signal: ActionInvoked) -> eyre::Result<()> {
+        let args = signal.args()?;
+        if args.action_key != START_ACTION {
+            return Ok(());
+        }
+
+        let suggestion = {
+            let mut state = self.state()?;
+            match state.suggestion.take() {
+                Some((id, suggestion)) if id == args.id => suggestion,
+                other => {
+                    state.suggestion = other;
+                    return Ok(());
+                }
+            }
+        };
+
+        let task = self.task_service.start_new_task(suggestion).await?;
+        tracing::info!("Started task '{}' from a nudge.", task.task.task_name);
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use super::*;
+use crate::{
+    core::{
+        storage::Storage,
+        testing::{build_task_service, PrivateBus},
+    },
+    entities::MODELS,
+};
+use tempfile::{tempdir, TempDir};
+use zbus::{interface, object_server::SignalEmitter, zvariant::OwnedValue};
+
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+}
+
+#[test]
+fn test_quiet_hours() {
+    let night: QuietHours = "22:00-08:00".parse().unwrap();
+    assert!(night.contains(time("23:30")));
+    assert!(night.contains(time("07:59")));
+    assert!(!night.contains(time("08:00")));
+    assert!(!night.contains(time("12:00")));
+
+    let lunch: QuietHours = "12:00 - 13:30".parse().unwrap();
+    assert!(lunch.contains(time("12:45")));
+    assert!(!lunch.contains(time("13:30")));
+
+    assert!("22:00".parse::<QuietHours>().is_err());
+    assert!("25:00-08:00".parse::<QuietHours>().is_err());
+}
+
+/// Summary and actions of every notification received by the mock
+type SentNotifications = Arc<std::sync::Mutex<Vec<(String, Vec<String>)>>>;
+
+#[derive(Default)]
+struct MockNotifications {
+    sent: SentNotifications,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl MockNotifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        _app_name: String,
+        _replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        _body: String,
+        actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        let mut sent = self.sent.lock().unwrap();
+        sent.push((summary, actions));
+        sent.len() as u32
+    }
+
+    #[zbus(signal)]
+    async fn action_invoked(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        action_key: &str,
+    ) -> zbus::Result<()>;
+}
+
+struct Setup {
+    _bus: PrivateBus,
+    _dir: TempDir,
+    task_service: TaskService,
+    nudge_service: NudgeService,
+    notifications: Connection,
+    sent: SentNotifications,
+}
+
+async fn setup() -> Setup {
+    let bus = PrivateBus::start();
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    let task_service = build_task_service(storage, "laptop");
+
+    let mock = MockNotifications::default();
+    let sent = mock.sent.clone();
+    let notifications = zbus::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .name("org.freedesktop.Notifications")
+        .unwrap()
+        .serve_at(NOTIFICATIONS_PATH, mock)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+
+    let nudge_service = NudgeService::builder()
+        .task_service(task_service.clone())
+        .threshold(Duration::ZERO)
+        .cooldown(Duration::from_secs(3600))
+        .quiet_hours(None)
+        .check_interval(Duration::from_millis(50))
+        .connection(Some(bus.connect().await))
+        .build();
+
+    tokio::spawn({
+        let nudge_service = nudge_service.clone();
+        async move { nudge_service.serve().await }
+    });
+
+    Setup {
+        _bus: bus,
+        _dir: dir,
+        task_service,
+        nudge_service,
+        notifications,
+        sent,
+    }
+}
+
+fn start_input(task_name: &str) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: Some("work".to_string()),
+        tags: vec![],
+        at: None,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires dbus-daemon, run with `cargo test -- --ignored`"]
+async fn test_nudge_action_starts_suggested_task() -> eyre::Result<()> {
+    let setup = setup().await;
+
+    setup
+        .task_service
+        .start_new_task(start_input("report"))
+        .await?;
+    setup.task_service.stop_current_task().await?;
+    setup.nudge_service.record_activity(unix_now())?;
+
+    let mut sent = Vec::new();
+    for _ in 0..100 {
+        sent = setup.sent.lock().unwrap().clone();
+        if !sent.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(sent.len(), 1, "a single nudge should have been sent");
+    assert_eq!(sent[0].1, vec!["start", "Start 'report'"]);
+
+    let emitter = SignalEmitter::new(&setup.notifications, NOTIFICATIONS_PATH)?;
+    MockNotifications::action_invoked(&emitter, 1, START_ACTION).await?;
+
+    for _ in 0..100 {
+        let current = setup.task_service.list_last_tasks(0, 1).await?;
+        if let Some(task) = current.first().filter(|t| t.task.end.is_none()) {
+            assert_eq!(task.task.task_name, "report");
+            assert_eq!(task.task.project.as_deref(), Some("work"));
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("the suggested task wasn't started");
+}
+
+#[tokio::test]
+#[ignore = "requires dbus-daemon, run with `cargo test -- --ignored`"]
+async fn test_no_nudge_while_task_running_or_idle() -> eyre::Result<()> {
+    let setup = setup().await;
+
+    setup
+        .task_service
+        .start_new_task(start_input("report"))
+        .await?;
+    setup.nudge_service.record_activity(unix_now())?;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(setup.sent.lock().unwrap().is_empty());
+
+    setup.task_service.stop_current_task().await?;
+    setup.nudge_service.record_idle()?;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(setup.sent.lock().unwrap().is_empty());
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! Authentication and encryption between peers, with keys derived from the
+//! pairing code and the pre-shared key. The requests only carry a cursor and
+//! are signed, the responses carrying the tasks are encrypted and bound to
+//! the request they answer, so that only the peers of a pairing can read and
+//! feed each other's tasks.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Name of the computer sending the request or the response
+pub const PEER_HEADER: &str = "x-o324-peer";
+/// Time the request was signed at, as a unix timestamp in ms
+pub const TIMESTAMP_HEADER: &str = "x-o324-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-o324-signature";
+
+/// Maximum difference between the clocks of two peers.
+pub const MAX_CLOCK_SKEW_MS: u64 = 5 * 60_000;
+
+const MIN_PSK_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 24;
+
+#[derive(Clone)]
+pub struct PeerKey {
+    /// Key signing the requests
+    mac_key: [u8; 32],
+    /// Key encrypting the responses
+    cipher_key: [u8; 32],
+}
+
+impl PeerKey {
+    pub fn derive(pairing_code: &str, psk: &str) -> eyre::Result<Self> {
+        if pairing_code.trim().is_empty() {
+            eyre::bail!("The pairing code of the p2p synchronization is empty");
+        }
+        if psk.chars().count() < MIN_PSK_LENGTH {
+            eyre::bail!(
+                "The pre-shared key of the p2p synchronization must be at least \
+                {MIN_PSK_LENGTH} characters long, `o324-daemon pair` generates one"
+            );
+        }
+
+        let subkey = |label: &[u8]| -> eyre::Result<[u8; 32]> {
+            let mut mac = new_mac(psk.as_bytes())?;
+            mac.update(label);
+            mac.update(pairing_code.trim().as_bytes());
+            Ok(mac.finalize().into_bytes().into())
+        };
+        Ok(Self {
+            mac_key: subkey(b"o324-p2p-key\n")?,
+            cipher_key: subkey(b"o324-p2p-cipher\n")?,
+        })
+    }
+
+    pub fn sign_request(
+        &self,
+        method: &str,
+        path: &str,
+        peer: &str,
+        timestamp: u64,
+    ) -> eyre::Result<String> {
+        let timestamp = timestamp.to_string();
+        self.sign(&[
+            method.as_bytes(),
+            path.as_bytes(),
+            peer.as_bytes(),
+            timestamp.as_bytes(),
+        ])
+    }
+
+    pub fn verify_request(
+        &self,
+        method: &str,
+        path: &str,
+        peer: &str,
+        timestamp: u64,
+        signature: &str,
+    ) -> eyre::Result<bool> {
+        let timestamp = timestamp.to_string();
+        self.verify(
+            &[
+                method.as_bytes(),
+                path.as_bytes(),
+                peer.as_bytes(),
+                timestamp.as_bytes(),
+            ],
+            signature,
+        )
+    }
+
+    /// Encrypts a response body, bound to the request it answers and to the
+    /// peer answering it. A random nonce is prepended to the ciphertext.
+    pub fn seal_response(
+        &self,
+        request_signature: &str,
+        peer: &str,
+        body: &[u8],
+    ) -> eyre::Result<Vec<u8>> {
+        let nonce = rand::random::<[u8; NONCE_LENGTH]>();
+        let aad = framed(&[request_signature.as_bytes(), peer.as_bytes()]);
+        let ciphertext = XChaCha20Poly1305::new((&self.cipher_key).into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: body,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| eyre::eyre!("Couldn't encrypt the response"))?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// Decrypts a response body, failing when it wasn't sealed by `peer` for
+    /// this request with the same key.
+    pub fn open_response(
+        &self,
+        request_signature: &str,
+        peer: &str,
+        sealed: &[u8],
+    ) -> Option<Vec<u8>> {
+        if sealed.len() < NONCE_LENGTH {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+        let aad = framed(&[request_signature.as_bytes(), peer.as_bytes()]);
+        XChaCha20Poly1305::new((&self.cipher_key).into())
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .ok()
+    }
+
+    fn mac(&self, parts: &[&[u8]]) -> eyre::Result<HmacSha256> {
+        let mut mac = new_mac(&self.mac_key)?;
+        mac.update(&framed(parts));
+        Ok(mac)
+    }
+
+    fn sign(&self, parts: &[&[u8]]) -> eyre::Result<String> {
+        Ok(hex::encode(self.mac(parts)?.finalize().into_bytes()))
+    }
+
+    fn verify(&self, parts: &[&[u8]], signature: &str) -> eyre::Result<bool> {
+        let Ok(signature) = hex::decode(signature) else {
+            return Ok(false);
+        };
+        Ok(self.mac(parts)?.verify_slice(&signature).is_ok())
+    }
+}
+
+/// Identifies the peers of a pairing in mDNS announcements without
+/// revealing the pairing code.
+pub fn group_id(pairing_code: &str) -> String {
+    let digest = Sha256::digest(format!("o324-p2p-group\n{}", pairing_code.trim()));
+    hex::encode(&digest[..8])
+}
+
+/// Concatenates length-prefixed parts, so that their boundaries can't be moved.
+fn framed(parts: &[&[u8]]) -> Vec<u8> {
+    let mut framed = Vec::new();
+    for part in parts {
+        framed.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        framed.extend_from_slice(part);
+    }
+    framed
+}
+
+fn new_mac(key: &[u8]) -> eyre::Result<HmacSha256> {
+    <HmacSha256 as Mac>::new_from_slice(key).map_err(|e| eyre::eyre!("Invalid HMAC key: {e}"))
+}
@@ -0,0 +1,58 @@
+use super::{auth, P2pSyncServiceInner};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+
+/// mDNS service type announced by the daemons.
+const SERVICE_TYPE: &str = "_o324._tcp.local.";
+
+impl P2pSyncServiceInner {
+    /// Announces this daemon on the local network and keeps track of the
+    /// peers of the same pairing.
+    pub(super) async fn discover(&self, port: u16) -> eyre::Result<()> {
+        let mdns = ServiceDaemon::new()?;
+        let group = auth::group_id(&self.config.pairing_code);
+
+        let host_name: String = self
+            .computer_name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            &self.computer_name,
+            &format!("{host_name}.local."),
+            "",
+            port,
+            &[("group", group.as_str())][..],
+        )?
+        .enable_addr_auto();
+        let own_fullname = service.get_fullname().to_string();
+        mdns.register(service)?;
+
+        let receiver = mdns.browse(SERVICE_TYPE)?;
+        while let Ok(event) = receiver.recv_async().await {
+            match event {
+                ServiceEvent::ServiceResolved(info) => {
+                    if info.get_fullname() == own_fullname
+                        || info.get_property_val_str("group") != Some(group.as_str())
+                    {
+                        continue;
+                    }
+                    let Some(address) = info.get_addresses_v4().into_iter().next() else {
+                        continue;
+                    };
+
+                    let address = format!("{address}:{}", info.get_port());
+                    tracing::info!("Discovered peer '{}' at {address}.", info.get_fullname());
+                    self.discovered_peers
+                        .insert(info.get_fullname().to_string(), address);
+                }
+                ServiceEvent::ServiceRemoved(_, fullname) => {
+                    self.discovered_peers.remove(&fullname);
+                }
+                _ => {}
+            }
+        }
+
+        eyre::bail!("mDNS discovery stopped")
+    }
+}
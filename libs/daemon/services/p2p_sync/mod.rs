@@ -0,0 +1,406 @@
+use crate::{
+    config::defs::P2pSyncConfig,
+    core::utils::unix_now,
+    repositories::{
+        sync_cursor::SyncCursorRepository,
+        task::{defs::TaskAction, TaskRepository},
+        task_operation::TaskOperationRepository,
+    },
+    services::task::import::TaskImporter,
+};
+use auth::{PeerKey, MAX_CLOCK_SKEW_MS, PEER_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use dashmap::DashMap;
+use protocol::{HelloResponse, OperationsQuery, OperationsResponse};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::{net::TcpListener, sync::Mutex};
+use wrap_builder::wrap_builder;
+
+pub mod auth;
+mod discovery;
+pub mod protocol;
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum number of operations sent per response.
+const MAX_OPERATIONS_LIMIT: u64 = 500;
+
+/// Time after which an unreachable peer is skipped.
+const PEER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Keeps the task store in sync with the other daemons of the local network.
+/// Every daemon serves the operations it recorded to its peers, and pulls
+/// the operations of the configured and discovered peers after the last
+/// sequence number it received from each of them.
+#[wrap_builder(Arc)]
+pub struct P2pSyncService {
+    config: P2pSyncConfig,
+    key: PeerKey,
+    computer_name: String,
+    task_repository: TaskRepository,
+    task_importer: TaskImporter,
+    task_operation_repository: TaskOperationRepository,
+    sync_cursor_repository: SyncCursorRepository,
+    #[builder(default)]
+    client: reqwest::Client,
+    /// Addresses of the peers found through mDNS, by service name
+    #[builder(default)]
+    discovered_peers: DashMap<String, String>,
+    /// Prevents concurrent synchronizations from importing the same operations
+    #[builder(default)]
+    lock: Mutex<()>,
+}
+
+impl P2pSyncService {
+    /// Serves the peers, discovers them and periodically pulls their changes.
+    pub async fn serve(&self) -> eyre::Result<()> {
+        let listen = self.config.get_listen();
+        let listener = TcpListener::bind(&listen)
+            .await
+            .map_err(|e| eyre::eyre!("Couldn't listen for peers on '{listen}': {e}"))?;
+        let port = listener.local_addr()?.port();
+
+        let discovery = async {
+            match self.config.is_mdns_enabled() {
+                true => self.discover(port).await,
+                false => std::future::pending().await,
+            }
+        };
+
+        tokio::try_join!(
+            self.serve_peers(listener),
+            discovery,
+            self.sync_periodically()
+        )?;
+        Ok(())
+    }
+
+    /// Serves the operations of this daemon to its peers.
+    pub async fn serve_peers(&self, listener: TcpListener) -> eyre::Result<()> {
+        tracing::info!("Listening for peers on {}.", listener.local_addr()?);
+
+        let router = Router::new()
+            .route("/v1/p2p/hello", get(hello))
+            .route("/v1/p2p/operations", get(operations))
+            .with_state(self.clone());
+        axum::serve(listener, router).await?;
+        Ok(())
+    }
+}
+
+impl P2pSyncServiceInner {
+    async fn sync_periodically(&self) -> eyre::Result<()> {
+        let interval = self.config.get_interval();
+        tracing::info!(
+            "P2P synchronization enabled (every {}s).",
+            interval.as_secs()
+        );
+
+        loop {
+            match self.sync().await {
+                Ok(imported) => {
+                    tracing::info!(
+                        "P2P synchronization done, {} change(s) imported.",
+                        imported.len()
+                    )
+                }
+                Err(e) => tracing::warn!("P2P synchronization failed: {e}"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Pulls the changes of every known peer. Unreachable peers are skipped,
+    /// the synchronization only failing when no peer could be reached.
+    pub async fn sync(&self) -> eyre::Result<Vec<TaskAction>> {
+        let _guard = self.lock.lock().await;
+
+        let mut peers = self.config.peers.clone();
+        for entry in self.discovered_peers.iter() {
+            if !peers.contains(entry.value()) {
+                peers.push(entry.value().clone());
+            }
+        }
+
+        let mut imported = Vec::new();
+        let mut errors = Vec::new();
+        for address in &peers {
+            match self.pull_peer(address).await {
+                Ok(actions) => imported.extend(actions),
+                Err(e) => {
+                    tracing::warn!("Couldn't synchronize with peer {address}: {e}");
+                    errors.push(e);
+                }
+            }
+        }
+
+        match errors.pop() {
+            Some(e) if errors.is_empty() && peers.len() == 1 => Err(e),
+            Some(e) if errors.len() + 1 == peers.len() => {
+                eyre::bail!(
+                    "None of the {} peers could be reached, last error: {e}",
+                    peers.len()
+                )
+            }
+            _ => Ok(imported),
+        }
+    }
+
+    /// Imports the operations a peer recorded since the last pull.
+    async fn pull_peer(&self, address: &str) -> eyre::Result<Vec<TaskAction>> {
+        let (peer, hello) = self
+            .request::<HelloResponse>(address, "/v1/p2p/hello", &[])
+            .await?;
+        if peer != hello.computer_name {
+            eyre::bail!(
+                "The peer answered as '{peer}' but introduced itself as '{}'",
+                hello.computer_name
+            );
+        }
+        if peer == self.computer_name {
+            return Ok(Vec::new());
+        }
+
+        let cursor_key = format!("p2p://{peer}");
+        let mut cursor = self.sync_cursor_repository.get(&cursor_key)?;
+        let mut imported = Vec::new();
+        loop {
+            let after = cursor.map(|cursor| cursor.to_string());
+            let query: Vec<(&str, &str)> = after
+                .iter()
+                .map(|after| ("after", after.as_str()))
+                .collect();
+            let (responder, page) = self
+                .request::<OperationsResponse>(address, "/v1/p2p/operations", &query)
+                .await?;
+            if responder != peer {
+                eyre::bail!("The peer at {address} changed from '{peer}' to '{responder}'");
+            }
+
+            imported.extend(self.task_importer.import(page.actions).await?);
+            if let Some(page_cursor) = page.cursor {
+                self.sync_cursor_repository.set(&cursor_key, page_cursor)?;
+                cursor = Some(page_cursor);
+            }
+            if !page.has_more {
+                return Ok(imported);
+            }
+        }
+    }
+
+    /// Sends a signed request to a peer, returning the name of the peer
+    /// along with its decrypted response.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        address: &str,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> eyre::Result<(String, T)> {
+        let mut url = reqwest::Url::parse(&format!("http://{address}{path}"))
+            .map_err(|e| eyre::eyre!("Invalid peer address '{address}': {e}"))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        let path_and_query = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+
+        let timestamp = unix_now();
+        let signature =
+            self.key
+                .sign_request("GET", &path_and_query, &self.computer_name, timestamp)?;
+        let response = self
+            .client
+            .get(url)
+            .timeout(PEER_TIMEOUT)
+            .header(PEER_HEADER, &self.computer_name)
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, &signature)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let peer = header(PEER_HEADER);
+        let body = response.bytes().await?;
+
+        if !status.is_success() {
+            eyre::bail!(
+                "The peer answered {status}: {}",
+                String::from_utf8_lossy(&body)
+            );
+        }
+        let Some(peer) = peer else {
+            eyre::bail!("The peer didn't name itself in its response");
+        };
+        let Some(body) = self.key.open_response(&signature, &peer, &body) else {
+            eyre::bail!("The response of '{peer}' couldn't be decrypted, are both peers using the same pairing code and key?");
+        };
+
+        Ok((peer, serde_json::from_slice(&body)?))
+    }
+
+    /// Lists the operations recorded after `after`, or a snapshot of the
+    /// tasks when the peer has never pulled or this store was reset.
+    async fn list_operations(&self, query: OperationsQuery) -> eyre::Result<OperationsResponse> {
+        let last_seq = self.task_operation_repository.last_seq().await?;
+        let after = match (query.after, last_seq) {
+            (Some(after), Some(last_seq)) if after <= last_seq => after,
+            _ => {
+                let tasks = self.task_repository.list_last_tasks(0, u64::MAX).await?;
+                return Ok(OperationsResponse {
+                    actions: tasks.into_iter().map(TaskAction::Upsert).collect(),
+                    cursor: last_seq,
+                    has_more: false,
+                });
+            }
+        };
+
+        let limit = query
+            .limit
+            .unwrap_or(MAX_OPERATIONS_LIMIT)
+            .clamp(1, MAX_OPERATIONS_LIMIT);
+        let operations = self
+            .task_operation_repository
+            .list_since(after + 1, limit)
+            .await?;
+
+        Ok(OperationsResponse {
+            cursor: operations
+                .last()
+                .map(|operation| operation.seq)
+                .or(Some(after)),
+            has_more: operations.len() as u64 == limit,
+            actions: operations
+                .into_iter()
+                .map(|operation| operation.action)
+                .collect(),
+        })
+    }
+
+    /// Checks the signature of a peer request, returning it to sign the response.
+    fn authenticate(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+    ) -> Result<String, ApiError> {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let unauthorized = |message: &str| ApiError(StatusCode::UNAUTHORIZED, message.to_string());
+
+        let (Some(peer), Some(timestamp), Some(signature)) = (
+            header(PEER_HEADER),
+            header(TIMESTAMP_HEADER).and_then(|timestamp| timestamp.parse::<u64>().ok()),
+            header(SIGNATURE_HEADER),
+        ) else {
+            return Err(unauthorized("Missing signature"));
+        };
+
+        if unix_now().abs_diff(timestamp) > MAX_CLOCK_SKEW_MS {
+            return Err(unauthorized("The clocks of the peers are too far apart"));
+        }
+
+        let path_and_query = uri
+            .path_and_query()
+            .map_or(uri.path(), |path_and_query| path_and_query.as_str());
+        if !self
+            .key
+            .verify_request(method.as_str(), path_and_query, peer, timestamp, signature)?
+        {
+            return Err(unauthorized("Invalid signature"));
+        }
+
+        Ok(signature.to_string())
+    }
+
+    /// Serializes and encrypts a response to an authenticated request.
+    fn respond<T: Serialize>(&self, request_signature: &str, value: eyre::Result<T>) -> Response {
+        let body = value.and_then(|value| {
+            let body = serde_json::to_vec(&value)?;
+            self.key
+                .seal_response(request_signature, &self.computer_name, &body)
+        });
+        let body = match body {
+            Ok(body) => body,
+            Err(e) => return ApiError::from(e).into_response(),
+        };
+
+        let mut response = body.into_response();
+        let headers = response.headers_mut();
+        for (name, value) in [
+            (PEER_HEADER, self.computer_name.as_str()),
+            ("content-type", "application/octet-stream"),
+        ] {
+            match HeaderValue::from_str(value) {
+                Ok(value) => {
+                    headers.insert(name, value);
+                }
+                Err(e) => return ApiError::from(eyre::Report::from(e)).into_response(),
+            }
+        }
+        response
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl From<eyre::Report> for ApiError {
+    fn from(e: eyre::Report) -> Self {
+        tracing::error!("Peer request failed: {e}");
+        Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+async fn hello(
+    State(service): State<P2pSyncService>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    match service.authenticate(&method, &uri, &headers) {
+        Ok(signature) => service.respond(
+            &signature,
+            Ok(HelloResponse {
+                computer_name: service.computer_name.clone(),
+            }),
+        ),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn operations(
+    State(service): State<P2pSyncService>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(query): Query<OperationsQuery>,
+) -> Response {
+    match service.authenticate(&method, &uri, &headers) {
+        Ok(signature) => {
+            let operations = service.list_operations(query).await;
+            service.respond(&signature, operations)
+        }
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,26 @@
+//! Requests and responses exchanged between peers.
+
+use crate::repositories::task::defs::TaskAction;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HelloResponse {
+    pub computer_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperationsQuery {
+    /// Sequence number of the last operation pulled, none to get a snapshot
+    pub after: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperationsResponse {
+    /// Operations recorded by the peer, or a snapshot of its tasks when the
+    /// requested sequence number is unknown to it
+    pub actions: Vec<TaskAction>,
+    /// Sequence number to resume the next pull from
+    pub cursor: Option<u64>,
+    pub has_more: bool,
+}
@@ -0,0 +1,167 @@
+use super::*;
+use crate::{
+    core::testing::SyncDevice, repositories::task::defs::StartTaskInput,
+    services::task::TaskService,
+};
+
+const PAIRING_CODE: &str = "abcd-efgh-jkmn";
+const PSK: &str = "0123456789abcdef0123456789abcdef";
+
+struct Device {
+    _device: SyncDevice,
+    task_service: TaskService,
+    p2p_sync_service: P2pSyncService,
+}
+
+/// Starts a daemon listening on loopback, synchronizing with `peer`.
+fn setup_device(computer_name: &str, psk: &str, listener: TcpListener, peer: &str) -> Device {
+    let device = SyncDevice::new(computer_name);
+
+    let p2p_sync_service = P2pSyncService::builder()
+        .config(P2pSyncConfig {
+            pairing_code: PAIRING_CODE.to_string(),
+            psk: psk.to_string(),
+            listen: None,
+            peers: vec![peer.to_string()],
+            mdns: Some(false),
+            interval_secs: None,
+        })
+        .key(PeerKey::derive(PAIRING_CODE, psk).unwrap())
+        .computer_name(computer_name.to_string())
+        .task_repository(device.task_repository.clone())
+        .task_importer(device.task_importer())
+        .task_operation_repository(device.task_operation_repository())
+        .sync_cursor_repository(device.sync_cursor_repository())
+        .build();
+
+    let server = p2p_sync_service.clone();
+    tokio::spawn(async move { server.serve_peers(listener).await });
+
+    Device {
+        task_service: device.task_service(None),
+        p2p_sync_service,
+        _device: device,
+    }
+}
+
+/// Binds two loopback listeners, returned along with their addresses.
+async fn bind_pair() -> ((TcpListener, String), (TcpListener, String)) {
+    let mut pair = Vec::new();
+    for _ in 0..2 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        pair.push((listener, address));
+    }
+    let second = pair.pop().unwrap();
+    (pair.pop().unwrap(), second)
+}
+
+fn start_input(task_name: &str) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at: None,
+    }
+}
+
+async fn task_names(device: &Device) -> eyre::Result<Vec<String>> {
+    let mut names: Vec<String> = device
+        .task_service
+        .list_last_tasks(0, u64::MAX)
+        .await?
+        .into_iter()
+        .map(|task| task.task.task_name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[tokio::test]
+async fn test_peers_converge_on_loopback() -> eyre::Result<()> {
+    let ((laptop_listener, laptop_address), (desktop_listener, desktop_address)) =
+        bind_pair().await;
+    let laptop = setup_device("laptop", PSK, laptop_listener, &desktop_address);
+    let desktop = setup_device("desktop", PSK, desktop_listener, &laptop_address);
+
+    // Tasks recorded before pairing are sent as a snapshot
+    let task = laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?
+        .task;
+    laptop.task_service.stop_current_task().await?;
+    desktop
+        .task_service
+        .start_new_task(start_input("review"))
+        .await?;
+
+    assert_eq!(laptop.p2p_sync_service.sync().await?.len(), 1);
+    assert_eq!(desktop.p2p_sync_service.sync().await?.len(), 1);
+    assert_eq!(task_names(&laptop).await?, vec!["review", "write report"]);
+    assert_eq!(task_names(&desktop).await?, task_names(&laptop).await?);
+
+    // Later changes are pulled from the operation log
+    desktop.task_service.delete_task(task.id.clone()).await?;
+    assert_eq!(laptop.p2p_sync_service.sync().await?.len(), 1);
+    assert!(laptop.task_service.get_task(task.id).await?.is_none());
+    assert!(laptop.p2p_sync_service.sync().await?.is_empty());
+    assert!(desktop.p2p_sync_service.sync().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_peers_with_another_key_are_rejected() -> eyre::Result<()> {
+    let ((laptop_listener, laptop_address), (intruder_listener, intruder_address)) =
+        bind_pair().await;
+    let laptop = setup_device("laptop", PSK, laptop_listener, &intruder_address);
+    let intruder = setup_device(
+        "intruder",
+        "fedcba9876543210fedcba9876543210",
+        intruder_listener,
+        &laptop_address,
+    );
+
+    laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?;
+
+    assert!(intruder.p2p_sync_service.sync().await.is_err());
+    assert!(task_names(&intruder).await?.is_empty());
+    assert!(laptop.p2p_sync_service.sync().await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_responses_are_encrypted() -> eyre::Result<()> {
+    let ((laptop_listener, laptop_address), (_, desktop_address)) = bind_pair().await;
+    let laptop = setup_device("laptop", PSK, laptop_listener, &desktop_address);
+    laptop
+        .task_service
+        .start_new_task(start_input("write report"))
+        .await?;
+
+    let key = PeerKey::derive(PAIRING_CODE, PSK)?;
+    let timestamp = unix_now();
+    let signature = key.sign_request("GET", "/v1/p2p/operations", "desktop", timestamp)?;
+    let body = reqwest::Client::new()
+        .get(format!("http://{laptop_address}/v1/p2p/operations"))
+        .header(PEER_HEADER, "desktop")
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(SIGNATURE_HEADER, &signature)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let needle = b"write report";
+    assert!(!body.windows(needle.len()).any(|window| window == needle));
+    assert!(key.open_response(&signature, "intruder", &body).is_none());
+    let opened = key
+        .open_response(&signature, "laptop", &body)
+        .expect("the response should be sealed for this request");
+    assert!(opened.windows(needle.len()).any(|window| window == needle));
+    Ok(())
+}
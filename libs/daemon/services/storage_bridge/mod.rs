@@ -1,6 +1,11 @@
 use crate::{
-    core::storage::Storage,
-    entities::{prefix_trie_node::PrefixTrieNode, task::Task},
+    core::{crypto::StorageKeyCheck, storage::Storage},
+    entities::{
+        idle_period::IdlePeriod, prefix_trie_node::PrefixTrieNode, schema_version::SchemaVersion,
+        sync_cursor::SyncCursor, sync_device::SyncDevice, sync_operation::SyncOperation,
+        task::Task, task_conflict::TaskConflict, task_operation::TaskOperation,
+        task_sync_base::TaskSyncBase, task_tombstone::TaskTombstone,
+    },
 };
 use native_db::{transaction::RTransaction, ToInput};
 use native_model::Model;
@@ -43,6 +48,26 @@ impl StorageBridgeServiceInner {
                         scan_and_serialize::<Task>(&txn)
                     } else if tid == &TypeId::of::<PrefixTrieNode>() {
                         scan_and_serialize::<PrefixTrieNode>(&txn)
+                    } else if tid == &TypeId::of::<TaskOperation>() {
+                        scan_and_serialize::<TaskOperation>(&txn)
+                    } else if tid == &TypeId::of::<IdlePeriod>() {
+                        scan_and_serialize::<IdlePeriod>(&txn)
+                    } else if tid == &TypeId::of::<SchemaVersion>() {
+                        scan_and_serialize::<SchemaVersion>(&txn)
+                    } else if tid == &TypeId::of::<SyncOperation>() {
+                        scan_and_serialize::<SyncOperation>(&txn)
+                    } else if tid == &TypeId::of::<SyncDevice>() {
+                        scan_and_serialize::<SyncDevice>(&txn)
+                    } else if tid == &TypeId::of::<SyncCursor>() {
+                        scan_and_serialize::<SyncCursor>(&txn)
+                    } else if tid == &TypeId::of::<TaskConflict>() {
+                        scan_and_serialize::<TaskConflict>(&txn)
+                    } else if tid == &TypeId::of::<StorageKeyCheck>() {
+                        scan_and_serialize::<StorageKeyCheck>(&txn)
+                    } else if tid == &TypeId::of::<TaskTombstone>() {
+                        scan_and_serialize::<TaskTombstone>(&txn)
+                    } else if tid == &TypeId::of::<TaskSyncBase>() {
+                        scan_and_serialize::<TaskSyncBase>(&txn)
                     } else {
                         unreachable!("Couldn't find table");
                     }
@@ -0,0 +1,138 @@
+use crate::repositories::sync_server::SyncServerRepository;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use protocol::{PullQuery, PullResponse, PushRequest, PushResponse};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tokio::net::TcpListener;
+use wrap_builder::wrap_builder;
+
+pub mod protocol;
+
+/// Maximum number of operations scanned by a pull.
+const MAX_PULL_LIMIT: u64 = 1000;
+
+/// Stores the task operations of several devices so that each one can pull
+/// the changes of the others.
+#[wrap_builder(Arc)]
+pub struct SyncServerService {
+    repository: SyncServerRepository,
+    /// Bearer token the devices must send, none to accept any request
+    #[builder(default)]
+    token: Option<String>,
+}
+
+impl SyncServerService {
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/v1/health", get(health))
+            .route("/v1/devices/{computer_name}/push", post(push))
+            .route("/v1/devices/{computer_name}/pull", get(pull))
+            .with_state(self.clone())
+    }
+
+    /// Serves the sync API until the listener fails.
+    pub async fn serve(&self, listener: TcpListener) -> eyre::Result<()> {
+        tracing::info!("Sync server listening on {}.", listener.local_addr()?);
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+impl SyncServerServiceInner {
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), ApiError> {
+        let Some(token) = &self.token else {
+            return Ok(());
+        };
+
+        // Digests are compared in constant time, not leaking the token length
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|value| Sha256::digest(value).ct_eq(&Sha256::digest(token)).into());
+
+        match authorized {
+            true => Ok(()),
+            false => Err(ApiError(StatusCode::UNAUTHORIZED, "Invalid token".into())),
+        }
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl From<eyre::Report> for ApiError {
+    fn from(e: eyre::Report) -> Self {
+        tracing::error!("Sync request failed: {e}");
+        Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, self.1).into_response()
+    }
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+async fn push(
+    State(service): State<SyncServerService>,
+    Path(computer_name): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<PushRequest>,
+) -> Result<Json<PushResponse>, ApiError> {
+    service.authorize(&headers)?;
+
+    if let Some(operation) = request
+        .operations
+        .iter()
+        .find(|operation| operation.computer_name != computer_name)
+    {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Operation {} was recorded by '{}', not '{computer_name}'",
+                operation.seq, operation.computer_name
+            ),
+        ));
+    }
+
+    let last_seq = service
+        .repository
+        .push(&computer_name, request.operations)?;
+    Ok(Json(PushResponse { last_seq }))
+}
+
+async fn pull(
+    State(service): State<SyncServerService>,
+    Path(computer_name): Path<String>,
+    Query(query): Query<PullQuery>,
+    headers: HeaderMap,
+) -> Result<Json<PullResponse>, ApiError> {
+    service.authorize(&headers)?;
+
+    let limit = query
+        .limit
+        .unwrap_or(MAX_PULL_LIMIT)
+        .clamp(1, MAX_PULL_LIMIT);
+    let page = service
+        .repository
+        .pull(&computer_name, query.after, limit)?;
+    let last_seq = service.repository.last_seq(&computer_name)?;
+
+    Ok(Json(PullResponse {
+        operations: page.operations,
+        cursor: page.cursor,
+        has_more: page.has_more,
+        last_seq,
+    }))
+}
@@ -0,0 +1,34 @@
+//! Requests and responses exchanged between the sync server and the daemons.
+
+use crate::entities::{sync_operation::SyncOperation, task_operation::TaskOperation};
+use serde::{Deserialize, Serialize};
+
+/// Operations recorded by a device, in sequence order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushRequest {
+    pub operations: Vec<TaskOperation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushResponse {
+    /// Sequence number of the last operation received from the device
+    pub last_seq: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PullQuery {
+    /// Cursor of the last operation pulled, none to pull from the start
+    pub after: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PullResponse {
+    /// Operations of the other devices
+    pub operations: Vec<SyncOperation>,
+    /// Cursor to resume the next pull from
+    pub cursor: Option<u64>,
+    pub has_more: bool,
+    /// Sequence number of the last operation received from the device
+    pub last_seq: Option<u64>,
+}
@@ -0,0 +1,50 @@
+use crate::repositories::task::defs::TaskChanges;
+
+/// Maximum number of mutations that can be undone.
+const MAX_HISTORY_SIZE: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// Human readable description of the mutation, e.g. "Start task 'foo'"
+    pub description: String,
+    pub changes: TaskChanges,
+}
+
+/// Undo and redo stacks of the task mutations made through the daemon.
+#[derive(Debug, Default)]
+pub struct TaskHistory {
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+}
+
+impl TaskHistory {
+    /// Records a new mutation, this invalidates the redo stack.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if entry.changes.is_empty() {
+            return;
+        }
+
+        self.push_undo(entry);
+        self.redo_stack.clear();
+    }
+
+    pub fn pop_undo(&mut self) -> Option<HistoryEntry> {
+        self.undo_stack.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<HistoryEntry> {
+        self.redo_stack.pop()
+    }
+
+    /// Pushes an entry on the undo stack without touching the redo stack.
+    pub fn push_undo(&mut self, entry: HistoryEntry) {
+        self.undo_stack.push(entry);
+        if self.undo_stack.len() > MAX_HISTORY_SIZE {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    pub fn push_redo(&mut self, entry: HistoryEntry) {
+        self.redo_stack.push(entry);
+    }
+}
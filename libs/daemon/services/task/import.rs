@@ -0,0 +1,41 @@
+use crate::{
+    repositories::{
+        task::{defs::TaskAction, TaskRepository},
+        task_prefix::TaskPrefixRepository,
+    },
+    services::events::EventBus,
+};
+use std::sync::Arc;
+use wrap_builder::wrap_builder;
+
+/// Imports the task actions received by a synchronization into the store.
+#[wrap_builder(Arc)]
+pub struct TaskImporter {
+    task_repository: TaskRepository,
+    task_prefix_repository: TaskPrefixRepository,
+    #[builder(default)]
+    event_bus: EventBus,
+}
+
+impl TaskImporterInner {
+    /// Applies the remote actions, records the conflicting edits and publishes
+    /// the changes. Returns the actions actually applied.
+    pub async fn import(&self, actions: Vec<TaskAction>) -> eyre::Result<Vec<TaskAction>> {
+        let (applied, conflicts) = self.task_repository.import_actions(actions).await?;
+        if !conflicts.is_empty() {
+            tracing::warn!(
+                "Found {} conflicting task edit(s), run `o324 conflicts` to resolve them",
+                conflicts.len()
+            );
+        }
+
+        self.task_prefix_repository
+            .apply_actions(&applied.actions)?;
+
+        let current_task = self.task_repository.get_current_task().await?;
+        self.event_bus
+            .publish_task_actions(&applied.actions, current_task);
+
+        Ok(applied.actions)
+    }
+}
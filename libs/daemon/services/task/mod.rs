@@ -1,23 +1,52 @@
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 use crate::{
-    entities::task::{Task, TaskUpdate},
+    core::utils::unix_now,
+    entities::{
+        task::{Task, TaskUpdate},
+        task_conflict::TaskConflict,
+    },
     repositories::{
         project_color::ProjectColorRepository,
         task::{
-            defs::{StartTaskInput, TaskRef},
+            defs::{
+                AddTaskInput, ConflictResolution, OverlapStrategy, SplitTaskInput, StartTaskInput,
+                TaskAction, TaskChanges, TaskRef,
+            },
             TaskRepository,
         },
         task_prefix::TaskPrefixRepository,
     },
+    services::{
+        events::EventBus, git_sync::GitSyncService, http_sync::HttpSyncService,
+        p2p_sync::P2pSyncService,
+    },
 };
+use history::{HistoryEntry, TaskHistory};
 use wrap_builder::wrap_builder;
 
+pub mod history;
+pub mod import;
+
+#[cfg(test)]
+mod tests;
+
 #[wrap_builder(Arc)]
 pub struct TaskService {
     task_repository: TaskRepository,
     task_prefix_repository: TaskPrefixRepository,
     project_color_repository: ProjectColorRepository,
+    #[builder(default)]
+    git_sync_service: Option<GitSyncService>,
+    #[builder(default)]
+    http_sync_service: Option<HttpSyncService>,
+    #[builder(default)]
+    p2p_sync_service: Option<P2pSyncService>,
+    #[builder(default)]
+    event_bus: EventBus,
+    #[builder(default)]
+    history: Mutex<TaskHistory>,
 }
 
 pub struct TaskWithMeta {
@@ -28,7 +57,8 @@ pub struct TaskWithMeta {
 
 #[allow(dead_code)]
 impl TaskServiceInner {
-    async fn task_with_meta(&self, task: Task) -> eyre::Result<TaskWithMeta> {
+    /// Resolves the shortest unique prefix and project color of a task.
+    pub async fn task_with_meta(&self, task: Task) -> eyre::Result<TaskWithMeta> {
         let prefix = self
             .task_prefix_repository
             .find_shortest_unique_prefix(&task.id)?;
@@ -45,6 +75,50 @@ impl TaskServiceInner {
         })
     }
 
+    /// Forwards the actions of a mutation, already appended to the operation
+    /// log by the repository, to the synchronization layer and publishes them
+    /// on the event bus. Synchronization failures are only logged since the
+    /// mutation is already persisted.
+    async fn record_actions(&self, actions: &[TaskAction]) -> eyre::Result<()> {
+        if let Some(git_sync_service) = &self.git_sync_service {
+            if let Err(e) = git_sync_service.record(actions) {
+                tracing::warn!("Failed to record task changes for git synchronization: {e}");
+            }
+        }
+
+        let current_task = self.task_repository.get_current_task().await?;
+        self.event_bus.publish_task_actions(actions, current_task);
+        Ok(())
+    }
+
+    /// Records the changes of a user mutation so that it can be undone.
+    async fn record_changes(&self, description: String, changes: TaskChanges) -> eyre::Result<()> {
+        self.record_actions(&changes.actions).await?;
+        self.history.lock().await.push(HistoryEntry {
+            description,
+            changes,
+        });
+        Ok(())
+    }
+
+    /// Applies actions reverting or replaying a history entry, provided the
+    /// tasks are still in the state left by `expected`.
+    async fn replay_actions(
+        &self,
+        expected: &[TaskAction],
+        actions: Vec<TaskAction>,
+    ) -> eyre::Result<TaskChanges> {
+        let changes = self
+            .task_repository
+            .replay_actions(expected, actions)
+            .await?;
+        self.record_actions(&changes.actions).await?;
+        self.task_prefix_repository
+            .apply_actions(&changes.actions)?;
+
+        Ok(changes)
+    }
+
     async fn tasks_with_meta(&self, tasks: Vec<Task>) -> eyre::Result<Vec<TaskWithMeta>> {
         let colors = self
             .project_color_repository
@@ -77,7 +151,9 @@ impl TaskServiceInner {
     }
 
     pub async fn start_new_task(&self, input: StartTaskInput) -> eyre::Result<TaskWithMeta> {
-        let (task, _) = self.task_repository.start_new_task(input).await?;
+        let (task, changes) = self.task_repository.start_new_task(input).await?;
+        self.record_changes(format!("Start task '{}'", task.task_name), changes)
+            .await?;
 
         self.task_prefix_repository
             .add_ids(std::slice::from_ref(&task.id))?;
@@ -85,8 +161,91 @@ impl TaskServiceInner {
         self.task_with_meta(task).await
     }
 
+    /// Logs a completed task after the fact.
+    pub async fn add_task(
+        &self,
+        input: AddTaskInput,
+        overlap: OverlapStrategy,
+    ) -> eyre::Result<TaskWithMeta> {
+        let (task, changes) = self.task_repository.add_task(input, overlap).await?;
+        self.task_prefix_repository
+            .apply_actions(&changes.actions)?;
+        self.record_changes(format!("Add task '{}'", task.task_name), changes)
+            .await?;
+
+        self.task_with_meta(task).await
+    }
+
+    /// Splits a task in two at `input.at`, returning the task created from there.
+    pub async fn split_task(
+        &self,
+        task_id: String,
+        input: SplitTaskInput,
+    ) -> eyre::Result<TaskWithMeta> {
+        let (first, second, changes) = self.task_repository.split_task(task_id, input).await?;
+        self.task_prefix_repository
+            .apply_actions(&changes.actions)?;
+        self.record_changes(format!("Split task '{}'", first.task_name), changes)
+            .await?;
+
+        self.task_with_meta(second).await
+    }
+
+    /// Merges tasks into the first one, the other ones being deleted.
+    pub async fn merge_tasks(&self, task_ids: Vec<String>) -> eyre::Result<TaskWithMeta> {
+        let (task, changes) = self.task_repository.merge_tasks(task_ids).await?;
+        self.task_prefix_repository
+            .apply_actions(&changes.actions)?;
+        self.record_changes(format!("Merge tasks into '{}'", task.task_name), changes)
+            .await?;
+
+        self.task_with_meta(task).await
+    }
+
+    /// Applies actions repairing inconsistent tasks, recorded as an undoable mutation.
+    pub async fn repair(&self, description: String, actions: Vec<TaskAction>) -> eyre::Result<()> {
+        let changes = self.task_repository.apply_actions(actions).await?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        self.task_prefix_repository
+            .apply_actions(&changes.actions)?;
+        self.record_changes(description, changes).await
+    }
+
     pub async fn stop_current_task(&self) -> eyre::Result<Option<TaskWithMeta>> {
-        let (task, _) = self.task_repository.stop_current_task().await?;
+        self.stop_current_task_at(unix_now()).await
+    }
+
+    /// Stops the running task at a time given by the user, which must be
+    /// between the task start and now.
+    pub async fn stop_current_task_backdated(
+        &self,
+        end: u64,
+    ) -> eyre::Result<Option<TaskWithMeta>> {
+        let (task, changes) = self
+            .task_repository
+            .stop_current_task_backdated(end)
+            .await?;
+        self.record_stop(task, changes).await
+    }
+
+    /// Stops the running task with an end time in the past, e.g. when the computer went to sleep.
+    pub async fn stop_current_task_at(&self, end: u64) -> eyre::Result<Option<TaskWithMeta>> {
+        let (task, changes) = self.task_repository.stop_current_task_at(end).await?;
+        self.record_stop(task, changes).await
+    }
+
+    async fn record_stop(
+        &self,
+        task: Option<Task>,
+        changes: TaskChanges,
+    ) -> eyre::Result<Option<TaskWithMeta>> {
+        if let Some(task) = &task {
+            self.record_changes(format!("Stop task '{}'", task.task_name), changes)
+                .await?;
+        }
 
         let task = if let Some(task_inner) = task {
             Some(self.task_with_meta(task_inner).await?)
@@ -97,28 +256,66 @@ impl TaskServiceInner {
         Ok(task)
     }
 
-    pub async fn cancel_current_task(&self) -> eyre::Result<Option<TaskWithMeta>> {
-        let (task, _) = self.task_repository.cancel_current_task().await?;
+    /// Removes a pause from the running task, returning the task resumed after the pause.
+    pub async fn pause_current_task(
+        &self,
+        pause_start: u64,
+        pause_end: u64,
+    ) -> eyre::Result<Option<TaskWithMeta>> {
+        let (task, changes) = self
+            .task_repository
+            .pause_current_task(pause_start, pause_end)
+            .await?;
 
-        let task = if let Some(task_inner) = task {
-            Some(self.task_with_meta(task_inner).await?)
-        } else {
-            None
+        let Some(task) = task else {
+            return Ok(None);
         };
 
-        Ok(task)
+        self.record_changes(format!("Pause task '{}'", task.task_name), changes)
+            .await?;
+        self.task_prefix_repository
+            .add_ids(std::slice::from_ref(&task.id))?;
+
+        Ok(Some(self.task_with_meta(task).await?))
     }
 
-    pub async fn delete_task(&self, task_id: String) -> eyre::Result<Option<TaskWithMeta>> {
-        let (task, _) = self.task_repository.delete_task(task_id).await?;
+    pub async fn cancel_current_task(&self) -> eyre::Result<Option<TaskWithMeta>> {
+        let (task, changes) = self.task_repository.cancel_current_task().await?;
+        let Some(task) = task else {
+            return Ok(None);
+        };
 
-        let task = if let Some(task_inner) = task {
-            Some(self.task_with_meta(task_inner).await?)
-        } else {
-            None
+        self.record_changes(format!("Cancel task '{}'", task.task_name), changes)
+            .await?;
+        // The prefix is computed while the id is still indexed
+        let task_id = task.id.clone();
+        let task = self.task_with_meta(task).await?;
+        self.task_prefix_repository.remove_ids(&[task_id])?;
+
+        Ok(Some(task))
+    }
+
+    pub async fn delete_task(&self, task_id: String) -> eyre::Result<Option<TaskWithMeta>> {
+        let (task, changes) = self.task_repository.delete_task(task_id).await?;
+        let Some(task) = task else {
+            return Ok(None);
         };
 
-        Ok(task)
+        self.record_changes(format!("Delete task '{}'", task.task_name), changes)
+            .await?;
+        // The prefix is computed while the id is still indexed
+        let task_id = task.id.clone();
+        let task = self.task_with_meta(task).await?;
+        self.task_prefix_repository.remove_ids(&[task_id])?;
+
+        Ok(Some(task))
+    }
+
+    pub async fn get_current_task(&self) -> eyre::Result<Option<TaskWithMeta>> {
+        match self.task_repository.get_current_task().await? {
+            Some(task) => Ok(Some(self.task_with_meta(task).await?)),
+            None => Ok(None),
+        }
     }
 
     pub async fn get_task(&self, task_ref: String) -> eyre::Result<Option<TaskWithMeta>> {
@@ -144,7 +341,9 @@ impl TaskServiceInner {
         task_ref: TaskRef,
         update: TaskUpdate,
     ) -> eyre::Result<TaskWithMeta> {
-        let (task, _) = self.task_repository.edit_task(task_ref, update).await?;
+        let (task, changes) = self.task_repository.edit_task(task_ref, update).await?;
+        self.record_changes(format!("Edit task '{}'", task.task_name), changes)
+            .await?;
 
         self.task_with_meta(task).await
     }
@@ -170,4 +369,96 @@ impl TaskServiceInner {
 
         self.tasks_with_meta(tasks).await
     }
+
+    /// Fields of tasks edited at the same time on another device, oldest first.
+    pub async fn list_conflicts(&self) -> eyre::Result<Vec<TaskConflict>> {
+        self.task_repository.list_conflicts().await
+    }
+
+    /// Settles a conflict with the local or remote values, the choice being
+    /// synchronized like any other edit.
+    pub async fn resolve_conflict(
+        &self,
+        conflict_id: String,
+        resolution: ConflictResolution,
+    ) -> eyre::Result<TaskWithMeta> {
+        let (task, changes) = self
+            .task_repository
+            .resolve_conflict(conflict_id, resolution)
+            .await?;
+        self.record_changes(
+            format!("Resolve the conflict on '{}'", task.task_name),
+            changes,
+        )
+        .await?;
+
+        self.task_with_meta(task).await
+    }
+
+    /// Synchronizes the task store with the configured remote.
+    pub async fn sync(&self) -> eyre::Result<Vec<TaskAction>> {
+        if let Some(git_sync_service) = &self.git_sync_service {
+            return git_sync_service.sync().await;
+        }
+        if let Some(http_sync_service) = &self.http_sync_service {
+            return http_sync_service.sync().await;
+        }
+        if let Some(p2p_sync_service) = &self.p2p_sync_service {
+            return p2p_sync_service.sync().await;
+        }
+
+        eyre::bail!("No synchronization is configured for this profile")
+    }
+
+    /// Reverts the last mutation, returning the entry along with the applied changes.
+    pub async fn undo(&self) -> eyre::Result<Option<HistoryEntry>> {
+        let mut history = self.history.lock().await;
+        let Some(entry) = history.pop_undo() else {
+            return Ok(None);
+        };
+
+        match self
+            .replay_actions(&entry.changes.actions, entry.changes.revert_actions.clone())
+            .await
+        {
+            Ok(changes) => {
+                let description = entry.description.clone();
+                history.push_redo(entry);
+                Ok(Some(HistoryEntry {
+                    description,
+                    changes,
+                }))
+            }
+            Err(e) => {
+                history.push_undo(entry);
+                Err(e)
+            }
+        }
+    }
+
+    /// Replays the last undone mutation, returning the entry along with the applied changes.
+    pub async fn redo(&self) -> eyre::Result<Option<HistoryEntry>> {
+        let mut history = self.history.lock().await;
+        let Some(entry) = history.pop_redo() else {
+            return Ok(None);
+        };
+
+        match self
+            .replay_actions(&entry.changes.revert_actions, entry.changes.actions.clone())
+            .await
+        {
+            Ok(changes) => {
+                let description = entry.description.clone();
+                history.push_undo(entry);
+                Ok(Some(HistoryEntry {
+                    description,
+                    changes,
+                }))
+            }
+            Err(e) => {
+                history.push_redo(entry);
+                Err(e)
+            }
+        }
+    }
 }
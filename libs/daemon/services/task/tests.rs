@@ -0,0 +1,155 @@
+use super::*;
+use crate::{
+    core::{storage::Storage, testing::build_task_service},
+    entities::MODELS,
+    services::events::DaemonEvent,
+};
+use tempfile::{tempdir, TempDir};
+
+fn setup_service() -> (TempDir, TaskService) {
+    let dir = tempdir().unwrap();
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS).unwrap();
+    (dir, build_task_service(storage, "laptop"))
+}
+
+fn start_input(task_name: &str) -> StartTaskInput {
+    StartTaskInput {
+        task_name: task_name.to_string(),
+        project: None,
+        tags: vec![],
+        at: None,
+    }
+}
+
+#[tokio::test]
+async fn test_undo_start_restores_previous_task() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+
+    let first = service.start_new_task(start_input("first")).await?.task;
+    let second = service.start_new_task(start_input("second")).await?.task;
+
+    let entry = service.undo().await?.expect("start should be undoable");
+    assert_eq!(entry.description, "Start task 'second'");
+
+    assert!(service.get_task(second.id.clone()).await?.is_none());
+    let restored = service.get_task(first.id.clone()).await?.unwrap();
+    assert!(restored.task.end.is_none(), "first task should run again");
+
+    // Redo starts the second task again, stopping the first one
+    service.redo().await?.expect("undone start should be redoable");
+    assert!(service.get_task(first.id).await?.unwrap().task.end.is_some());
+    assert!(service.get_task(second.id).await?.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undo_delete_and_new_mutation_clears_redo() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+
+    let task = service.start_new_task(start_input("report")).await?.task;
+    service.stop_current_task().await?;
+    service.delete_task(task.id.clone()).await?;
+    assert!(service.get_task(task.id.clone()).await?.is_none());
+
+    service.undo().await?.expect("delete should be undoable");
+    let restored = service.get_task(task.id.clone()).await?.unwrap();
+    assert_eq!(restored.task.task_name, "report");
+
+    service.start_new_task(start_input("other")).await?;
+    assert!(service.redo().await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nothing_to_undo() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+    assert!(service.undo().await?.is_none());
+    assert!(service.redo().await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mutations_publish_events() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+    let mut events = service.event_bus.subscribe();
+
+    let task = service.start_new_task(start_input("report")).await?.task;
+    assert!(matches!(events.try_recv(), Ok(DaemonEvent::TaskChanged(t)) if t.id == task.id));
+    assert!(matches!(
+        events.try_recv(),
+        Ok(DaemonEvent::CurrentTaskChanged(Some(t))) if t.id == task.id
+    ));
+
+    service.stop_current_task().await?;
+    assert!(matches!(events.try_recv(), Ok(DaemonEvent::TaskChanged(t)) if t.end.is_some()));
+    assert!(matches!(
+        events.try_recv(),
+        Ok(DaemonEvent::CurrentTaskChanged(None))
+    ));
+
+    service.delete_task(task.id.clone()).await?;
+    assert!(matches!(events.try_recv(), Ok(DaemonEvent::TaskDeleted(id)) if id == task.id));
+    assert!(events.try_recv().is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stop_backdated_validates_end() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+    let task = service.start_new_task(start_input("report")).await?.task;
+
+    assert!(service
+        .stop_current_task_backdated(task.start - 1_000)
+        .await
+        .is_err());
+    assert!(service
+        .stop_current_task_backdated(unix_now() + 60_000)
+        .await
+        .is_err());
+
+    let stopped = service
+        .stop_current_task_backdated(task.start + 1)
+        .await?
+        .expect("the task should be stopped");
+    assert_eq!(stopped.task.end, Some(task.start + 1));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_removes_merged_ids_from_prefixes() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+    let first = service.start_new_task(start_input("coding")).await?;
+    let second = service.start_new_task(start_input("coding")).await?;
+
+    let merged = service
+        .merge_tasks(vec![first.task.id.clone(), second.task.id.clone()])
+        .await?;
+
+    assert_eq!(merged.task.id, first.task.id);
+    assert!(service.task_prefix_repository.contains(&first.task.id)?);
+    assert!(!service.task_prefix_repository.contains(&second.task.id)?);
+
+    // Undoing the merge indexes the merged task again
+    service.undo().await?;
+    assert!(service.task_prefix_repository.contains(&second.task.id)?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undo_refuses_task_modified_since() -> eyre::Result<()> {
+    let (_dir, service) = setup_service();
+    let task = service.start_new_task(start_input("report")).await?.task;
+
+    // An edit made outside of the history, e.g. by a synchronization
+    let mut synced = task.clone();
+    synced.task_name = "synced report".to_string();
+    service
+        .task_repository
+        .apply_actions(vec![TaskAction::Upsert(synced)])
+        .await?;
+
+    assert!(service.undo().await.is_err());
+    let current = service.get_task(task.id).await?.unwrap();
+    assert_eq!(current.task.task_name, "synced report");
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::{
+    config,
+    core::{
+        crypto::{read_key_check, StorageKey},
+        migration::Migrator,
+        storage::Storage,
+    },
+    entities::{copy_records, seal_records, MIGRATIONS},
+};
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+use wrap_builder::wrap_builder;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    Unlocked,
+    /// The storage is encrypted and waits for its passphrase
+    Locked,
+    /// The profile asks for encryption but no passphrase was chosen yet
+    Uninitialized,
+}
+
+/// Holds the storage of an encrypted profile locked until the passphrase is
+/// given, the other services refusing requests meanwhile.
+#[wrap_builder(Arc)]
+pub struct VaultService {
+    storage: Storage,
+    /// Whether the records are encrypted, a plain storage is always unlocked
+    encrypted: bool,
+    #[builder(default = watch::channel(false).0)]
+    unlocked: watch::Sender<bool>,
+    /// Keeps concurrent unlocks from deriving different keys
+    #[builder(default)]
+    unlocking: Mutex<()>,
+}
+
+impl VaultServiceInner {
+    pub fn state(&self) -> eyre::Result<LockState> {
+        if !self.is_locked() {
+            return Ok(LockState::Unlocked);
+        }
+        match read_key_check(&self.storage)? {
+            Some(_) => Ok(LockState::Locked),
+            None => Ok(LockState::Uninitialized),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.encrypted && !*self.unlocked.borrow()
+    }
+
+    pub async fn wait_unlocked(&self) {
+        if !self.encrypted {
+            return;
+        }
+        let mut unlocked = self.unlocked.subscribe();
+        // The sender lives as long as the service
+        let _ = unlocked.wait_for(|unlocked| *unlocked).await;
+    }
+
+    /// Unlocks the storage with its passphrase, or encrypts it with a new
+    /// one the first time, then applies the migrations left pending.
+    pub async fn unlock(&self, passphrase: String) -> eyre::Result<()> {
+        if !self.encrypted {
+            eyre::bail!("The storage of this profile isn't encrypted");
+        }
+        let _unlocking = self.unlocking.lock().await;
+        if !self.is_locked() {
+            return Ok(());
+        }
+
+        let storage = self.storage.clone();
+        tokio::task::spawn_blocking(move || unlock_storage(&storage, &passphrase)).await??;
+        self.unlocked.send_replace(true);
+        tracing::info!("Storage unlocked.");
+        Ok(())
+    }
+}
+
+fn unlock_storage(storage: &Storage, passphrase: &str) -> eyre::Result<()> {
+    match read_key_check(storage)? {
+        Some(check) => {
+            storage.install_key(StorageKey::verify(passphrase, &check)?)?;
+            config::storage::upgrade_storage(storage)
+        }
+        None => {
+            let (key, check) = StorageKey::create(passphrase)?;
+            storage.install_key(key)?;
+            // The records are sealed along with the migrations, so that a
+            // failure leaves them as they were
+            let migrator = Migrator::new(storage, &MIGRATIONS);
+            let report = migrator.upgrade_with(|txn| {
+                seal_records(txn)?;
+                txn.insert(check)?;
+                Ok(())
+            });
+            if let Err(e) = report {
+                storage.forget_key()?;
+                return Err(e);
+            }
+
+            // The backups, including the one of this upgrade, and the pages
+            // freed by the sealing hold the records in plain text
+            for backup in migrator.backups()? {
+                std::fs::remove_file(backup)?;
+            }
+            storage.rewrite(copy_records)?;
+            tracing::info!("Storage encrypted with the new passphrase.");
+            Ok(())
+        }
+    }
+}
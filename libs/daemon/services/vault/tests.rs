@@ -0,0 +1,113 @@
+use super::*;
+use crate::{
+    core::testing::build_task_service,
+    entities::MODELS,
+    repositories::{idle_period::IdlePeriodRepository, task::defs::StartTaskInput},
+    services::task::TaskService,
+};
+use tempfile::tempdir;
+
+fn vault(storage: &Storage) -> VaultService {
+    VaultService::builder()
+        .storage(storage.clone())
+        .encrypted(true)
+        .build()
+}
+
+async fn start_task(task_service: &TaskService, task_name: &str) -> eyre::Result<()> {
+    task_service
+        .start_new_task(StartTaskInput {
+            task_name: task_name.to_string(),
+            project: Some("acme".to_string()),
+            tags: vec![],
+            at: None,
+        })
+        .await?;
+    Ok(())
+}
+
+fn contains(path: &std::path::Path, needle: &[u8]) -> eyre::Result<bool> {
+    let content = std::fs::read(path)?;
+    Ok(content.windows(needle.len()).any(|window| window == needle))
+}
+
+#[tokio::test]
+async fn test_unlock_encrypts_then_verifies_passphrase() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.db");
+    let storage = Storage::try_new(&path, &MODELS)?;
+    let task_service = build_task_service(storage.clone(), "laptop");
+    start_task(&task_service, "review").await?;
+    IdlePeriodRepository::builder()
+        .storage(storage.clone())
+        .computer_name("laptop".to_string())
+        .build()
+        .start(1_000)?;
+    assert!(contains(&path, b"review")?);
+    assert!(contains(&path, b"acme")?);
+    // Left by an earlier migration
+    std::fs::copy(&path, dir.path().join("test.db.v4.1000.bak"))?;
+
+    let first_vault = vault(&storage);
+    assert_eq!(first_vault.state()?, LockState::Uninitialized);
+    assert!(first_vault.unlock("short".to_string()).await.is_err());
+    assert!(first_vault.is_locked());
+
+    first_vault.unlock("correct horse".to_string()).await?;
+    assert_eq!(first_vault.state()?, LockState::Unlocked);
+    first_vault.wait_unlocked().await;
+
+    // Neither the database file nor a backup keep the plain records
+    for needle in [&b"review"[..], b"acme", b"laptop"] {
+        assert!(!contains(&path, needle)?);
+    }
+    assert_eq!(std::fs::read_dir(dir.path())?.count(), 1);
+
+    // As seen by the next run of the daemon
+    let vault = vault(&storage);
+    assert_eq!(vault.state()?, LockState::Locked);
+    assert!(vault.unlock("wrong horse".to_string()).await.is_err());
+    assert!(vault.is_locked());
+    vault.unlock("correct horse".to_string()).await?;
+    assert!(!vault.is_locked());
+
+    let current = task_service.get_current_task().await?.unwrap().task;
+    assert_eq!(current.task_name, "review");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_locked_storage_refuses_sealed_records() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let storage = Storage::try_new(dir.path().join("test.db"), &MODELS)?;
+    let task_service = build_task_service(storage.clone(), "laptop");
+    start_task(&task_service, "review").await?;
+
+    storage.lock()?;
+    let error = start_task(&task_service, "write").await.unwrap_err();
+    assert!(format!("{error:#}").contains("The storage is locked"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storages_keep_their_own_key() -> eyre::Result<()> {
+    let dir = tempdir()?;
+    let laptop = Storage::try_new(dir.path().join("laptop.db"), &MODELS)?;
+    let desktop = Storage::try_new(dir.path().join("desktop.db"), &MODELS)?;
+
+    vault(&laptop).unlock("correct horse".to_string()).await?;
+    vault(&desktop).unlock("battery staple".to_string()).await?;
+
+    for storage in [laptop, desktop] {
+        let task_service = build_task_service(storage, "laptop");
+        start_task(&task_service, "review").await?;
+        let current = task_service.get_current_task().await?.unwrap().task;
+        assert_eq!(current.task_name, "review");
+
+        // The sealed records can be updated and removed
+        task_service.stop_current_task().await?;
+        task_service.delete_task(current.id.clone()).await?;
+        assert!(task_service.get_task(current.id).await?.is_none());
+    }
+    Ok(())
+}
@@ -14,6 +14,8 @@ pub struct TaskDto {
     pub computer_name: String,
     pub start: u64,
     pub end: Option<u64>,
+    pub notes: Option<String>,
+    pub project_color_hue: Option<u32>,
     pub __hash: u64,
 }
 
@@ -24,6 +26,7 @@ pub struct TaskUpdateDto {
     pub tags: Optional<Vec<String>>,
     pub start: Optional<u64>,
     pub end: Optional<Option<u64>>,
+    pub notes: Optional<Option<String>>,
 }
 
 #[derive(Type, Serialize, Deserialize, Debug)]
@@ -31,6 +34,35 @@ pub struct StartTaskInputDto {
     pub task_name: String,
     pub project: Option<String>,
     pub tags: Vec<String>,
+    /// Start of the task in the past, now when `None`
+    pub at: Option<u64>,
+}
+
+#[derive(Type, Serialize, Deserialize, Debug)]
+pub struct AddTaskInputDto {
+    pub task_name: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// The task taking over a split task from `at`.
+#[derive(Type, Serialize, Deserialize, Debug)]
+pub struct SplitTaskInputDto {
+    pub at: u64,
+    pub task_name: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// What to do with existing tasks overlapping an added task.
+#[derive(Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapStrategyDto {
+    /// Fail without changing anything
+    Reject,
+    /// Shorten, split or remove the overlapping tasks
+    Trim,
 }
 
 #[derive(Type, Debug, Deserialize, Serialize)]
@@ -48,6 +80,7 @@ pub struct TaskActionUpsertDto {
     pub computer_name: String,
     pub start: u64,
     pub end: Option<u64>,
+    pub notes: Option<String>,
     pub __hash: u64,
 }
 
@@ -58,6 +91,15 @@ pub enum TaskActionDto {
     Delete(TaskId),
 }
 
+/// A mutation reverted by `undo` or replayed by `redo`.
+#[derive(Type, Serialize, Deserialize, Debug)]
+pub struct HistoryEntryDto {
+    /// Description of the original mutation, e.g. "Start task 'foo'"
+    pub description: String,
+    /// Actions applied to revert or replay the mutation
+    pub actions: Vec<TaskActionDtoPacked>,
+}
+
 /// Defines the *type* of database operation to perform. This is a simple enum.
 #[derive(Type, Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
 pub enum DbOperationTypeDto {
@@ -100,3 +142,78 @@ pub struct ActivityDto {
     pub at: u64,
     pub computer_name: String,
 }
+
+/// A task that kept running while the user was away.
+#[derive(Type, Serialize, Deserialize, Debug, Clone)]
+pub struct AfkPeriodDto {
+    pub task_id: TaskId,
+    pub task_name: String,
+    pub idle_start: u64,
+    pub idle_end: u64,
+}
+
+/// What to do with the idle time of an AFK period.
+#[derive(Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AfkResolutionDto {
+    /// Count the idle time in the task
+    Keep,
+    /// Stop the task when the user went idle
+    Discard,
+    /// Stop the task when the user went idle and resume it on return
+    Split,
+}
+
+/// Fields of a task edited at the same time on this device and another one.
+#[derive(Type, Serialize, Deserialize, Debug, Clone)]
+pub struct TaskConflictDto {
+    pub id: String,
+    /// Names of the conflicting fields, e.g. `task_name`
+    pub fields: Vec<String>,
+    /// The task as stored on this device
+    pub local: TaskActionUpsertDto,
+    /// The task as received from the other device
+    pub remote: TaskActionUpsertDto,
+    pub detected_at: u64,
+}
+
+/// Version of the conflicting fields to keep.
+#[derive(Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolutionDto {
+    Local,
+    Remote,
+}
+
+/// Whether the storage can be read, see `unlock`.
+#[derive(Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockStateDto {
+    Unlocked,
+    /// The storage is encrypted and waits for its passphrase
+    Locked,
+    /// The storage is to be encrypted with the first passphrase given
+    Uninitialized,
+}
+
+#[derive(Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityIssueKindDto {
+    /// Two tasks of the same computer share some time
+    Overlap,
+    /// A few seconds are left untracked between two tasks of the same computer
+    Gap,
+    /// A task ends before it starts
+    InvertedRange,
+    /// A task lasts longer than the requested threshold
+    LongTask,
+    /// An activity was recorded after the current time
+    FutureActivity,
+    /// The prefix index references a task that doesn't exist
+    OrphanPrefix,
+}
+
+#[derive(Type, Serialize, Deserialize, Debug, Clone)]
+pub struct IntegrityIssueDto {
+    pub id: String,
+    pub kind: IntegrityIssueKindDto,
+    pub description: String,
+    /// Description of the fix, `None` when the issue can't be fixed automatically
+    pub fix: Option<String>,
+}
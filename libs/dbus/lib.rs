@@ -3,7 +3,7 @@ pub mod dto;
 pub mod proxy;
 
 pub use zbus;
-use zbus::fdo;
+use zbus::{fdo, object_server::SignalEmitter};
 pub use zvariant;
 
 pub trait O324ServiceInterface {
@@ -11,8 +11,23 @@ pub trait O324ServiceInterface {
         &self,
         input: dto::StartTaskInputDto,
     ) -> impl std::future::Future<Output = fdo::Result<dto::TaskDto>>;
+    fn add_task(
+        &self,
+        input: dto::AddTaskInputDto,
+        overlap: dto::OverlapStrategyDto,
+    ) -> impl std::future::Future<Output = fdo::Result<dto::TaskDto>>;
+    fn split_task(
+        &self,
+        task_id: String,
+        input: dto::SplitTaskInputDto,
+    ) -> impl std::future::Future<Output = fdo::Result<dto::TaskDto>>;
+    fn merge_tasks(
+        &self,
+        task_ids: Vec<String>,
+    ) -> impl std::future::Future<Output = fdo::Result<dto::TaskDto>>;
     fn stop_current_task(
         &self,
+        at: Option<u64>,
     ) -> impl std::future::Future<Output = fdo::Result<Option<dto::TaskDto>>>;
     fn cancel_current_task(
         &self,
@@ -48,8 +63,69 @@ pub trait O324ServiceInterface {
         end_timestamp: u64,
     ) -> impl std::future::Future<Output = fdo::Result<Vec<dto::ActivityDto>>>;
 
+    fn get_pending_afk(
+        &self,
+    ) -> impl std::future::Future<Output = fdo::Result<Option<dto::AfkPeriodDto>>>;
+    fn resolve_afk(
+        &self,
+        resolution: dto::AfkResolutionDto,
+    ) -> impl std::future::Future<Output = fdo::Result<Option<dto::AfkPeriodDto>>>;
+
+    fn check_integrity(
+        &self,
+        max_task_duration_secs: u64,
+    ) -> impl std::future::Future<Output = fdo::Result<Vec<dto::IntegrityIssueDto>>>;
+    fn fix_integrity_issues(
+        &self,
+        issue_ids: Vec<String>,
+    ) -> impl std::future::Future<Output = fdo::Result<Vec<dto::IntegrityIssueDto>>>;
+
+    fn list_conflicts(
+        &self,
+    ) -> impl std::future::Future<Output = fdo::Result<Vec<dto::TaskConflictDto>>>;
+    fn resolve_conflict(
+        &self,
+        conflict_id: String,
+        resolution: dto::ConflictResolutionDto,
+    ) -> impl std::future::Future<Output = fdo::Result<dto::TaskDto>>;
+
+    fn undo(&self) -> impl std::future::Future<Output = fdo::Result<Option<dto::HistoryEntryDto>>>;
+    fn redo(&self) -> impl std::future::Future<Output = fdo::Result<Option<dto::HistoryEntryDto>>>;
+
+    fn sync(&self) -> impl std::future::Future<Output = fdo::Result<u64>>;
+
+    /// Unlocks the encrypted storage, the other methods failing until then
+    fn unlock(&self, passphrase: String) -> impl std::future::Future<Output = fdo::Result<()>>;
+    fn get_lock_state(&self) -> impl std::future::Future<Output = fdo::Result<dto::LockStateDto>>;
+
     fn db_query(
         &self,
         operation: dto::DbOperationDto,
     ) -> impl std::future::Future<Output = fdo::Result<dto::DbResultDtoPacked>>;
+
+    /// Emitted when a task is created or edited
+    fn task_changed(
+        emitter: &SignalEmitter<'_>,
+        task: dto::TaskDto,
+    ) -> impl std::future::Future<Output = zbus::Result<()>>;
+    /// Emitted when a task is deleted
+    fn task_deleted(
+        emitter: &SignalEmitter<'_>,
+        task_id: String,
+    ) -> impl std::future::Future<Output = zbus::Result<()>>;
+    /// Emitted when the running task starts, stops or is edited
+    fn current_task_changed(
+        emitter: &SignalEmitter<'_>,
+        task: Option<dto::TaskDto>,
+    ) -> impl std::future::Future<Output = zbus::Result<()>>;
+    /// Emitted when the focused window changes
+    fn activity_recorded(
+        emitter: &SignalEmitter<'_>,
+        activity: dto::ActivityDto,
+    ) -> impl std::future::Future<Output = zbus::Result<()>>;
+    /// Emitted when a task kept running while the user was away
+    fn afk_detected(
+        emitter: &SignalEmitter<'_>,
+        period: dto::AfkPeriodDto,
+    ) -> impl std::future::Future<Output = zbus::Result<()>>;
 }
@@ -12,7 +12,18 @@ use zbus::fdo;
 )]
 pub trait O324Service {
     async fn start_new_task(&self, input: dto::StartTaskInputDto) -> fdo::Result<dto::TaskDto>;
-    async fn stop_current_task(&self) -> fdo::Result<Option<dto::TaskDto>>;
+    async fn add_task(
+        &self,
+        input: dto::AddTaskInputDto,
+        overlap: dto::OverlapStrategyDto,
+    ) -> fdo::Result<dto::TaskDto>;
+    async fn split_task(
+        &self,
+        task_id: String,
+        input: dto::SplitTaskInputDto,
+    ) -> fdo::Result<dto::TaskDto>;
+    async fn merge_tasks(&self, task_ids: Vec<String>) -> fdo::Result<dto::TaskDto>;
+    async fn stop_current_task(&self, at: Option<u64>) -> fdo::Result<Option<dto::TaskDto>>;
     async fn cancel_current_task(&self) -> fdo::Result<Option<dto::TaskDto>>;
     async fn delete_task(&self, task_id: String) -> fdo::Result<Option<dto::TaskDto>>;
     async fn get_task_by_prefix(&self, task_ref: String)
@@ -28,7 +39,31 @@ pub trait O324Service {
         start_timestamp: u64,
         end_timestamp: u64,
     ) -> fdo::Result<Vec<dto::TaskDto>>;
+    async fn get_pending_afk(&self) -> fdo::Result<Option<dto::AfkPeriodDto>>;
+    async fn resolve_afk(
+        &self,
+        resolution: dto::AfkResolutionDto,
+    ) -> fdo::Result<Option<dto::AfkPeriodDto>>;
+    async fn check_integrity(
+        &self,
+        max_task_duration_secs: u64,
+    ) -> fdo::Result<Vec<dto::IntegrityIssueDto>>;
+    async fn fix_integrity_issues(
+        &self,
+        issue_ids: Vec<String>,
+    ) -> fdo::Result<Vec<dto::IntegrityIssueDto>>;
+    async fn list_conflicts(&self) -> fdo::Result<Vec<dto::TaskConflictDto>>;
+    async fn resolve_conflict(
+        &self,
+        conflict_id: String,
+        resolution: dto::ConflictResolutionDto,
+    ) -> fdo::Result<dto::TaskDto>;
+    async fn undo(&self) -> fdo::Result<Option<dto::HistoryEntryDto>>;
+    async fn redo(&self) -> fdo::Result<Option<dto::HistoryEntryDto>>;
     async fn ping(&self) -> fdo::Result<String>;
+    async fn sync(&self) -> fdo::Result<u64>;
+    async fn unlock(&self, passphrase: String) -> fdo::Result<()>;
+    async fn get_lock_state(&self) -> fdo::Result<dto::LockStateDto>;
     async fn db_query(&self, operation: dto::DbOperationDto)
         -> fdo::Result<dto::DbResultDtoPacked>;
     async fn list_activity_range(
@@ -36,4 +71,24 @@ pub trait O324Service {
         start_timestamp: u64,
         end_timestamp: u64,
     ) -> fdo::Result<Vec<dto::ActivityDto>>;
+
+    /// Emitted when a task is created or updated
+    #[zbus(signal)]
+    fn task_changed(&self, task: dto::TaskDto) -> zbus::Result<()>;
+
+    /// Emitted when a task is deleted
+    #[zbus(signal)]
+    fn task_deleted(&self, task_id: String) -> zbus::Result<()>;
+
+    /// Emitted when the running task starts, stops or is edited
+    #[zbus(signal)]
+    fn current_task_changed(&self, task: Option<dto::TaskDto>) -> zbus::Result<()>;
+
+    /// Emitted when the focused window changes
+    #[zbus(signal)]
+    fn activity_recorded(&self, activity: dto::ActivityDto) -> zbus::Result<()>;
+
+    /// Emitted when a task kept running while the user was away
+    #[zbus(signal)]
+    fn afk_detected(&self, period: dto::AfkPeriodDto) -> zbus::Result<()>;
 }
@@ -1,2 +1,3 @@
 pub mod wayland_backend;
+pub mod wayland_idle_backend;
 pub mod x11;
@@ -14,6 +14,7 @@ pub mod nest {
         unused_imports,
         static_mut_refs
     )]
+    #![allow(clippy::all)]
 
     use smallvec::smallvec;
     use wayland_client::{
@@ -0,0 +1,161 @@
+use crate::{IdleEvent, WindowTrackerError};
+use std::cell::Cell;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use wayland_client::{protocol::wl_seat::WlSeat, Display, EventQueue, GlobalManager, Main};
+
+pub mod nest {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(
+        non_upper_case_globals,
+        non_snake_case,
+        unused_imports,
+        static_mut_refs
+    )]
+    #![allow(clippy::all)]
+
+    use smallvec::smallvec;
+    use wayland_client::{
+        protocol::{wl_output, wl_seat, wl_surface},
+        AnonymousObject, Main, Proxy, ProxyMap,
+    };
+    use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+    use wayland_commons::{
+        map::{Object, ObjectMetadata},
+        Interface, MessageGroup,
+    };
+    use wayland_sys as sys;
+
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/wayland_protocols/ext_idle_notify_v1.rs"
+    ));
+}
+
+use nest::ext_idle_notification_v1::Event as NotificationEvent;
+use nest::ext_idle_notifier_v1::ExtIdleNotifierV1;
+
+/// Longest wait for an event before checking whether the receiver is gone.
+const DISPATCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Idle detection through the ext-idle-notify-v1 protocol.
+pub struct WaylandIdleBackend {
+    _display: Display,
+    event_queue: EventQueue,
+    notifier: Main<ExtIdleNotifierV1>,
+    seat: Main<WlSeat>,
+}
+
+impl WaylandIdleBackend {
+    pub fn new() -> Result<Self, WindowTrackerError> {
+        let display = Display::connect_to_env()?;
+        let mut event_queue = display.create_event_queue();
+        let attached_display = (*display).clone().attach(event_queue.token());
+        let globals = GlobalManager::new(&attached_display);
+
+        event_queue
+            .sync_roundtrip(&mut (), |_, _, _| {})
+            .map_err(|e| WindowTrackerError::WaylandConnection(e.to_string()))?;
+
+        let notifier = globals
+            .instantiate_exact::<ExtIdleNotifierV1>(1)
+            .map_err(|_| {
+                WindowTrackerError::WaylandProtocolMissing(
+                    "ext-idle-notify-v1 not found. Does your compositor support idle notifications?"
+                        .to_string(),
+                )
+            })?;
+
+        let seat = globals
+            .instantiate_range::<WlSeat>(1, 7)
+            .map_err(|_| WindowTrackerError::WaylandProtocolMissing("wl_seat".to_string()))?;
+
+        Ok(Self {
+            _display: display,
+            event_queue,
+            notifier,
+            seat,
+        })
+    }
+
+    /// Forwards idle notifications to `sender`, blocking the current thread
+    /// until the receiving end is dropped. The receiver is checked at least
+    /// every `DISPATCH_TIMEOUT`.
+    pub fn run(
+        mut self,
+        timeout: Duration,
+        sender: mpsc::Sender<IdleEvent>,
+    ) -> Result<(), WindowTrackerError> {
+        let closed = Rc::new(Cell::new(false));
+
+        let notification = self
+            .notifier
+            .get_idle_notification(timeout.as_millis() as u32, &self.seat);
+
+        notification.quick_assign({
+            let closed = closed.clone();
+            let sender = sender.clone();
+            move |_notification, event, _| {
+                let event = match event {
+                    NotificationEvent::Idled => IdleEvent::Idle { idle_for: timeout },
+                    NotificationEvent::Resumed => IdleEvent::Resumed,
+                };
+
+                if sender.blocking_send(event).is_err() {
+                    closed.set(true);
+                }
+            }
+        });
+
+        while !closed.get() && !sender.is_closed() {
+            self.dispatch_timeout(DISPATCH_TIMEOUT)
+                .map_err(|e| WindowTrackerError::WaylandConnection(e.to_string()))?;
+        }
+
+        notification.destroy();
+        Ok(())
+    }
+
+    /// Dispatches the pending events, waiting at most `timeout` for new ones.
+    fn dispatch_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.event_queue.dispatch_pending(&mut (), |_, _, _| {})?;
+
+        // Events were queued meanwhile, they are dispatched on the next call
+        let Some(guard) = self.event_queue.prepare_read() else {
+            return Ok(());
+        };
+        match self.event_queue.display().flush() {
+            Err(e) if e.kind() != io::ErrorKind::WouldBlock => return Err(e),
+            _ => (),
+        }
+
+        let mut fds = [libc::pollfd {
+            fd: self.event_queue.display().get_connection_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        }];
+        let timeout_ms = timeout.as_millis().try_into().unwrap_or(libc::c_int::MAX);
+        // SAFETY: `fds` is a valid array of one pollfd for the duration of the call
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), 1, timeout_ms) };
+        match ready {
+            0 => return Ok(()),
+            ..0 => {
+                let e = io::Error::last_os_error();
+                return match e.kind() {
+                    io::ErrorKind::Interrupted => Ok(()),
+                    _ => Err(e),
+                };
+            }
+            _ => (),
+        }
+
+        match guard.read_events() {
+            Err(e) if e.kind() != io::ErrorKind::WouldBlock => return Err(e),
+            _ => (),
+        }
+        self.event_queue.dispatch_pending(&mut (), |_, _, _| {})?;
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use once_cell::sync::Lazy;
 use std::sync::Arc;
-use x11rb::connection::Connection;
+use std::time::Duration;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::screensaver::{self, ConnectionExt as _};
 use x11rb::protocol::xproto::{self, ConnectionExt, Window};
 use x11rb::rust_connection::RustConnection;
 
@@ -65,11 +67,34 @@ pub struct X11Backend {
 impl X11Backend {
     pub fn try_new() -> Result<Self, WindowTrackerError> {
         let (conn, screen_num) = X11_CONNECTION.as_ref().map_err(Clone::clone)?.clone();
+        Self::from_connection(conn, screen_num)
+    }
+
+    pub fn from_connection(
+        conn: Arc<RustConnection>,
+        screen_num: usize,
+    ) -> Result<Self, WindowTrackerError> {
         let atoms = X11Atoms::intern_all(&*conn)?;
         let root = conn.setup().roots[screen_num].root;
         Ok(Self { conn, atoms, root })
     }
 
+    /// Time elapsed since the last user input, as reported by the MIT-SCREEN-SAVER extension.
+    pub fn get_idle_time(&self) -> Result<Duration, WindowTrackerError> {
+        if self
+            .conn
+            .extension_information(screensaver::X11_EXTENSION_NAME)?
+            .is_none()
+        {
+            return Err(WindowTrackerError::NotAvailable(
+                "MIT-SCREEN-SAVER extension".to_string(),
+            ));
+        }
+
+        let info = self.conn.screensaver_query_info(self.root)?.reply()?;
+        Ok(Duration::from_millis(info.ms_since_user_input as u64))
+    }
+
     pub fn get_window_info(
         &self,
         window: Window,
@@ -18,4 +18,10 @@ fn main() {
         generated_dir.join("wlr_foreign_toplevel_management.rs"),
         Side::Client,
     );
+
+    generate_code(
+        protocols_dir.join("ext-idle-notify-v1.xml"),
+        generated_dir.join("ext_idle_notify_v1.rs"),
+        Side::Client,
+    );
 }
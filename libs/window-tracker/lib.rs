@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use thiserror::Error;
-use tracing::error;
 use x11rb::rust_connection::{ConnectError, ConnectionError, ReplyError};
 
 pub mod backends;
@@ -10,7 +10,9 @@ pub mod utils;
 
 use backends::x11::X11InitError;
 
-use crate::providers::{fht::FhtProvider, wayland::WaylandProvider, x11::X11Provider};
+use crate::providers::{
+    fht::FhtProvider, wayland::WaylandProvider, wayland_idle::WaylandIdleProvider, x11::X11Provider,
+};
 
 #[derive(Error, Debug)]
 #[allow(dead_code)]
@@ -93,6 +95,13 @@ impl Compositor {
             Compositor::X11 => Ok(Box::new(X11Provider::try_new()?)),
         }
     }
+
+    pub fn try_into_idle_provider(&self) -> Result<Box<dyn IdleProvider>, WindowTrackerError> {
+        match self {
+            Compositor::Wayland | Compositor::Fht => Ok(Box::new(WaylandIdleProvider::try_new()?)),
+            Compositor::X11 => Ok(Box::new(X11Provider::try_new_idle()?)),
+        }
+    }
 }
 
 #[async_trait]
@@ -115,6 +124,23 @@ pub enum WindowEvent {
     WindowTitleChanged(WindowInfo),
 }
 
+/// Notifies when the user stops interacting with the computer and when they come back.
+#[async_trait]
+pub trait IdleProvider: Send + Sync {
+    async fn start_idle_monitoring(
+        &self,
+        timeout: Duration,
+    ) -> Result<tokio::sync::mpsc::Receiver<IdleEvent>, WindowTrackerError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// No input was received for `idle_for`
+    Idle { idle_for: Duration },
+    /// Input was received after an `Idle` event
+    Resumed,
+}
+
 pub struct WindowTracker {
     provider: Box<dyn WindowProvider>,
 }
@@ -170,3 +196,38 @@ impl WindowTracker {
         self.provider.get_compositor()
     }
 }
+
+pub struct IdleTracker {
+    provider: Box<dyn IdleProvider>,
+}
+
+fn find_idle_provider() -> Result<Box<dyn IdleProvider>, WindowTrackerError> {
+    // Wayland first, an X11 server may also be reachable through XWayland
+    let providers_to_try = [Compositor::Wayland, Compositor::X11];
+
+    for compositor in providers_to_try {
+        match compositor.try_into_idle_provider() {
+            Ok(provider) => return Ok(provider),
+            Err(e) => {
+                tracing::debug!("skipping idle provider {compositor:?} due to error: {e:?}");
+            }
+        }
+    }
+
+    Err(WindowTrackerError::UnsupportedDisplayServer)
+}
+
+impl IdleTracker {
+    pub fn try_new() -> Result<Self, WindowTrackerError> {
+        Ok(Self {
+            provider: find_idle_provider()?,
+        })
+    }
+
+    pub async fn start_monitoring(
+        &self,
+        timeout: Duration,
+    ) -> Result<tokio::sync::mpsc::Receiver<IdleEvent>, WindowTrackerError> {
+        self.provider.start_idle_monitoring(timeout).await
+    }
+}
@@ -1,3 +1,4 @@
 pub mod fht;
 pub mod wayland;
+pub mod wayland_idle;
 pub mod x11;
@@ -0,0 +1,55 @@
+use crate::backends::wayland_idle_backend::WaylandIdleBackend;
+use crate::{IdleEvent, IdleProvider, WindowTrackerError};
+use async_trait::async_trait;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// An idle provider for Wayland compositors implementing ext-idle-notify-v1.
+pub struct WaylandIdleProvider;
+
+impl WaylandIdleProvider {
+    pub fn try_new() -> Result<Self, WindowTrackerError> {
+        // Make sure the compositor exposes the protocol before accepting the provider
+        WaylandIdleBackend::new()?;
+        Ok(Self)
+    }
+}
+
+#[async_trait]
+impl IdleProvider for WaylandIdleProvider {
+    async fn start_idle_monitoring(
+        &self,
+        timeout: Duration,
+    ) -> Result<mpsc::Receiver<IdleEvent>, WindowTrackerError> {
+        let (tx, rx) = mpsc::channel(16);
+        let (init_sender, init_receiver) = oneshot::channel();
+
+        // Wayland objects are bound to the thread dispatching their events
+        thread::spawn(move || {
+            let backend = match WaylandIdleBackend::new() {
+                Ok(b) => {
+                    let _ = init_sender.send(Ok(()));
+                    b
+                }
+                Err(e) => {
+                    let _ = init_sender.send(Err(e));
+                    return;
+                }
+            };
+
+            match backend.run(timeout, tx) {
+                Ok(()) => tracing::info!("Idle monitoring finished."),
+                Err(e) => tracing::error!("Idle monitoring failed: {e}"),
+            }
+        });
+
+        match init_receiver.await {
+            Ok(Ok(())) => Ok(rx),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(WindowTrackerError::CommandFailed(
+                "Wayland idle thread panicked during initialization".to_string(),
+            )),
+        }
+    }
+}
@@ -1,7 +1,16 @@
 use crate::backends::x11::X11Backend;
-use crate::{Compositor, WindowEvent, WindowInfo, WindowProvider, WindowTrackerError};
+use crate::{
+    Compositor, IdleEvent, IdleProvider, WindowEvent, WindowInfo, WindowProvider,
+    WindowTrackerError,
+};
 use async_trait::async_trait;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Interval between two idle time queries.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Minimum delay between two logs of a failing idle time query.
+const IDLE_ERROR_LOG_INTERVAL: Duration = Duration::from_secs(60);
 
 /// A window provider for generic X11 environments using EWMH.
 /// This is often a fallback for desktop environments that don't have a specific provider.
@@ -16,6 +25,13 @@ impl X11Provider {
             backend: X11Backend::try_new()?,
         })
     }
+
+    /// Creates a new X11Provider, failing if the server can't report idle time.
+    pub fn try_new_idle() -> Result<Self, WindowTrackerError> {
+        let provider = Self::try_new()?;
+        provider.backend.get_idle_time()?;
+        Ok(provider)
+    }
 }
 
 #[async_trait]
@@ -68,3 +84,110 @@ impl WindowProvider for X11Provider {
         Compositor::X11
     }
 }
+
+#[async_trait]
+impl IdleProvider for X11Provider {
+    /// Note: This is a polling-based implementation.
+    async fn start_idle_monitoring(
+        &self,
+        timeout: Duration,
+    ) -> Result<tokio::sync::mpsc::Receiver<IdleEvent>, WindowTrackerError> {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let backend = self.backend.clone();
+
+        tokio::spawn(async move {
+            let mut is_idle = false;
+            let mut last_error_log: Option<Instant> = None;
+            loop {
+                // Stop polling once the monitor is dropped, e.g. on a restart
+                if tx.is_closed() {
+                    break;
+                }
+
+                match backend.get_idle_time() {
+                    Ok(idle_for) => {
+                        last_error_log = None;
+                        let event = match (is_idle, idle_for >= timeout) {
+                            (false, true) => Some(IdleEvent::Idle { idle_for }),
+                            (true, false) => Some(IdleEvent::Resumed),
+                            _ => None,
+                        };
+
+                        if let Some(event) = event {
+                            is_idle = !is_idle;
+                            if tx.send(event).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        // The query fails every second while the server misbehaves
+                        if last_error_log.is_none_or(|at| at.elapsed() >= IDLE_ERROR_LOG_INTERVAL) {
+                            tracing::error!("Error polling for idle time: {}", e);
+                            last_error_log = Some(Instant::now());
+                        }
+                    }
+                }
+                tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command};
+    use std::sync::Arc;
+
+    /// An Xvfb server killed on drop.
+    struct Xvfb(Child);
+
+    impl Drop for Xvfb {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// Starts Xvfb on a free display.
+    fn connect_to_xvfb() -> (Xvfb, X11Backend) {
+        let display = format!(":{}", 90 + std::process::id() % 100);
+        let child = Command::new("Xvfb")
+            .arg(&display)
+            .spawn()
+            .expect("Xvfb should be installed");
+        let xvfb = Xvfb(child);
+
+        for _ in 0..50 {
+            if let Ok((conn, screen_num)) = x11rb::connect(Some(&display)) {
+                let backend = X11Backend::from_connection(Arc::new(conn), screen_num).unwrap();
+                return (xvfb, backend);
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        panic!("Xvfb didn't start on display {display}");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires Xvfb, run with `cargo test -- --ignored`"]
+    async fn test_idle_event_without_input() {
+        let (_xvfb, backend) = connect_to_xvfb();
+
+        let provider = X11Provider { backend };
+        let mut events = provider
+            .start_idle_monitoring(Duration::from_millis(500))
+            .await
+            .unwrap();
+
+        // Nobody is typing on a virtual framebuffer
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("no idle event received");
+        assert!(
+            matches!(event, Some(IdleEvent::Idle { idle_for }) if idle_for >= Duration::from_millis(500))
+        );
+    }
+}